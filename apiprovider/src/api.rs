@@ -6,9 +6,13 @@ use std::{hash::Hash, rc::Rc, sync::Arc};
 use common::{
     internal::{self, login, logout, INTERNAL_SEGMENT},
     objects,
-    public::{self, scaffold, userinfo, PUBLIC_SEGMENT},
+    public::{self, batch, scaffold, userinfo, PUBLIC_SEGMENT},
     APIError, APIOutcome, APIResult,
 };
+use gloo::{
+    net::eventsource::futures::{EventSource, EventSourceSubscription},
+    utils::errors::JsError,
+};
 use reqwest::{header::COOKIE, Client, StatusCode, Url};
 use serde::{de::DeserializeOwned, Serialize};
 use yew::prelude::*;
@@ -22,7 +26,7 @@ pub struct LinkdokuAPI {
     client: Arc<Client>,
     base: AttrValue,
     login: Option<AttrValue>,
-    cache: Rc<ObjectCache>,
+    pub(crate) cache: Rc<ObjectCache>,
 }
 
 impl PartialEq for LinkdokuAPI {
@@ -67,6 +71,7 @@ impl LinkdokuAPI {
         Url::parse(&combined).expect("Unable to construct API URL?")
     }
 
+    #[cfg(feature = "ssr")]
     fn compute_basic_uri_by_uuid(&self, kind: &str, uuid: &str) -> Url {
         let combined = format!(
             "{}api{}/{}/by-uuid/{}",
@@ -148,6 +153,32 @@ impl LinkdokuAPI {
         outcome.into()
     }
 
+    /// Make an API call whose response is stored, if the object cache is
+    /// persisted, so that it can still be given when the backend cannot be
+    /// reached
+    async fn make_stored_api_call<IN, OUT>(
+        &self,
+        name: &str,
+        api: Url,
+        query_params: impl IntoIterator<Item = (&str, &str)>,
+        body: Option<IN>,
+    ) -> APIResult<OUT>
+    where
+        IN: Serialize + std::fmt::Debug,
+        OUT: Serialize + DeserializeOwned + std::fmt::Debug,
+    {
+        match self.make_api_call(api, query_params, body).await {
+            Ok(response) => {
+                self.cache.store(name, &response);
+                Ok(response)
+            }
+            Err(APIError::ClientIssue(e)) => {
+                self.cache.stored(name).ok_or(APIError::ClientIssue(e))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn login_providers(&self) -> APIResult<login::providers::Response> {
         let uri = self.compute_uri(INTERNAL_SEGMENT, login::providers::URI);
@@ -177,7 +208,9 @@ impl LinkdokuAPI {
             error: error.map(String::from),
         };
         let uri = self.compute_uri(INTERNAL_SEGMENT, login::complete::URI);
-        self.make_api_call(uri, None, Some(body)).await
+        let response: login::complete::Response = self.make_api_call(uri, None, Some(body)).await?;
+        self.cache.change_identity(Some(&response.userinfo.uuid));
+        Ok(response)
     }
 
     #[tracing::instrument(skip(self))]
@@ -195,9 +228,14 @@ impl LinkdokuAPI {
     #[tracing::instrument(skip(self))]
     pub async fn logout(&self) -> APIResult<logout::Response> {
         let uri = self.compute_uri(INTERNAL_SEGMENT, logout::URI);
-        self.make_api_call(uri, None, EMPTY_BODY).await
+        let response: logout::Response = self.make_api_call(uri, None, EMPTY_BODY).await?;
+        self.cache.change_identity(None);
+        Ok(response)
     }
 
+    /// Fetch a single object by UUID, which is only done when rendering on
+    /// the server, since the browser fetches objects in batches
+    #[cfg(feature = "ssr")]
     #[tracing::instrument(skip(self))]
     pub(crate) async fn get_generic_obj<T: DeserializeOwned + std::fmt::Debug>(
         &self,
@@ -208,6 +246,17 @@ impl LinkdokuAPI {
         self.make_api_call(uri, None, NO_BODY).await
     }
 
+    #[tracing::instrument(skip(self, uuids))]
+    pub(crate) async fn get_generic_batch<T: DeserializeOwned + std::fmt::Debug>(
+        &self,
+        kind: &str,
+        uuids: Vec<String>,
+    ) -> APIResult<batch::Response<T>> {
+        let uri = self.compute_uri(PUBLIC_SEGMENT, &batch::uri(kind));
+        let req = batch::Request { uuids };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
     #[tracing::instrument(skip(self))]
     pub(crate) async fn get_generic_obj_by_name<T: DeserializeOwned + std::fmt::Debug>(
         &self,
@@ -240,16 +289,33 @@ impl LinkdokuAPI {
         .await
     }
 
+    /// List a page of a role's puzzles, restricted to those with every one
+    /// of the given tags
     #[tracing::instrument(skip_all)]
     pub async fn published_puzzle_list(
         &self,
         role_uuid: impl Into<String>,
+        page: usize,
+        sort: public::role::puzzles::PuzzleSort,
+        tags: &[String],
     ) -> APIResult<public::role::puzzles::Response> {
         let uri = self.compute_uri(PUBLIC_SEGMENT, public::role::puzzles::URI);
         let req = public::role::puzzles::Request {
             uuid: role_uuid.into(),
+            page,
+            sort,
+            tags: tags.to_vec(),
         };
-        let ret: public::role::puzzles::Response = self.make_api_call(uri, None, Some(req)).await?;
+        let name = format!(
+            "role-puzzles/{}/{:?}/{}/{}",
+            req.uuid,
+            req.sort,
+            req.tags.join(","),
+            req.page
+        );
+        let ret: public::role::puzzles::Response = self
+            .make_stored_api_call(&name, uri, None, Some(req))
+            .await?;
 
         for pm in &ret.puzzles {
             self.cache.insert(&pm.uuid, Rc::new(Ok(pm.clone())));
@@ -264,21 +330,17 @@ impl LinkdokuAPI {
         owner: impl Into<String>,
         short_name: impl Into<String>,
         display_name: impl Into<String>,
-        description: impl Into<String>,
-        data: &objects::PuzzleData,
+        initial_state: &objects::PuzzleState,
+        tags: &[String],
     ) -> APIResult<public::puzzle::create::Response> {
         let uri = self.compute_uri(PUBLIC_SEGMENT, public::puzzle::create::URI);
         let req = public::puzzle::create::Request {
             owner: owner.into(),
             display_name: display_name.into(),
             short_name: short_name.into(),
-            initial_state: objects::PuzzleState {
-                uuid: "".into(), // Ignored, but hey
-                description: description.into(),
-                visibility: objects::Visibility::Restricted, // Ignored, but hey
-                updated_at: "".into(),                       // Ignored, but hey
-                data: data.clone(),
-            },
+            // The UUID, visibility, and updated_at of the initial state are ignored
+            initial_state: initial_state.clone(),
+            tags: tags.to_vec(),
         };
         self.make_api_call(uri, None, Some(req)).await
     }
@@ -408,12 +470,265 @@ impl LinkdokuAPI {
     }
 
     #[tracing::instrument(skip_all)]
-    pub async fn try_expand_tinyurl(
+    pub async fn tags_by_name(&self, names: &[&str]) -> APIResult<public::tag::by_name::Response> {
+        let uri = self.compute_uri(PUBLIC_SEGMENT, public::tag::by_name::URI);
+        let req = public::tag::by_name::Request {
+            names: names.iter().map(|s| s.to_string()).collect(),
+        };
+        let tags: public::tag::by_name::Response = self.make_api_call(uri, None, Some(req)).await?;
+
+        for tag in &tags.tags {
+            self.cache.insert(&tag.uuid, Rc::new(Ok(tag.clone())));
+        }
+
+        Ok(tags)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn expand_url(
+        &self,
+        url: impl Into<String>,
+    ) -> APIResult<internal::util::expand_url::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::util::expand_url::URI);
+        let req = internal::util::expand_url::Request { url: url.into() };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn list_tokens(&self) -> APIResult<internal::token::list::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::token::list::URI);
+        self.make_api_call(uri, None, NO_BODY).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn create_token(
+        &self,
+        name: impl Into<String>,
+        scope: internal::token::TokenScope,
+    ) -> APIResult<internal::token::create::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::token::create::URI);
+        let req = internal::token::create::Request {
+            name: name.into(),
+            scope,
+        };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn revoke_token(
+        &self,
+        uuid: impl Into<String>,
+    ) -> APIResult<internal::token::revoke::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::token::revoke::URI);
+        let req = internal::token::revoke::Request { uuid: uuid.into() };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_overview(&self) -> APIResult<internal::admin::overview::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::admin::overview::URI);
+        self.make_api_call(uri, None, NO_BODY).await
+    }
+
+    /// Tag changes come back as the full list of tags, which refreshes the
+    /// cached copies of any tags which were changed
+    async fn admin_tag_call<B: Serialize + std::fmt::Debug>(
+        &self,
+        func: &str,
+        body: B,
+    ) -> APIResult<internal::admin::TagList> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, func);
+        let tags: internal::admin::TagList = self.make_api_call(uri, None, Some(body)).await?;
+
+        for tag in &tags.tags {
+            self.cache.insert(&tag.uuid, Rc::new(Ok(tag.clone())));
+        }
+
+        Ok(tags)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_create_tag(
+        &self,
+        name: impl Into<String>,
+        colour: impl Into<String>,
+        black_text: bool,
+        description: impl Into<String>,
+    ) -> APIResult<internal::admin::TagList> {
+        let req = internal::admin::tag::create::Request {
+            name: name.into(),
+            colour: colour.into(),
+            black_text,
+            description: description.into(),
+        };
+        self.admin_tag_call(internal::admin::tag::create::URI, req)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_update_tag(&self, tag: objects::Tag) -> APIResult<internal::admin::TagList> {
+        self.admin_tag_call(internal::admin::tag::update::URI, tag)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_delete_tag(
         &self,
-        slug: impl Into<String>,
-    ) -> APIResult<internal::util::expand_tinyurl::Response> {
-        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::util::expand_tinyurl::URI);
-        let req = internal::util::expand_tinyurl::Request { slug: slug.into() };
+        uuid: impl Into<String>,
+    ) -> APIResult<internal::admin::TagList> {
+        let req = internal::admin::tag::delete::Request { uuid: uuid.into() };
+        self.admin_tag_call(internal::admin::tag::delete::URI, req)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_merge_tags(
+        &self,
+        from: impl Into<String>,
+        into: impl Into<String>,
+    ) -> APIResult<internal::admin::TagList> {
+        let req = internal::admin::tag::merge::Request {
+            from: from.into(),
+            into: into.into(),
+        };
+        self.admin_tag_call(internal::admin::tag::merge::URI, req)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_hide_puzzle(
+        &self,
+        uuid: impl Into<String>,
+        hidden: bool,
+    ) -> APIResult<internal::admin::puzzle::hide::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::admin::puzzle::hide::URI);
+        let req = internal::admin::puzzle::hide::Request {
+            uuid: uuid.into(),
+            hidden,
+        };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_unpublish_puzzle(
+        &self,
+        uuid: impl Into<String>,
+    ) -> APIResult<internal::admin::puzzle::unpublish::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::admin::puzzle::unpublish::URI);
+        let req = internal::admin::puzzle::unpublish::Request { uuid: uuid.into() };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_lock_role(
+        &self,
+        uuid: impl Into<String>,
+        locked: bool,
+    ) -> APIResult<internal::admin::role::lock::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::admin::role::lock::URI);
+        let req = internal::admin::role::lock::Request {
+            uuid: uuid.into(),
+            locked,
+        };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_resolve_reports(
+        &self,
+        target: internal::report::ReportTarget,
+    ) -> APIResult<internal::admin::report::resolve::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::admin::report::resolve::URI);
+        let req = internal::admin::report::resolve::Request { target };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn admin_dismiss_reports(
+        &self,
+        target: internal::report::ReportTarget,
+    ) -> APIResult<internal::admin::report::dismiss::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::admin::report::dismiss::URI);
+        let req = internal::admin::report::dismiss::Request { target };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn file_report(
+        &self,
+        target: internal::report::ReportTarget,
+        reason: internal::report::ReportReason,
+        note: impl Into<String>,
+    ) -> APIResult<internal::report::file::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::report::file::URI);
+        let req = internal::report::file::Request {
+            target,
+            reason,
+            note: note.into(),
+        };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn followed_roles(&self) -> APIResult<internal::follow::list::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::follow::list::URI);
+        self.make_api_call(uri, None, NO_BODY).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn set_following(
+        &self,
+        role: impl Into<String>,
+        follow: bool,
+    ) -> APIResult<internal::follow::set::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::follow::set::URI);
+        let req = internal::follow::set::Request {
+            role: role.into(),
+            follow,
+        };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn notifications(
+        &self,
+        page: usize,
+    ) -> APIResult<internal::notification::list::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::notification::list::URI);
+        let req = internal::notification::list::Request { page };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn unread_notifications(
+        &self,
+    ) -> APIResult<internal::notification::unread::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::notification::unread::URI);
+        self.make_api_call(uri, None, NO_BODY).await
+    }
+
+    /// Mark the given notifications as read, or all of them if none are given
+    #[tracing::instrument(skip_all)]
+    pub async fn mark_notifications_read(
+        &self,
+        uuids: Option<Vec<String>>,
+    ) -> APIResult<internal::notification::mark_read::Response> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::notification::mark_read::URI);
+        let req = internal::notification::mark_read::Request { uuids };
+        self.make_api_call(uri, None, Some(req)).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn check_solution(
+        &self,
+        state: impl Into<String>,
+        grid: Vec<Option<u8>>,
+    ) -> APIResult<public::puzzle::check_solution::Response> {
+        let uri = self.compute_uri(PUBLIC_SEGMENT, public::puzzle::check_solution::URI);
+        let req = public::puzzle::check_solution::Request {
+            state: state.into(),
+            grid,
+        };
         self.make_api_call(uri, None, Some(req)).await
     }
 
@@ -422,8 +737,9 @@ impl LinkdokuAPI {
         &self,
     ) -> APIResult<public::puzzle::recent_published::Response> {
         let uri = self.compute_uri(PUBLIC_SEGMENT, public::puzzle::recent_published::URI);
-        let response: public::puzzle::recent_published::Response =
-            self.make_api_call(uri, None, NO_BODY).await?;
+        let response: public::puzzle::recent_published::Response = self
+            .make_stored_api_call("recent-puzzles", uri, None, NO_BODY)
+            .await?;
 
         for pmeta in &response.puzzles {
             self.cache.insert(&pmeta.uuid, Rc::new(Ok(pmeta.clone())));
@@ -431,4 +747,13 @@ impl LinkdokuAPI {
 
         Ok(response)
     }
+
+    /// Open the stream of announced changes, which stays open for as long
+    /// as the returned event source is kept
+    pub(crate) fn follow_changes(&self) -> Result<(EventSource, EventSourceSubscription), JsError> {
+        let uri = self.compute_uri(INTERNAL_SEGMENT, internal::changes::URI);
+        let mut source = EventSource::new(uri.as_str())?;
+        let changes = source.subscribe("message")?;
+        Ok((source, changes))
+    }
 }
@@ -2,10 +2,11 @@
 
 use std::{rc::Rc, sync::Arc};
 
+use frontend_core::LinkdokuBase;
 use reqwest::Client;
 use yew::prelude::*;
 
-use crate::ObjectCache;
+use crate::{live::LiveUpdates, ObjectCache};
 
 #[derive(Clone)]
 pub(crate) struct APIContents {
@@ -22,21 +23,38 @@ impl PartialEq for APIContents {
 #[derive(Clone, Properties, PartialEq)]
 pub struct APIProviderProps {
     pub children: Children,
+    /// If given, cached objects are also kept in the browser's storage, for
+    /// as long as this version hash stays the same
+    #[prop_or_default]
+    pub persist_version: Option<AttrValue>,
 }
 
 #[function_component(LinkdokuAPIProvider)]
 pub fn core_client_provider(props: &APIProviderProps) -> Html {
-    let client = use_state(|| APIContents {
-        client: Arc::new(
-            Client::builder()
-                .build()
-                .expect("Unable to construct client"),
-        ),
-        cache: Rc::new(ObjectCache::new()),
+    // Stored copies are only for whoever the page was rendered for
+    let _identity = use_context::<LinkdokuBase>()
+        .and_then(|base| base.userinfo)
+        .map(|info| info.uuid);
+    let client = use_state(|| {
+        let cache = ObjectCache::new();
+        // There is no browser storage to persist to when rendering on the server
+        #[cfg(not(feature = "ssr"))]
+        if let Some(version) = &props.persist_version {
+            cache.persist(version, _identity.as_deref());
+        }
+        APIContents {
+            client: Arc::new(
+                Client::builder()
+                    .build()
+                    .expect("Unable to construct client"),
+            ),
+            cache: Rc::new(cache),
+        }
     });
 
     html! {
         <ContextProvider<APIContents> context={(*client).clone()}>
+            <LiveUpdates />
             { for props.children.iter() }
         </ContextProvider<APIContents>>
     }
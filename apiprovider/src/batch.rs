@@ -0,0 +1,73 @@
+//! Coalescing of object fetches
+//!
+//! Lists of puzzles, and the tags on them, each fetch their own objects.
+//! Rather than making a request for every one of them, all the fetches of a
+//! kind of object which are asked for in the same tick are made together as
+//! batch requests.
+
+use std::{collections::HashMap, time::Duration};
+
+use common::{public::batch::MAX_BATCH, APIError, APIResult};
+use tracing::trace;
+use yew::platform::{pinned::oneshot, spawn_local, time::sleep};
+
+use crate::{Cacheable, LinkdokuAPI};
+
+impl LinkdokuAPI {
+    /// Fetch an object by UUID, as part of a batch
+    pub(crate) async fn get_batched_obj<T: Cacheable>(&self, uuid: &str) -> APIResult<T> {
+        let (sender, receiver) = oneshot::channel();
+        let first = {
+            let mut waiting = self.cache.waiting_map::<T>().borrow_mut();
+            let first = waiting.is_empty();
+            waiting.entry(uuid.to_string()).or_default().push(sender);
+            first
+        };
+        if first {
+            let api = self.clone();
+            spawn_local(async move {
+                // Let everything else asked for in this tick join the batch
+                sleep(Duration::ZERO).await;
+                api.fetch_waiting::<T>().await;
+            });
+        }
+        receiver
+            .await
+            .unwrap_or_else(|_| Err(APIError::ClientIssue("Batched fetch was abandoned".into())))
+    }
+
+    async fn fetch_waiting<T: Cacheable>(&self) {
+        let waiting = std::mem::take(&mut *self.cache.waiting_map::<T>().borrow_mut());
+        let uuids: Vec<String> = waiting.keys().cloned().collect();
+        trace!("Fetching {} {} in a batch", uuids.len(), T::api_name());
+
+        let mut results = HashMap::new();
+        for chunk in uuids.chunks(MAX_BATCH) {
+            match self
+                .get_generic_batch::<T>(T::api_name(), chunk.to_vec())
+                .await
+            {
+                Ok(found) => {
+                    for obj in found.objects {
+                        results.insert(obj.uuid().to_string(), Ok(obj));
+                    }
+                }
+                Err(e) => {
+                    for uuid in chunk {
+                        results.insert(uuid.clone(), Err(e.clone()));
+                    }
+                }
+            }
+        }
+
+        for (uuid, senders) in waiting {
+            // Anything omitted from the batch cannot be seen
+            let result = results
+                .remove(&uuid)
+                .unwrap_or(Err(APIError::ObjectNotFound));
+            for sender in senders {
+                let _ = sender.send(result.clone());
+            }
+        }
+    }
+}
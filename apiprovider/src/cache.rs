@@ -1,18 +1,24 @@
 use std::{
-    cell::RefCell,
-    collections::{hash_map::Entry, HashMap},
+    cell::{Cell, RefCell},
+    collections::{hash_map::Entry, HashMap, HashSet},
     marker::PhantomData,
     rc::Rc,
     sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
 };
 
-use common::APIResult;
-use serde::{de::DeserializeOwned, Serialize};
+use common::{APIError, APIResult};
+use gloo::storage::{LocalStorage, Storage};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use state::TypeMap;
-use tracing::trace;
-use yew::{prelude::*, suspense::*};
+use tracing::{trace, warn};
+use yew::{
+    platform::{pinned::oneshot, spawn_local},
+    prelude::*,
+    suspense::*,
+};
 
-use crate::{backend::APIContents, use_apiprovider};
+use crate::{backend::APIContents, use_apiprovider, LinkdokuAPI};
 
 mod seal {
     pub trait Sealed {}
@@ -22,6 +28,7 @@ pub trait Cacheable:
     Serialize + DeserializeOwned + Clone + PartialEq + std::fmt::Debug + seal::Sealed + 'static
 {
     fn api_name() -> &'static str;
+    fn uuid(&self) -> &str;
 }
 
 macro_rules! cacheable {
@@ -31,6 +38,9 @@ macro_rules! cacheable {
             fn api_name() -> &'static str {
                 $name
             }
+            fn uuid(&self) -> &str {
+                &self.uuid
+            }
         }
     };
 }
@@ -78,19 +88,136 @@ impl<T: Cacheable> AsRef<APIResult<T>> for Cached<T> {
     }
 }
 
+/// How long stored copies of cached objects may be used for
+const STORED_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const STORAGE_PREFIX: &str = "linkdoku-cache/";
+const STORAGE_VERSION: &str = "linkdoku-cache-version";
+/// The identity whose view of things is stored, empty when logged out
+const STORAGE_IDENTITY: &str = "linkdoku-cache-identity";
+
+#[derive(Serialize, Deserialize)]
+struct Stored<V> {
+    /// Milliseconds since the epoch, as the browser counts them
+    stored_at: f64,
+    value: V,
+}
+
+impl<V> Stored<V> {
+    fn expired(&self) -> bool {
+        js_sys::Date::now() - self.stored_at > STORED_TTL.as_millis() as f64
+    }
+}
+
+fn stored_name<T: Cacheable>(key: &str) -> String {
+    format!("{}/{}", T::api_name(), key)
+}
+
+fn stored_identity() -> Option<String> {
+    LocalStorage::get(STORAGE_IDENTITY).ok()
+}
+
+/// Every key in local storage holding a stored copy
+fn stored_keys() -> Vec<String> {
+    let storage = LocalStorage::raw();
+    (0..storage.length().unwrap_or(0))
+        .filter_map(|n| storage.key(n).ok().flatten())
+        .filter(|key| key.starts_with(STORAGE_PREFIX))
+        .collect()
+}
+
 pub struct ObjectCache {
     next_listener: AtomicUsize,
     content: TypeMap![],
+    persist: Cell<bool>,
 }
 
 type CacheMap<T> = RefCell<HashMap<String, Rc<APIResult<T>>>>;
 type CallbackMap<T> = RefCell<HashMap<String, HashMap<usize, Callback<Rc<APIResult<T>>>>>>;
+pub(crate) type WaitingMap<T> = RefCell<HashMap<String, Vec<oneshot::Sender<APIResult<T>>>>>;
 
 impl ObjectCache {
     pub(crate) fn new() -> Self {
         Self {
             next_listener: Default::default(),
             content: Default::default(),
+            persist: Cell::new(false),
+        }
+    }
+
+    /// Keep copies of cached objects in the browser's local storage, so that
+    /// later visits can show them straight away, even when offline.
+    ///
+    /// Copies stored by any other version of Linkdoku, or for anyone other
+    /// than the given identity, are discarded, as are any older than
+    /// [`STORED_TTL`].
+    pub fn persist(&self, version_hash: &str, identity: Option<&str>) {
+        let same_version =
+            LocalStorage::get::<String>(STORAGE_VERSION).ok().as_deref() == Some(version_hash);
+        let same_identity = stored_identity().as_deref() == Some(identity.unwrap_or_default());
+        for key in stored_keys() {
+            let usable = same_version
+                && same_identity
+                && LocalStorage::get::<Stored<serde_json::Value>>(&key)
+                    .is_ok_and(|stored| !stored.expired());
+            if !usable {
+                LocalStorage::delete(&key);
+            }
+        }
+        match LocalStorage::set(STORAGE_VERSION, version_hash)
+            .and_then(|()| LocalStorage::set(STORAGE_IDENTITY, identity.unwrap_or_default()))
+        {
+            Ok(()) => self.persist.set(true),
+            Err(e) => warn!("Unable to persist the object cache: {e}"),
+        }
+    }
+
+    /// Discard every stored copy if someone else is now using the browser,
+    /// since what one person can see may be hidden from the next.  Logging
+    /// out counts as someone else, so nothing stored outlives a session.
+    pub(crate) fn change_identity(&self, identity: Option<&str>) {
+        let identity = identity.unwrap_or_default();
+        if !self.persist.get() || stored_identity().as_deref() == Some(identity) {
+            return;
+        }
+        for key in stored_keys() {
+            LocalStorage::delete(&key);
+        }
+        if let Err(e) = LocalStorage::set(STORAGE_IDENTITY, identity) {
+            warn!("Unable to record who the object cache is for, no longer persisting: {e}");
+            self.persist.set(false);
+        }
+    }
+
+    pub(crate) fn store<V: Serialize>(&self, name: &str, value: &V) {
+        if self.persist.get() {
+            let stored = Stored {
+                stored_at: js_sys::Date::now(),
+                value,
+            };
+            if let Err(e) = LocalStorage::set(format!("{STORAGE_PREFIX}{name}"), stored) {
+                warn!("Unable to store {name}: {e}");
+            }
+        }
+    }
+
+    pub(crate) fn stored<V: DeserializeOwned>(&self, name: &str) -> Option<V> {
+        if !self.persist.get() {
+            return None;
+        }
+        let key = format!("{STORAGE_PREFIX}{name}");
+        match LocalStorage::get::<Stored<V>>(&key) {
+            Ok(stored) if !stored.expired() => Some(stored.value),
+            Ok(_) => {
+                LocalStorage::delete(&key);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn unstore(&self, name: &str) {
+        if self.persist.get() {
+            LocalStorage::delete(format!("{STORAGE_PREFIX}{name}"));
         }
     }
 
@@ -111,7 +238,34 @@ impl ObjectCache {
             self.content.get()
         }
     }
+
+    /// Fetches which are waiting to be made as part of a batch
+    pub(crate) fn waiting_map<T: Cacheable>(&self) -> &WaitingMap<T> {
+        if let Some(value) = self.content.try_get() {
+            value
+        } else {
+            self.content.set(<WaitingMap<T>>::default());
+            self.content.get()
+        }
+    }
+
     pub fn insert<T: Cacheable>(&self, uuid: &str, value: Rc<APIResult<T>>) {
+        self.update(uuid, value, true)
+    }
+
+    /// Bring a stored copy of an object back into the cache, if there is one
+    fn restore<T: Cacheable>(&self, key: &str) -> bool {
+        match self.stored::<T>(&stored_name::<T>(key)) {
+            Some(value) => {
+                trace!("Restored {} {}", T::api_name(), key);
+                self.update(key, Rc::new(Ok(value)), false);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update<T: Cacheable>(&self, uuid: &str, value: Rc<APIResult<T>>, store: bool) {
         match self.cache_map().borrow_mut().entry(uuid.to_string()) {
             Entry::Occupied(mut o) => {
                 if o.get() == &value {
@@ -124,6 +278,14 @@ impl ObjectCache {
                 v.insert(value.clone());
             }
         };
+        if store {
+            // Failing to reach the backend says nothing about the object
+            match value.as_ref() {
+                Ok(obj) => self.store(&stored_name::<T>(uuid), obj),
+                Err(APIError::ClientIssue(_)) => {}
+                Err(_) => self.unstore(&stored_name::<T>(uuid)),
+            }
+        }
         let cbs = self.callback_map().borrow().get(uuid).cloned();
         for (_, cb) in cbs.iter().flatten() {
             cb.emit(value.clone())
@@ -157,6 +319,50 @@ impl ObjectCache {
         let mut cbmap = self.callback_map::<T>().borrow_mut();
         cbmap.get_mut(uuid).and_then(|map| map.remove(&entry));
     }
+
+    /// Deal with the object of the given UUID having changed, or with
+    /// anything at all having changed if there is no UUID.
+    ///
+    /// Entries which nothing is listening to are simply forgotten, so that
+    /// they are fetched afresh when next wanted.  The keys of those which are
+    /// listened to are returned, along with the UUID to fetch them again by.
+    pub(crate) fn invalidate<T: Cacheable>(&self, uuid: Option<&str>) -> Vec<(String, String)> {
+        let listened: HashSet<String> = self
+            .callback_map::<T>()
+            .borrow()
+            .iter()
+            .filter(|(_, cbs)| !cbs.is_empty())
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut refetch = vec![];
+        self.cache_map::<T>().borrow_mut().retain(|key, value| {
+            // Failed lookups by UUID are worth retrying, but failed lookups
+            // by name have no UUID to retry with
+            let found = match (value.as_ref(), uuid) {
+                (Ok(obj), _) => Some(obj.uuid()),
+                (Err(_), Some(uuid)) if key == uuid => Some(uuid),
+                (Err(_), _) => None,
+            };
+            let affected = match uuid {
+                Some(uuid) => key == uuid || found == Some(uuid),
+                None => true,
+            };
+            if !affected {
+                return true;
+            }
+            match found {
+                Some(found) if listened.contains(key) => {
+                    refetch.push((key.clone(), found.to_string()));
+                    true
+                }
+                _ => {
+                    self.unstore(&stored_name::<T>(key));
+                    false
+                }
+            }
+        });
+        refetch
+    }
 }
 
 struct ObjectCacheListener<T: Cacheable> {
@@ -184,6 +390,17 @@ pub enum CacheLookupKind {
     ByShortName,
 }
 
+async fn fetch_value<T: Cacheable>(
+    api: &LinkdokuAPI,
+    key: &str,
+    kind: CacheLookupKind,
+) -> APIResult<T> {
+    match kind {
+        CacheLookupKind::ByUUID => api.get_batched_obj::<T>(key).await,
+        CacheLookupKind::ByShortName => api.get_generic_obj_by_name::<T>(T::api_name(), key).await,
+    }
+}
+
 #[hook]
 pub fn use_cached_value_<T: Cacheable + 'static>(
     key: AttrValue,
@@ -268,15 +485,19 @@ pub fn use_cached_value_<T: Cacheable + 'static>(
                         cache: cache.clone(),
                         value,
                     }));
-                } else {
-                    let result = match kind {
-                        CacheLookupKind::ByUUID => {
-                            api.get_generic_obj::<T>(T::api_name(), &key).await
+                } else if cache.restore::<T>(&key) {
+                    // Stored copies are shown straight away, and then checked
+                    // with the backend, unless it cannot be reached
+                    spawn_local(async move {
+                        match fetch_value::<T>(&api, &key, kind).await {
+                            Err(APIError::ClientIssue(e)) => {
+                                warn!("Unable to check stored {} {}: {e}", T::api_name(), key)
+                            }
+                            result => controller.insert(&key, Rc::new(result)),
                         }
-                        CacheLookupKind::ByShortName => {
-                            api.get_generic_obj_by_name::<T>(T::api_name(), &key).await
-                        }
-                    };
+                    });
+                } else {
+                    let result = fetch_value::<T>(&api, &key, kind).await;
                     controller.insert(&key, Rc::new(result));
                 }
             }
@@ -8,7 +8,9 @@
 
 mod api;
 mod backend;
+mod batch;
 mod cache;
+mod live;
 
 pub use api::{use_apiprovider, LinkdokuAPI};
 pub use backend::{APIProviderProps, LinkdokuAPIProvider};
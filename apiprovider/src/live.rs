@@ -0,0 +1,90 @@
+//! Live updates of cached objects
+//!
+//! The backend announces changes to puzzles, roles, and tags as they are
+//! made.  Anything in the [`ObjectCache`] which is being listened to is
+//! fetched again straight away, so that it updates in place, and anything
+//! else which changed is forgotten.
+
+use std::rc::Rc;
+
+use common::{
+    internal::changes::Change,
+    objects::{Puzzle, PuzzleMetadata, Role, Tag},
+};
+use futures_util::{future::join_all, stream::StreamExt};
+use tracing::{trace, warn};
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::{use_apiprovider, Cacheable, LinkdokuAPI};
+
+async fn refresh<T: Cacheable>(api: &LinkdokuAPI, uuid: Option<&str>) {
+    let refetches = api
+        .cache
+        .invalidate::<T>(uuid)
+        .into_iter()
+        .map(|(key, uuid)| async move {
+            trace!("Refreshing {} {}", T::api_name(), key);
+            let value = api.get_batched_obj::<T>(&uuid).await;
+            api.cache.insert(&key, Rc::new(value));
+        });
+    // Refetching everything together lets it all be fetched in batches
+    join_all(refetches).await;
+}
+
+async fn apply(api: &LinkdokuAPI, change: &Change) {
+    match change {
+        Change::Puzzle(uuid) => {
+            refresh::<Puzzle>(api, Some(uuid)).await;
+            refresh::<PuzzleMetadata>(api, Some(uuid)).await;
+        }
+        Change::Role(uuid) => refresh::<Role>(api, Some(uuid)).await,
+        Change::Tag(uuid) => refresh::<Tag>(api, Some(uuid)).await,
+        Change::Connected | Change::Resync => {
+            refresh::<Puzzle>(api, None).await;
+            refresh::<PuzzleMetadata>(api, None).await;
+            refresh::<Role>(api, None).await;
+            refresh::<Tag>(api, None).await;
+        }
+    }
+}
+
+/// Follows the announced changes for as long as it is rendered
+#[function_component(LiveUpdates)]
+pub(crate) fn live_updates_render() -> Html {
+    let api = use_apiprovider();
+
+    use_effect_with_deps(
+        |api: &LinkdokuAPI| {
+            let source = match api.follow_changes() {
+                Ok((source, mut changes)) => {
+                    let api = api.clone();
+                    spawn_local(async move {
+                        // Nothing can have been missed before the first connection
+                        let mut connected = false;
+                        while let Some(Ok((_, message))) = changes.next().await {
+                            let change = message
+                                .data()
+                                .as_string()
+                                .and_then(|data| serde_json::from_str::<Change>(&data).ok());
+                            match change {
+                                Some(Change::Connected) if !connected => connected = true,
+                                Some(change) => apply(&api, &change).await,
+                                None => warn!("Unable to understand an announced change"),
+                            }
+                        }
+                        trace!("No longer following changes");
+                    });
+                    Some(source)
+                }
+                Err(e) => {
+                    warn!("Unable to follow changes: {e}");
+                    None
+                }
+            };
+            move || drop(source)
+        },
+        api,
+    );
+
+    html! {}
+}
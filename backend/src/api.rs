@@ -2,6 +2,7 @@
 //!
 
 use axum::{
+    extract::State,
     routing::{get, post},
     Json, Router,
 };
@@ -9,20 +10,29 @@ use common::{
     internal::{self, INTERNAL_SEGMENT},
     public::{
         scaffold::{self, hash_version_info},
+        v1::V1_SEGMENT,
         PUBLIC_SEGMENT,
     },
     APIError, APIResult,
 };
 use git_testament::git_testament;
-use puzzleutils::fpuzzles;
-use reqwest::{header::LOCATION, redirect::Policy, Client};
+use puzzleutils::{convert::convert, formats::Format, fpuzzles};
 
-use crate::{login::PrivateCookies, state::BackendState};
+use crate::{
+    import::resolve_link, login::PrivateCookies, shortener::Expander, state::BackendState,
+    sudokupad::PuzzleFetcher,
+};
 
+mod admin;
+mod changes;
+mod notification;
 mod objects;
 mod puzzle;
+mod report;
 mod role;
 mod tag;
+mod token;
+mod v1;
 
 git_testament!(VERSION);
 
@@ -33,39 +43,25 @@ async fn get_scaffold() -> APIResult<scaffold::Response> {
     })
 }
 
-async fn try_expand_tinyurl(
+async fn expand_url(
     cookies: PrivateCookies,
-    Json(req): Json<internal::util::expand_tinyurl::Request>,
-) -> APIResult<internal::util::expand_tinyurl::Response> {
+    State(expander): State<Expander>,
+    State(fetcher): State<PuzzleFetcher>,
+    Json(req): Json<internal::util::expand_url::Request>,
+) -> APIResult<internal::util::expand_url::Response> {
     let login_state = cookies.get_login_flow_status().await;
     if login_state.user().is_none() {
         return Err(APIError::PermissionDenied);
     }
 
-    let client = Client::builder()
-        .redirect(Policy::none())
-        .build()
-        .map_err(|e| APIError::Generic(format!("Unable to build HTTP client: {e}")))?;
-
-    let url = format!("https://tinyurl.com/{}", req.slug);
-
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| APIError::Generic(format!("Unable to run HTTP get: {e}")))?;
-
-    let url = response
-        .headers()
-        .get(LOCATION)
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| APIError::Generic("Did not get a redirect?".into()))?;
-
-    let fpuzz = fpuzzles::extract(url)
-        .ok_or_else(|| APIError::Generic("Unable to extract fpuzzles data".into()))?;
+    let (url, decoded) = resolve_link(&expander, &fetcher, &req.url).await?;
+    let conversion = convert(&decoded, Format::FPuzzles)
+        .map_err(|e| APIError::Generic(format!("Unable to convert puzzle: {e}")))?;
 
-    Ok(internal::util::expand_tinyurl::Response {
-        replacement: fpuzzles::encode(&fpuzz),
+    Ok(internal::util::expand_url::Response {
+        url,
+        replacement: fpuzzles::encode(&conversion.value),
+        unmapped: conversion.unmapped,
     })
 }
 
@@ -74,16 +70,18 @@ fn public_router() -> Router<BackendState> {
 }
 
 fn internal_router() -> Router<BackendState> {
-    Router::new().route(
-        internal::util::expand_tinyurl::URI,
-        post(try_expand_tinyurl),
-    )
+    Router::new().route(internal::util::expand_url::URI, post(expand_url))
 }
 
 pub fn router() -> Router<BackendState> {
     let internal = Router::new()
         .merge(internal_router())
-        .merge(crate::login::internal_router());
+        .merge(crate::login::internal_router())
+        .merge(token::internal_router())
+        .merge(admin::internal_router())
+        .merge(report::internal_router())
+        .merge(notification::internal_router())
+        .merge(changes::internal_router());
     let public = Router::new()
         .merge(public_router())
         .merge(crate::login::public_router())
@@ -95,4 +93,5 @@ pub fn router() -> Router<BackendState> {
     Router::new()
         .nest(INTERNAL_SEGMENT, internal)
         .nest(PUBLIC_SEGMENT, public)
+        .nest(V1_SEGMENT, v1::router())
 }
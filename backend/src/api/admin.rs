@@ -0,0 +1,166 @@
+//! Administration and moderation APIs
+
+use axum::{
+    routing::{get, post},
+    Json, Router,
+};
+use common::{internal::admin, APIError, APIResult};
+use database::{activity, Connection};
+use tracing::info;
+
+use crate::{login::PrivateCookies, state::BackendState};
+
+async fn overview(
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> APIResult<admin::overview::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    Ok(activity::admin::overview(&mut db, user).await?)
+}
+
+async fn create_tag(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::tag::create::Request>,
+) -> APIResult<admin::tag::create::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!("Administrator {user} creating tag {}", req.name);
+    Ok(activity::admin::create_tag(
+        &mut db,
+        user,
+        &req.name,
+        &req.colour,
+        req.black_text,
+        &req.description,
+    )
+    .await?)
+}
+
+async fn update_tag(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::tag::update::Request>,
+) -> APIResult<admin::tag::update::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!("Administrator {user} updating tag {}", req.uuid);
+    Ok(activity::admin::update_tag(&mut db, user, &req).await?)
+}
+
+async fn delete_tag(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::tag::delete::Request>,
+) -> APIResult<admin::tag::delete::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!("Administrator {user} deleting tag {}", req.uuid);
+    Ok(activity::admin::delete_tag(&mut db, user, &req.uuid).await?)
+}
+
+async fn merge_tags(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::tag::merge::Request>,
+) -> APIResult<admin::tag::merge::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!(
+        "Administrator {user} merging tag {} into {}",
+        req.from, req.into
+    );
+    Ok(activity::admin::merge_tags(&mut db, user, &req.from, &req.into).await?)
+}
+
+async fn hide_puzzle(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::puzzle::hide::Request>,
+) -> APIResult<admin::puzzle::hide::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!(
+        "Administrator {user} setting puzzle {} hidden to {}",
+        req.uuid, req.hidden
+    );
+    Ok(activity::admin::set_puzzle_hidden(&mut db, user, &req.uuid, req.hidden).await?)
+}
+
+async fn unpublish_puzzle(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::puzzle::unpublish::Request>,
+) -> APIResult<admin::puzzle::unpublish::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!("Administrator {user} unpublishing puzzle {}", req.uuid);
+    Ok(activity::admin::unpublish_puzzle(&mut db, user, &req.uuid).await?)
+}
+
+async fn lock_role(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::role::lock::Request>,
+) -> APIResult<admin::role::lock::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!(
+        "Administrator {user} setting role {} locked to {}",
+        req.uuid, req.locked
+    );
+    Ok(activity::admin::set_role_locked(&mut db, user, &req.uuid, req.locked).await?)
+}
+
+async fn resolve_reports(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::report::resolve::Request>,
+) -> APIResult<admin::report::resolve::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!(
+        "Administrator {user} resolving reports about {:?}",
+        req.target
+    );
+    Ok(activity::admin::close_reports(&mut db, user, &req.target, true).await?)
+}
+
+async fn dismiss_reports(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<admin::report::dismiss::Request>,
+) -> APIResult<admin::report::dismiss::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!(
+        "Administrator {user} dismissing reports about {:?}",
+        req.target
+    );
+    Ok(activity::admin::close_reports(&mut db, user, &req.target, false).await?)
+}
+
+pub fn internal_router() -> Router<BackendState> {
+    Router::new()
+        .route(admin::overview::URI, get(overview))
+        .route(admin::tag::create::URI, post(create_tag))
+        .route(admin::tag::update::URI, post(update_tag))
+        .route(admin::tag::delete::URI, post(delete_tag))
+        .route(admin::tag::merge::URI, post(merge_tags))
+        .route(admin::puzzle::hide::URI, post(hide_puzzle))
+        .route(admin::puzzle::unpublish::URI, post(unpublish_puzzle))
+        .route(admin::role::lock::URI, post(lock_role))
+        .route(admin::report::resolve::URI, post(resolve_reports))
+        .route(admin::report::dismiss::URI, post(dismiss_reports))
+}
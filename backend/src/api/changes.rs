@@ -0,0 +1,76 @@
+//! Live announcements of changes, as server-sent events
+//!
+//! Announcing a puzzle reveals its UUID, so each subscriber only hears
+//! about puzzles they are able to see.  Puzzles which everyone may see are
+//! passed straight on, and only restricted ones are checked per subscriber.
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::get,
+    Router,
+};
+use common::internal::changes::{self, Change};
+use database::{
+    changes::{subscribe, Audience, ChangeEvent},
+    models, Pool,
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{login::PrivateCookies, state::BackendState};
+
+/// May the subscriber hear about changes to this restricted puzzle?
+///
+/// Puzzles which are gone, or which we cannot check, are not announced
+/// since we can no longer tell who was allowed to see them.
+async fn may_see(pool: &Pool, user: Option<&str>, uuid: &str) -> bool {
+    let Ok(mut conn) = pool.get().await else {
+        return false;
+    };
+    match models::Puzzle::by_uuid(&mut conn, uuid).await {
+        Ok(Some(puzzle)) => puzzle.can_be_seen(&mut conn, user).await.unwrap_or(false),
+        _ => false,
+    }
+}
+
+async fn stream_changes(
+    cookies: PrivateCookies,
+    State(pool): State<Pool>,
+) -> Sse<impl Stream<Item = Result<Event, serde_json::Error>>> {
+    let user = cookies
+        .get_login_flow_status()
+        .await
+        .user_uuid()
+        .map(String::from);
+    let announced = stream::unfold(
+        (subscribe(), pool, user),
+        |(mut receiver, pool, user)| async move {
+            let change = loop {
+                match receiver.recv().await {
+                    Ok(ChangeEvent::Puzzle(uuid, audience)) => {
+                        if audience == Audience::Everyone
+                            || may_see(&pool, user.as_deref(), &uuid).await
+                        {
+                            break Change::Puzzle(uuid);
+                        }
+                    }
+                    Ok(ChangeEvent::Role(uuid)) => break Change::Role(uuid),
+                    Ok(ChangeEvent::Tag(uuid)) => break Change::Tag(uuid),
+                    Err(RecvError::Lagged(_)) => break Change::Resync,
+                    Err(RecvError::Closed) => return None,
+                }
+            };
+            Some((change, (receiver, pool, user)))
+        },
+    );
+    let events = stream::once(async { Change::Connected })
+        .chain(announced)
+        .map(|change| Event::default().json_data(change));
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+pub fn internal_router() -> Router<BackendState> {
+    Router::new().route(changes::URI, get(stream_changes))
+}
@@ -0,0 +1,77 @@
+//! Following roles and the notification inbox
+//!
+//! Notifications are for people, so API tokens may not read them or follow
+//! roles; only logged in browser sessions may.
+
+use axum::{
+    routing::{get, post},
+    Json, Router,
+};
+use common::{
+    internal::{follow, notification},
+    APIError, APIResult,
+};
+use database::{activity, Connection};
+
+use crate::{login::PrivateCookies, state::BackendState};
+
+async fn list_follows(
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> APIResult<follow::list::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+    Ok(follow::list::Response {
+        roles: activity::notification::followed_roles(&mut db, user).await?,
+    })
+}
+
+async fn set_follow(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<follow::set::Request>,
+) -> APIResult<follow::set::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+    Ok(follow::list::Response {
+        roles: activity::notification::follow(&mut db, user, &req.role, req.follow).await?,
+    })
+}
+
+async fn list_notifications(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<notification::list::Request>,
+) -> APIResult<notification::list::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+    Ok(activity::notification::list(&mut db, user, req.page).await?)
+}
+
+async fn unread_notifications(
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> APIResult<notification::unread::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+    Ok(activity::notification::unread(&mut db, user).await?)
+}
+
+async fn mark_notifications_read(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<notification::mark_read::Request>,
+) -> APIResult<notification::mark_read::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+    Ok(activity::notification::mark_read(&mut db, user, req.uuids.as_deref()).await?)
+}
+
+pub fn internal_router() -> Router<BackendState> {
+    Router::new()
+        .route(follow::list::URI, get(list_follows))
+        .route(follow::set::URI, post(set_follow))
+        .route(notification::list::URI, post(list_notifications))
+        .route(notification::unread::URI, get(unread_notifications))
+        .route(notification::mark_read::URI, post(mark_notifications_read))
+}
@@ -2,8 +2,16 @@
 
 // These are always rooted at PUBLIC_SEGMENT
 
-use axum::{extract::Path, routing::get, Router};
-use common::{objects, APIError, APIResult};
+use axum::{
+    extract::Path,
+    routing::{get, post},
+    Json, Router,
+};
+use common::{
+    objects,
+    public::batch::{self, MAX_BATCH},
+    APIError, APIResult,
+};
 use database::{activity, models, Connection};
 use time::format_description::well_known::Iso8601;
 
@@ -30,13 +38,17 @@ async fn get_role_(item: &str, mut db: Connection, is_name: bool) -> APIResult<o
         .transpose()
         .unwrap_or(Err(APIError::ObjectNotFound))?;
 
-    Ok(objects::Role {
+    Ok(role_object(role))
+}
+
+fn role_object(role: models::Role) -> objects::Role {
+    objects::Role {
         uuid: role.uuid,
         owner: role.owner,
         short_name: role.short_name,
         display_name: role.display_name,
         description: role.description,
-    })
+    }
 }
 
 async fn get_puzzle(
@@ -72,13 +84,17 @@ async fn get_tag(Path(uuid): Path<String>, mut db: Connection) -> APIResult<obje
         .transpose()
         .unwrap_or(Err(APIError::ObjectNotFound))?;
 
-    Ok(objects::Tag {
+    Ok(tag_object(tag))
+}
+
+fn tag_object(tag: models::Tag) -> objects::Tag {
+    objects::Tag {
         uuid: tag.uuid,
         name: tag.name,
         colour: tag.colour,
         black_text: tag.black_text,
         description: tag.description,
-    })
+    }
 }
 
 async fn get_puzzle_metadata(
@@ -102,6 +118,10 @@ async fn get_puzzle_metadata(
         return Err(APIError::ObjectNotFound);
     }
 
+    puzzle_metadata_object(puzzle)
+}
+
+fn puzzle_metadata_object(puzzle: models::Puzzle) -> APIResult<objects::PuzzleMetadata> {
     Ok(objects::PuzzleMetadata {
         uuid: puzzle.uuid,
         owner: puzzle.owner,
@@ -115,11 +135,114 @@ async fn get_puzzle_metadata(
     })
 }
 
+fn check_batch(req: &batch::Request) -> APIResult<()> {
+    if req.uuids.len() > MAX_BATCH {
+        Err(APIError::BadInput)
+    } else {
+        Ok(())
+    }
+}
+
+/// Of the given puzzles, those which exist and which the user may see
+async fn visible_puzzles(
+    db: &mut Connection,
+    user: Option<&str>,
+    uuids: &[String],
+) -> APIResult<Vec<models::Puzzle>> {
+    let puzzles = models::Puzzle::by_uuids(db, uuids)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?;
+    let mut visible = vec![];
+    for puzzle in puzzles {
+        if puzzle
+            .can_be_seen(db, user)
+            .await
+            .map_err(|e| APIError::DatabaseError(e.to_string()))?
+        {
+            visible.push(puzzle);
+        }
+    }
+    Ok(visible)
+}
+
+async fn batch_roles(
+    mut db: Connection,
+    Json(req): Json<batch::Request>,
+) -> APIResult<batch::Response<objects::Role>> {
+    check_batch(&req)?;
+    let roles = models::Role::by_uuids(&mut db, &req.uuids)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?;
+
+    Ok(batch::Response {
+        objects: roles.into_iter().map(role_object).collect(),
+    })
+}
+
+async fn batch_puzzles(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<batch::Request>,
+) -> APIResult<batch::Response<objects::Puzzle>> {
+    check_batch(&req)?;
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.user_uuid();
+
+    let mut objects = vec![];
+    for puzzle in visible_puzzles(&mut db, user, &req.uuids).await? {
+        objects.push(activity::puzzle::into_api_object(&mut db, user, puzzle).await?);
+    }
+
+    Ok(batch::Response { objects })
+}
+
+async fn batch_tags(
+    mut db: Connection,
+    Json(req): Json<batch::Request>,
+) -> APIResult<batch::Response<objects::Tag>> {
+    check_batch(&req)?;
+    // Tags are cached by the database layer, so fetching them one at a time
+    // is cheap
+    let mut objects = vec![];
+    for uuid in &req.uuids {
+        if let Some(tag) = models::Tag::by_uuid(&mut db, uuid)
+            .await
+            .map_err(|e| APIError::DatabaseError(e.to_string()))?
+        {
+            objects.push(tag_object(tag));
+        }
+    }
+
+    Ok(batch::Response { objects })
+}
+
+async fn batch_puzzle_metadata(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<batch::Request>,
+) -> APIResult<batch::Response<objects::PuzzleMetadata>> {
+    check_batch(&req)?;
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.user_uuid();
+
+    Ok(batch::Response {
+        objects: visible_puzzles(&mut db, user, &req.uuids)
+            .await?
+            .into_iter()
+            .map(puzzle_metadata_object)
+            .collect::<APIResult<_>>()?,
+    })
+}
+
 pub fn public_router() -> Router<BackendState> {
     Router::new()
         .route("/role/by-uuid/:uuid", get(get_role_by_uuid))
         .route("/role/by-name/:uuid", get(get_role_by_name))
         .route("/puzzle/by-uuid/:uuid", get(get_puzzle))
         .route("/tag/by-uuid/:uuid", get(get_tag))
-        .route("/puzzle-metadata/by-uuid/:uuid)", get(get_puzzle_metadata))
+        .route("/puzzle-metadata/by-uuid/:uuid", get(get_puzzle_metadata))
+        .route(&batch::uri("role"), post(batch_roles))
+        .route(&batch::uri("puzzle"), post(batch_puzzles))
+        .route(&batch::uri("tag"), post(batch_tags))
+        .route(&batch::uri("puzzle-metadata"), post(batch_puzzle_metadata))
 }
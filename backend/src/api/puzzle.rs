@@ -1,15 +1,56 @@
 //! Puzzle APIs
 
+use std::net::SocketAddr;
+
 use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
     routing::{get, post},
     Json, Router,
 };
-use common::{clean_short_name, objects, public::puzzle, APIError, APIResult};
-use database::{activity, Connection};
+use common::{
+    clean_short_name,
+    objects::{self, PuzzleData, SolutionCheck},
+    public::puzzle,
+    APIError, APIResult,
+};
+use database::{activity, models, Connection};
+use puzzleutils::{ctc, fpuzzles};
 use time::format_description::well_known::Iso8601;
 use tracing::info;
 
-use crate::{login::PrivateCookies, state::BackendState};
+use crate::{
+    config::ConfigState,
+    import,
+    login::{LoginFlowStatus, PrivateCookies},
+    ratelimit::{anonymous_key, RateLimits},
+    shortener::Expander,
+    state::BackendState,
+    sudokupad::PuzzleFetcher,
+};
+
+/// The most puzzles which may be imported, or restored, in one request
+pub(super) const MAX_IMPORT: usize = 100;
+
+/// API tokens may only change puzzles belonging to the role in their scope
+async fn check_token_scope(
+    db: &mut Connection,
+    flow: &LoginFlowStatus,
+    puzzle: &str,
+) -> APIResult<()> {
+    if flow.token_scope().is_none() {
+        return Ok(());
+    }
+    let puzzle = models::Puzzle::by_uuid(db, puzzle)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?
+        .ok_or(APIError::ObjectNotFound)?;
+    if flow.may_change(&puzzle.owner) {
+        Ok(())
+    } else {
+        Err(APIError::PermissionDenied)
+    }
+}
 
 async fn create_puzzle(
     mut db: Connection,
@@ -17,6 +58,9 @@ async fn create_puzzle(
     Json(req): Json<puzzle::create::Request>,
 ) -> APIResult<puzzle::create::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    if !logged_in.may_change(&req.owner) {
+        return Err(APIError::PermissionDenied);
+    }
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -42,6 +86,7 @@ async fn create_puzzle(
         &short_name,
         &req.display_name,
         &req.initial_state,
+        &req.tags,
     )
     .await?;
 
@@ -70,6 +115,7 @@ async fn update_puzzle_metadata(
     Json(req): Json<puzzle::update_metadata::Request>,
 ) -> APIResult<puzzle::update_metadata::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    check_token_scope(&mut db, &logged_in, &req.puzzle).await?;
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -99,6 +145,7 @@ async fn update_puzzle_state(
     Json(req): Json<puzzle::update_state::Request>,
 ) -> APIResult<puzzle::update_state::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    check_token_scope(&mut db, &logged_in, &req.puzzle).await?;
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -125,6 +172,7 @@ async fn add_puzzle_state(
     Json(req): Json<puzzle::add_state::Request>,
 ) -> APIResult<puzzle::add_state::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    check_token_scope(&mut db, &logged_in, &req.puzzle).await?;
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -147,6 +195,7 @@ async fn set_puzzle_visibility(
     Json(req): Json<puzzle::set_visibility::Request>,
 ) -> APIResult<puzzle::set_visibility::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    check_token_scope(&mut db, &logged_in, &req.puzzle).await?;
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -174,6 +223,7 @@ async fn set_puzzle_state_visibility(
     Json(req): Json<puzzle::set_state_visibility::Request>,
 ) -> APIResult<puzzle::set_state_visibility::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    check_token_scope(&mut db, &logged_in, &req.puzzle).await?;
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -201,6 +251,7 @@ async fn edit_puzzle_tags(
     Json(req): Json<puzzle::edit_tags::Request>,
 ) -> APIResult<puzzle::edit_tags::Response> {
     let logged_in = cookies.get_login_flow_status().await;
+    check_token_scope(&mut db, &logged_in, &req.puzzle).await?;
     let logged_in = match logged_in.user() {
         Some(data) => data,
         None => {
@@ -254,6 +305,108 @@ async fn get_puzzle_recent_published(
     Ok(puzzle::recent_published::Response { puzzles })
 }
 
+async fn check_puzzle_solution(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    State(limits): State<RateLimits>,
+    State(config): State<ConfigState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(req): Json<puzzle::check_solution::Request>,
+) -> APIResult<puzzle::check_solution::Response> {
+    let logged_in = cookies.get_login_flow_status().await;
+    let requester = match logged_in.user_uuid() {
+        Some(uuid) => uuid.to_string(),
+        None => anonymous_key(&headers, config.forwarded_for_header.as_deref(), addr),
+    };
+
+    if !limits.check_solution.check(&requester) {
+        return Err(APIError::RateLimited);
+    }
+
+    let state =
+        activity::puzzle::state_for_checking(&mut db, logged_in.user_uuid(), &req.state).await?;
+
+    let mode: SolutionCheck = state.solution_check.into();
+    if mode == SolutionCheck::Disabled {
+        return Err(APIError::PermissionDenied);
+    }
+
+    let data: PuzzleData =
+        serde_json::from_str(&state.data).map_err(|e| APIError::Generic(e.to_string()))?;
+    let solution = match &data {
+        PuzzleData::FPuzzles(value) => fpuzzles::solution(value).or_else(|| ctc::solution(value)),
+        _ => None,
+    }
+    // A solution without a single known digit would pass any grid
+    .filter(|solution| solution.iter().any(|&digit| digit != 0))
+    .ok_or(APIError::NoSolutionAvailable)?;
+
+    let wrong = puzzleutils::count_wrong_cells(&solution, &req.grid).ok_or(APIError::BadInput)?;
+
+    // Note: we never return anything derived from the solution beyond the count
+    Ok(match (wrong, mode) {
+        (0, _) => puzzle::check_solution::Response::Correct,
+        (wrong, SolutionCheck::CountWrong) => puzzle::check_solution::Response::WrongCells(wrong),
+        _ => puzzle::check_solution::Response::Incorrect,
+    })
+}
+
+async fn import_puzzles(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    State(expander): State<Expander>,
+    State(fetcher): State<PuzzleFetcher>,
+    State(limits): State<RateLimits>,
+    Json(req): Json<puzzle::import::Request>,
+) -> APIResult<puzzle::import::Response> {
+    let logged_in = cookies.get_login_flow_status().await;
+    if !logged_in.may_change(&req.owner) {
+        return Err(APIError::PermissionDenied);
+    }
+    let user = logged_in.user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    if !limits.import.check(user) {
+        return Err(APIError::RateLimited);
+    }
+
+    if req.manifest.puzzles.is_empty() || req.manifest.puzzles.len() > MAX_IMPORT {
+        return Err(APIError::BadInput);
+    }
+
+    // Check the role up front, rather than failing every row for the same reason
+    let role = models::Role::by_uuid(&mut db, &req.owner)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?
+        .ok_or(APIError::ObjectNotFound)?;
+    if !role
+        .can_modify(&mut db, user)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?
+    {
+        return Err(APIError::PermissionDenied);
+    }
+
+    info!(
+        "Importing {} puzzle(s) into {}",
+        req.manifest.puzzles.len(),
+        role.uuid
+    );
+
+    let rows = import::import(
+        &mut db,
+        &expander,
+        &fetcher,
+        user,
+        &role.uuid,
+        &req.manifest,
+        req.dry_run,
+    )
+    .await;
+
+    Ok(puzzle::import::Response { rows })
+}
+
 pub fn public_router() -> Router<BackendState> {
     Router::new()
         .route(puzzle::create::URI, post(create_puzzle))
@@ -271,4 +424,6 @@ pub fn public_router() -> Router<BackendState> {
             puzzle::recent_published::URI,
             get(get_puzzle_recent_published),
         )
+        .route(puzzle::check_solution::URI, post(check_puzzle_solution))
+        .route(puzzle::import::URI, post(import_puzzles))
 }
@@ -0,0 +1,30 @@
+//! Content reporting
+
+use axum::{extract::State, routing::post, Json, Router};
+use common::{internal::report, APIError, APIResult};
+use database::{activity, Connection};
+use tracing::info;
+
+use crate::{login::PrivateCookies, ratelimit::RateLimits, state::BackendState};
+
+async fn file_report(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    State(limits): State<RateLimits>,
+    Json(req): Json<report::file::Request>,
+) -> APIResult<report::file::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    // Reports come from people, so tokens may not file them
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    if !limits.report.check(user) {
+        return Err(APIError::RateLimited);
+    }
+
+    info!("{user} reporting {:?} for {:?}", req.target, req.reason);
+    Ok(activity::report::file(&mut db, user, &req.target, req.reason, &req.note).await?)
+}
+
+pub fn internal_router() -> Router<BackendState> {
+    Router::new().route(report::file::URI, post(file_report))
+}
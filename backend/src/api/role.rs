@@ -1,7 +1,15 @@
 //! Role APIs such as updating/creating them
 
 use axum::{routing::post, Json, Router};
-use common::{clean_short_name, objects, public, APIError, APIResult, BadShortNameReason};
+use common::{
+    archive::ARCHIVE_FORMAT,
+    clean_short_name, objects,
+    public::{
+        self,
+        role::puzzles::{MAX_TAGS, PAGE_SIZE},
+    },
+    APIError, APIResult, BadShortNameReason,
+};
 use database::{
     activity::{self},
     models, Connection,
@@ -9,9 +17,23 @@ use database::{
 use time::format_description::well_known::Iso8601;
 use tracing::info;
 
-use crate::{login::PrivateCookies, state::BackendState};
-
-const RESERVED_ROLE_NAMES: &[&str] = &["puzzle", "role", "settings", "linkdoku"];
+use crate::{api::puzzle::MAX_IMPORT, login::PrivateCookies, state::BackendState};
+
+/// Short names which would clash with top level pages.  Adding to these
+/// needs a migration to rename any roles which already use the name.
+const RESERVED_ROLE_NAMES: &[&str] = &[
+    "puzzle",
+    "role",
+    "settings",
+    "admin",
+    "linkdoku",
+    "api",
+    "assets",
+    "sitemap",
+    "embed",
+    "oembed",
+    "notifications",
+];
 
 async fn update_role(
     mut db: Connection,
@@ -24,6 +46,9 @@ async fn update_role(
     } else {
         return Err(APIError::PermissionDenied);
     };
+    if !flow.may_change(&req.uuid) {
+        return Err(APIError::PermissionDenied);
+    }
 
     let mut role = models::Role::by_uuid(&mut db, &req.uuid)
         .await
@@ -55,6 +80,15 @@ async fn role_puzzles(
     cookies: PrivateCookies,
     Json(req): Json<public::role::puzzles::Request>,
 ) -> APIResult<public::role::puzzles::Response> {
+    if req.tags.len() > MAX_TAGS {
+        return Err(APIError::BadInput);
+    }
+    let offset = req
+        .page
+        .checked_mul(PAGE_SIZE)
+        .and_then(|offset| i64::try_from(offset).ok())
+        .ok_or(APIError::BadInput)?;
+
     let role = models::Role::by_uuid(&mut db, &req.uuid)
         .await
         .map_err(|e| APIError::DatabaseError(e.to_string()))?
@@ -65,23 +99,19 @@ async fn role_puzzles(
     let logged_in = cookies.get_login_flow_status().await;
     let user = logged_in.user_uuid();
 
-    let puzzles = role
-        .visible_puzzles(&mut db, user)
+    // Anything which visible_puzzle_page returns can be seen by the user, so
+    // there is no need to check each puzzle, which would upset the paging
+    let (puzzles, total) = role
+        .visible_puzzle_page(&mut db, user, req.sort, &req.tags, offset, PAGE_SIZE as i64)
         .await
         .map_err(|e| APIError::DatabaseError(e.to_string()))?;
 
-    info!("Found {} puzzles", puzzles.len());
+    info!("Found {} of {total} puzzles", puzzles.len());
 
-    let mut ret = vec![];
-
-    for puzzle in puzzles {
-        match puzzle
-            .can_be_seen(&mut db, user)
-            .await
-            .map_err(|e| APIError::DatabaseError(e.to_string()))
-        {
-            Err(e) => return Err(e),
-            Ok(true) => ret.push(objects::PuzzleMetadata {
+    let puzzles = puzzles
+        .into_iter()
+        .map(|puzzle| {
+            Ok(objects::PuzzleMetadata {
                 uuid: puzzle.uuid,
                 owner: puzzle.owner,
                 display_name: puzzle.display_name,
@@ -91,18 +121,58 @@ async fn role_puzzles(
                     .updated_at
                     .format(&Iso8601::DEFAULT)
                     .map_err(|e| APIError::Generic(e.to_string()))?,
-            }),
-            Ok(false) => {}
-        }
+            })
+        })
+        .collect::<APIResult<_>>()?;
+
+    Ok(public::role::puzzles::Response {
+        puzzles,
+        total: total as usize,
+    })
+}
+
+async fn export_role(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<public::role::export::Request>,
+) -> APIResult<public::role::export::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    info!("Exporting puzzles belonging to {}", req.uuid);
+
+    Ok(activity::archive::export(&mut db, user, &req.uuid).await?)
+}
+
+async fn restore_role(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<public::role::restore::Request>,
+) -> APIResult<public::role::restore::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.user_uuid().ok_or(APIError::PermissionDenied)?;
+    if !flow.may_change(&req.uuid) {
+        return Err(APIError::PermissionDenied);
+    }
+    if req.archive.format > ARCHIVE_FORMAT || req.archive.puzzles.len() > MAX_IMPORT {
+        return Err(APIError::BadInput);
     }
 
-    info!("Calling user can see {} of them", ret.len());
+    info!(
+        "Restoring {} puzzles from {} into {}",
+        req.archive.puzzles.len(),
+        req.archive.role.uuid,
+        req.uuid
+    );
 
-    Ok(public::role::puzzles::Response { puzzles: ret })
+    let puzzles = activity::archive::restore(&mut db, user, &req.uuid, &req.archive).await?;
+    Ok(public::role::restore::Response { puzzles })
 }
 
 pub fn public_router() -> Router<BackendState> {
     Router::new()
         .route(public::role::update::URI, post(update_role))
         .route(public::role::puzzles::URI, post(role_puzzles))
+        .route(public::role::export::URI, post(export_role))
+        .route(public::role::restore::URI, post(restore_role))
 }
@@ -16,6 +16,17 @@ async fn list_tags(
     Ok(public::tag::list::Response { tags })
 }
 
+async fn tags_by_name(
+    mut db: Connection,
+    Json(req): Json<public::tag::by_name::Request>,
+) -> APIResult<public::tag::by_name::Response> {
+    let tags = activity::tag::by_names(&mut db, &req.names).await?;
+
+    Ok(public::tag::by_name::Response { tags })
+}
+
 pub fn public_router() -> Router<BackendState> {
-    Router::new().route(public::tag::list::URI, post(list_tags))
+    Router::new()
+        .route(public::tag::list::URI, post(list_tags))
+        .route(public::tag::by_name::URI, post(tags_by_name))
 }
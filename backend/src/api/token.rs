@@ -0,0 +1,55 @@
+//! Personal API token management
+//!
+//! Tokens can only be managed from a logged in browser session, so that a
+//! leaked token cannot be used to make more of them.
+
+use axum::{
+    routing::{get, post},
+    Json, Router,
+};
+use common::{internal::token, APIError, APIResult};
+use database::{activity, Connection};
+
+use crate::{login::PrivateCookies, state::BackendState};
+
+async fn list_tokens(
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> APIResult<token::list::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    let tokens = activity::token::list(&mut db, user).await?;
+    Ok(token::list::Response { tokens })
+}
+
+async fn create_token(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<token::create::Request>,
+) -> APIResult<token::create::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    let (token, secret) = activity::token::create(&mut db, user, &req.name, &req.scope).await?;
+    Ok(token::create::Response { token, secret })
+}
+
+async fn revoke_token(
+    mut db: Connection,
+    cookies: PrivateCookies,
+    Json(req): Json<token::revoke::Request>,
+) -> APIResult<token::revoke::Response> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.session_user_uuid().ok_or(APIError::PermissionDenied)?;
+
+    let tokens = activity::token::revoke(&mut db, user, &req.uuid).await?;
+    Ok(token::revoke::Response { tokens })
+}
+
+pub fn internal_router() -> Router<BackendState> {
+    Router::new()
+        .route(token::list::URI, get(list_tokens))
+        .route(token::create::URI, post(create_token))
+        .route(token::revoke::URI, post(revoke_token))
+}
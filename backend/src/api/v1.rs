@@ -0,0 +1,341 @@
+//! Version 1 of the public REST API
+//!
+//! The paths and response types live in [`common::public::v1`], this is
+//! where they are served from, along with the OpenAPI document for them.
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use common::{
+    objects,
+    public::v1::{self, ErrorBody, PuzzleList, SearchQuery, SearchResults, TagList, TagQuery},
+    APIError, BadShortNameReason,
+};
+use database::{activity, models, Connection};
+use time::format_description::well_known::Iso8601;
+use utoipa::OpenApi;
+
+use crate::{login::PrivateCookies, state::BackendState};
+
+/// The most results of each kind a search returns
+const SEARCH_LIMIT: i64 = 20;
+
+/// Errors from the v1 API carry a proper HTTP status code
+pub struct V1Error(APIError);
+
+impl<E: Into<APIError>> From<E> for V1Error {
+    fn from(e: E) -> Self {
+        V1Error(e.into())
+    }
+}
+
+impl IntoResponse for V1Error {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(v1::status_code(&self.0))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(ErrorBody::from(self.0))).into_response()
+    }
+}
+
+type V1Result<T> = Result<Json<T>, V1Error>;
+
+fn db_error(e: impl ToString) -> APIError {
+    APIError::DatabaseError(e.to_string())
+}
+
+/// Invisible things are reported as missing so as not to leak their existence
+fn hide_denied(e: impl Into<APIError>) -> APIError {
+    match e.into() {
+        APIError::PermissionDenied => APIError::ObjectNotFound,
+        e => e,
+    }
+}
+
+fn role_object(role: models::Role) -> objects::Role {
+    objects::Role {
+        uuid: role.uuid,
+        owner: role.owner,
+        short_name: role.short_name,
+        display_name: role.display_name,
+        description: role.description,
+    }
+}
+
+fn metadata_object(puzzle: models::Puzzle) -> Result<objects::PuzzleMetadata, APIError> {
+    Ok(objects::PuzzleMetadata {
+        updated_at: puzzle
+            .updated_at
+            .format(&Iso8601::DEFAULT)
+            .map_err(|e| APIError::Generic(e.to_string()))?,
+        uuid: puzzle.uuid,
+        owner: puzzle.owner,
+        display_name: puzzle.display_name,
+        short_name: puzzle.short_name,
+        visibility: puzzle.visibility.into(),
+    })
+}
+
+/// Roles may be named by UUID or by short name
+async fn find_role(db: &mut Connection, role: &str) -> Result<models::Role, APIError> {
+    if let Some(found) = models::Role::by_uuid(db, role).await.map_err(db_error)? {
+        return Ok(found);
+    }
+    models::Role::by_short_name(db, role)
+        .await
+        .map_err(db_error)?
+        .ok_or(APIError::ObjectNotFound)
+}
+
+#[utoipa::path(
+    get,
+    path = "/roles/{role}",
+    params(("role" = String, Path, description = "Role UUID or short name")),
+    responses(
+        (status = 200, description = "The role", body = objects::Role),
+        (status = 404, description = "No such role", body = ErrorBody),
+    ),
+)]
+async fn get_role(Path(role): Path<String>, mut db: Connection) -> V1Result<objects::Role> {
+    // Role data is always public, so there's no access control to be done here
+    Ok(Json(role_object(find_role(&mut db, &role).await?)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/roles/{role}/puzzles",
+    params(("role" = String, Path, description = "Role UUID or short name")),
+    responses(
+        (status = 200, description = "The role's puzzles which the caller can see", body = PuzzleList),
+        (status = 404, description = "No such role", body = ErrorBody),
+    ),
+)]
+async fn get_role_puzzles(
+    Path(role): Path<String>,
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> V1Result<PuzzleList> {
+    let role = find_role(&mut db, &role).await?;
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.user_uuid();
+
+    let visible = role
+        .visible_puzzles(&mut db, user)
+        .await
+        .map_err(db_error)?;
+    let mut puzzles = vec![];
+    for puzzle in visible {
+        if puzzle.can_be_seen(&mut db, user).await.map_err(db_error)? {
+            puzzles.push(metadata_object(puzzle)?);
+        }
+    }
+
+    Ok(Json(PuzzleList { puzzles }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/roles/{role}/puzzles/{puzzle}",
+    params(
+        ("role" = String, Path, description = "Role short name"),
+        ("puzzle" = String, Path, description = "Puzzle short name"),
+    ),
+    responses(
+        (status = 200, description = "The puzzle, with the states the caller can see", body = objects::Puzzle),
+        (status = 404, description = "No such puzzle", body = ErrorBody),
+    ),
+)]
+async fn get_role_puzzle(
+    Path((role, puzzle)): Path<(String, String)>,
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> V1Result<objects::Puzzle> {
+    let flow = cookies.get_login_flow_status().await;
+    let user = flow.user_uuid();
+    let uuid = activity::puzzle::lookup(&mut db, &role, &puzzle, user)
+        .await
+        .map_err(hide_denied)?;
+    puzzle_by_uuid(&mut db, user, &uuid).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/puzzles/{puzzle}",
+    params(("puzzle" = String, Path, description = "Puzzle UUID")),
+    responses(
+        (status = 200, description = "The puzzle, with the states the caller can see", body = objects::Puzzle),
+        (status = 404, description = "No such puzzle", body = ErrorBody),
+    ),
+)]
+async fn get_puzzle(
+    Path(puzzle): Path<String>,
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> V1Result<objects::Puzzle> {
+    let flow = cookies.get_login_flow_status().await;
+    puzzle_by_uuid(&mut db, flow.user_uuid(), &puzzle).await
+}
+
+async fn puzzle_by_uuid(
+    db: &mut Connection,
+    user: Option<&str>,
+    uuid: &str,
+) -> V1Result<objects::Puzzle> {
+    let puzzle = models::Puzzle::by_uuid(db, uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or(APIError::ObjectNotFound)?;
+    let puzzle = activity::puzzle::into_api_object(db, user, puzzle)
+        .await
+        .map_err(hide_denied)?;
+    Ok(Json(puzzle))
+}
+
+#[utoipa::path(
+    get,
+    path = "/states/{state}",
+    params(("state" = String, Path, description = "Puzzle state UUID")),
+    responses(
+        (status = 200, description = "The puzzle state", body = objects::PuzzleState),
+        (status = 404, description = "No such puzzle state", body = ErrorBody),
+    ),
+)]
+async fn get_state(
+    Path(state): Path<String>,
+    mut db: Connection,
+    cookies: PrivateCookies,
+) -> V1Result<objects::PuzzleState> {
+    let flow = cookies.get_login_flow_status().await;
+    let state = activity::puzzle::visible_state(&mut db, flow.user_uuid(), &state)
+        .await
+        .map_err(hide_denied)?;
+    Ok(Json(state))
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags",
+    params(TagQuery),
+    responses((status = 200, description = "Matching tags", body = TagList)),
+)]
+async fn get_tags(Query(query): Query<TagQuery>, mut db: Connection) -> V1Result<TagList> {
+    let tags = activity::tag::list(&mut db, &query.pattern).await?;
+    Ok(Json(TagList { tags }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/tags/{tag}",
+    params(("tag" = String, Path, description = "Tag UUID")),
+    responses(
+        (status = 200, description = "The tag", body = objects::Tag),
+        (status = 404, description = "No such tag", body = ErrorBody),
+    ),
+)]
+async fn get_tag(Path(tag): Path<String>, mut db: Connection) -> V1Result<objects::Tag> {
+    let tag = models::Tag::by_uuid(&mut db, &tag)
+        .await
+        .map_err(db_error)?
+        .ok_or(APIError::ObjectNotFound)?;
+    Ok(Json(objects::Tag {
+        uuid: tag.uuid,
+        name: tag.name,
+        colour: tag.colour,
+        black_text: tag.black_text,
+        description: tag.description,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Roles, published puzzles, and tags with matching names", body = SearchResults),
+        (status = 400, description = "Nothing to search for", body = ErrorBody),
+    ),
+)]
+async fn search(Query(query): Query<SearchQuery>, mut db: Connection) -> V1Result<SearchResults> {
+    let text = query.q.trim();
+    if text.is_empty() {
+        return Err(APIError::BadInput.into());
+    }
+
+    let roles = models::Role::search(&mut db, text, SEARCH_LIMIT)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(role_object)
+        .collect();
+    let puzzles = models::Puzzle::search_published(&mut db, text, SEARCH_LIMIT)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(metadata_object)
+        .collect::<Result<_, _>>()?;
+    let mut tags = activity::tag::list(&mut db, text).await?;
+    tags.truncate(SEARCH_LIMIT as usize);
+
+    Ok(Json(SearchResults {
+        roles,
+        puzzles,
+        tags,
+    }))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Linkdoku",
+        description = "Read-only access to the roles, puzzles, and tags on a Linkdoku instance"
+    ),
+    servers((url = "/api/v1")),
+    paths(
+        get_role,
+        get_role_puzzles,
+        get_role_puzzle,
+        get_puzzle,
+        get_state,
+        get_tags,
+        get_tag,
+        search
+    ),
+    components(schemas(
+        objects::Role,
+        objects::Puzzle,
+        objects::PuzzleState,
+        objects::PuzzleData,
+        objects::UrlEntry,
+        objects::Visibility,
+        objects::SolutionCheck,
+        objects::PuzzleMetadata,
+        objects::Tag,
+        PuzzleList,
+        TagList,
+        SearchResults,
+        ErrorBody,
+        APIError,
+        BadShortNameReason
+    ))
+)]
+struct ApiDoc;
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+pub fn router() -> Router<BackendState> {
+    Router::new()
+        .route(v1::ROLE, get(get_role))
+        .route(v1::ROLE_PUZZLES, get(get_role_puzzles))
+        .route(v1::ROLE_PUZZLE, get(get_role_puzzle))
+        .route(v1::PUZZLE, get(get_puzzle))
+        .route(v1::STATE, get(get_state))
+        .route(v1::TAGS, get(get_tags))
+        .route(v1::TAG, get(get_tag))
+        .route(v1::SEARCH, get(search))
+        .route(v1::OPENAPI, get(openapi))
+}
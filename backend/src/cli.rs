@@ -3,7 +3,7 @@
 
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tracing::info;
 
 #[derive(Parser, Debug, Clone)]
@@ -25,6 +25,54 @@ pub struct Cli {
     /// Perform a health check instead of becoming a server
     #[arg(long)]
     pub healthcheck: bool,
+
+    /// Maintenance command to run instead of becoming a server
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Add suggested variant tags to all existing puzzles
+    BackfillTags {
+        /// Only report what would be tagged, do not change anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import the puzzles listed in a JSON or YAML manifest into a role
+    Import {
+        /// The manifest, read as JSON if named `*.json` and YAML otherwise
+        manifest: PathBuf,
+        /// The UUID or short name of the role to import into
+        #[arg(long)]
+        role: String,
+        /// Only check the manifest, do not create anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export every puzzle of a role, including restricted states, as an archive
+    Export {
+        /// The UUID or short name of the role to export
+        role: String,
+        /// Where to write the archive
+        output: PathBuf,
+    },
+    /// Restore the puzzles in an exported archive into a role
+    Restore {
+        /// The archive, as written by `export`
+        archive: PathBuf,
+        /// The UUID or short name of the role to restore into
+        #[arg(long)]
+        role: String,
+    },
+    /// Make an identity an administrator
+    GrantAdmin {
+        /// The UUID or OIDC handle of the identity
+        identity: String,
+        /// Revoke administrator rights instead
+        #[arg(long)]
+        revoke: bool,
+    },
 }
 
 impl Cli {
@@ -0,0 +1,211 @@
+//! Maintenance commands
+//!
+//! These run against the database instead of starting the server.
+
+use std::path::Path;
+
+use common::{archive::Archive, objects::PuzzleData, public::puzzle::import::Outcome, APIError};
+use database::{activity, models, AsyncPgConnection};
+use puzzleutils::variants;
+use tracing::{info, warn};
+
+use crate::{
+    cli::Command, config::Configuration, import, shortener::Expander, sudokupad::PuzzleFetcher,
+};
+
+type CommandResult = Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+pub async fn run(config: &Configuration, command: &Command) -> CommandResult {
+    match command {
+        Command::BackfillTags { dry_run } => backfill_tags(config, *dry_run).await,
+        Command::Import {
+            manifest,
+            role,
+            dry_run,
+        } => import_manifest(config, manifest, role, *dry_run).await,
+        Command::Export { role, output } => export_role(config, role, output).await,
+        Command::Restore { archive, role } => restore_archive(config, archive, role).await,
+        Command::GrantAdmin { identity, revoke } => grant_admin(config, identity, !*revoke).await,
+    }
+}
+
+async fn backfill_tags(config: &Configuration, dry_run: bool) -> CommandResult {
+    let mut conn = database::create_connection(config.database_url.as_str()).await?;
+
+    let mut tagged = 0;
+    for puzzle in models::Puzzle::all(&mut conn).await? {
+        // Suggestions come from the most recent state of the puzzle
+        let Some(state) = puzzle.all_states(&mut conn).await?.pop() else {
+            continue;
+        };
+        let data: PuzzleData = match serde_json::from_str(&state.data) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Unable to decode data for state {}: {e}", state.uuid);
+                continue;
+            }
+        };
+        let value = match data {
+            PuzzleData::FPuzzles(value) if !value.is_null() => value,
+            _ => continue,
+        };
+
+        let names: Vec<String> = variants::suggest_tags(&value)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let existing = puzzle.get_tags(&mut conn).await?;
+        for tag in models::Tag::by_names(&mut conn, &names).await? {
+            if existing.contains(&tag.uuid) {
+                continue;
+            }
+            info!(
+                "Tagging {} ({}) with {}",
+                puzzle.display_name, puzzle.uuid, tag.name
+            );
+            if !dry_run {
+                puzzle.add_tag(&mut conn, &tag.uuid).await?;
+            }
+            tagged += 1;
+        }
+    }
+
+    if dry_run {
+        info!("Would have added {tagged} tag(s)");
+    } else {
+        info!("Added {tagged} tag(s)");
+    }
+
+    Ok(())
+}
+
+/// Find a role by its UUID or, failing that, its short name
+async fn find_role(
+    conn: &mut AsyncPgConnection,
+    role: &str,
+) -> Result<models::Role, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    match models::Role::by_uuid(conn, role).await? {
+        Some(role) => Ok(role),
+        None => Ok(models::Role::by_short_name(conn, role)
+            .await?
+            .ok_or_else(|| format!("No role called {role}"))?),
+    }
+}
+
+async fn import_manifest(
+    config: &Configuration,
+    manifest: &Path,
+    role: &str,
+    dry_run: bool,
+) -> CommandResult {
+    let manifest = import::read_manifest(manifest)?;
+    let expander = Expander::new(&config.shorteners)?;
+    let fetcher = PuzzleFetcher::new(&config.sudokupad)?;
+    let mut conn = database::create_connection(config.database_url.as_str()).await?;
+
+    let role = find_role(&mut conn, role).await?;
+    info!(
+        "Importing {} puzzle(s) into {} ({})",
+        manifest.puzzles.len(),
+        role.display_name,
+        role.uuid
+    );
+
+    // Puzzles are imported as though the role's owner had created them
+    let rows = import::import(
+        &mut conn,
+        &expander,
+        &fetcher,
+        &role.owner,
+        &role.uuid,
+        &manifest,
+        dry_run,
+    )
+    .await;
+
+    let mut failed = 0;
+    for (n, row) in rows.iter().enumerate() {
+        let outcome = match &row.outcome {
+            Outcome::Created(uuid) => format!("created {uuid}"),
+            Outcome::Valid => "valid".to_string(),
+            Outcome::Failed(reason) => {
+                failed += 1;
+                format!("FAILED: {reason}")
+            }
+        };
+        println!("{:>4} {}: {outcome}", n + 1, row.short_name);
+        for warning in &row.warnings {
+            println!("       warning: {warning}");
+        }
+    }
+    println!(
+        "{} of {} puzzle(s) {}",
+        rows.len() - failed,
+        rows.len(),
+        if dry_run { "are valid" } else { "imported" }
+    );
+
+    Ok(())
+}
+
+async fn export_role(config: &Configuration, role: &str, output: &Path) -> CommandResult {
+    let mut conn = database::create_connection(config.database_url.as_str()).await?;
+    let role = find_role(&mut conn, role).await?;
+
+    // The export is made as the role's owner, so that nothing is withheld
+    let archive = activity::archive::export(&mut conn, &role.owner, &role.uuid)
+        .await
+        .map_err(APIError::from)?;
+    std::fs::write(output, archive.to_json()?)?;
+    info!(
+        "Exported {} puzzle(s) from {} ({})",
+        archive.puzzles.len(),
+        role.display_name,
+        role.uuid
+    );
+
+    Ok(())
+}
+
+async fn restore_archive(config: &Configuration, archive: &Path, role: &str) -> CommandResult {
+    let archive = Archive::from_json(&std::fs::read_to_string(archive)?)?;
+    let mut conn = database::create_connection(config.database_url.as_str()).await?;
+    let role = find_role(&mut conn, role).await?;
+
+    let puzzles = activity::archive::restore(&mut conn, &role.owner, &role.uuid, &archive)
+        .await
+        .map_err(APIError::from)?;
+    info!(
+        "Restored {} puzzle(s) from {} into {} ({})",
+        puzzles.len(),
+        archive.role.short_name,
+        role.display_name,
+        role.uuid
+    );
+
+    Ok(())
+}
+
+async fn grant_admin(config: &Configuration, identity: &str, is_admin: bool) -> CommandResult {
+    let mut conn = database::create_connection(config.database_url.as_str()).await?;
+    let identity = match models::Identity::from_uuid(&mut conn, identity).await? {
+        Some(identity) => identity,
+        None => models::Identity::from_handle(&mut conn, identity)
+            .await?
+            .ok_or_else(|| format!("No identity called {identity}"))?,
+    };
+
+    let identity = identity.set_admin(&mut conn, is_admin).await?;
+    info!(
+        "{} ({}) is {}an administrator, the frontend will show this once they next log in",
+        identity.display_name,
+        identity.uuid,
+        if identity.is_admin {
+            "now "
+        } else {
+            "no longer "
+        }
+    );
+
+    Ok(())
+}
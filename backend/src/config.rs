@@ -20,6 +20,59 @@ pub struct OpenIDProvider {
     pub scopes: Vec<String>,
 }
 
+/// Which URL shorteners we will expand links from, and how hard we try
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Shorteners {
+    /// Hosts whose redirects we follow, subdomains are included
+    pub hosts: Vec<String>,
+    /// The most redirects we follow for one link
+    pub max_hops: usize,
+    /// How long we wait for each redirect, in seconds
+    pub timeout_secs: u64,
+    /// How long we remember where a link went, in seconds
+    pub cache_secs: u64,
+}
+
+impl Default for Shorteners {
+    fn default() -> Self {
+        Self {
+            hosts: ["tinyurl.com", "bit.ly", "tiny.cc", "is.gd", "sudokupad.app"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            max_hops: 5,
+            timeout_secs: 5,
+            cache_secs: 3600,
+        }
+    }
+}
+
+/// Where we fetch puzzles stored by SudokuPad from
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SudokuPad {
+    /// Hosts whose links may be short puzzle IDs, subdomains are included
+    pub hosts: Vec<String>,
+    /// Puzzle fetch endpoint, `{id}` is replaced by the puzzle ID
+    pub fetch_url: String,
+    /// How long we wait for a puzzle, in seconds
+    pub timeout_secs: u64,
+}
+
+impl Default for SudokuPad {
+    fn default() -> Self {
+        Self {
+            hosts: ["sudokupad.app", "app.crackingthecryptic.com"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            fetch_url: "https://sudokupad.app/api/puzzle/{id}".into(),
+            timeout_secs: 10,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Configuration {
     pub port: u16,
@@ -30,6 +83,13 @@ pub struct Configuration {
     pub sentry_dsn: Option<String>,
     pub sentry_env: Option<String>,
     pub openid: LinkedHashMap<String, OpenIDProvider>,
+    #[serde(default)]
+    pub shorteners: Shorteners,
+    #[serde(default)]
+    pub sudokupad: SudokuPad,
+    /// A header set by a trusted reverse proxy to the client's address
+    #[serde(default)]
+    pub forwarded_for_header: Option<String>,
 }
 
 #[allow(unstable_name_collisions)]
@@ -83,12 +143,36 @@ impl Configuration {
                 "*****"
             }
         );
+        info!(
+            "Expand short links from: {}",
+            self.shorteners.hosts.iter().join(", ")
+        );
+        info!(
+            "Short links: at most {} hops, {}s timeout, cached for {}s",
+            self.shorteners.max_hops, self.shorteners.timeout_secs, self.shorteners.cache_secs
+        );
+        info!(
+            "SudokuPad puzzles for {} fetched from {}",
+            self.sudokupad.hosts.iter().join(", "),
+            self.sudokupad.fetch_url
+        );
+        info!(
+            "Client addresses taken from: {}",
+            self.forwarded_for_header
+                .as_deref()
+                .unwrap_or("the connection")
+        );
         for (name, prov) in &self.openid {
             info!("OpenID provider: {}", name);
             prov.show();
         }
     }
 
+    /// The absolute URL of a path on this site, such as `/puzzle/...`
+    pub fn site_url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url.as_str().trim_end_matches('/'))
+    }
+
     fn safe_database_url(&self) -> Url {
         let mut ret = self.database_url.clone();
         ret.set_password(Some("****")).unwrap();
@@ -0,0 +1,385 @@
+//! oEmbed provider, and the puzzle cards which embeds show
+//!
+//! Embeds are shown to whoever reads the page they are on, so everything
+//! here is looked up as though nobody were logged in.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use common::objects::{self, PuzzleData, Visibility};
+use database::{activity, models, Connection};
+use serde::Serialize;
+use tracing::warn;
+use url::Url;
+
+use crate::{config::ConfigState, sitemap::escape, state::BackendState};
+
+/// The size of the card, unless the consumer asks for something smaller
+const CARD_WIDTH: u32 = 400;
+const CARD_HEIGHT: u32 = 180;
+
+/// The range of widths which the card can be asked to fit
+const MIN_CARD_WIDTH: u32 = 240;
+const MAX_CARD_WIDTH: u32 = 800;
+
+/// Cards shorter than this cannot show even the puzzle's name
+const MIN_CARD_HEIGHT: u32 = 60;
+
+/// Thumbnails come from SudokuPad at this size
+const THUMBNAIL_SIZE: u32 = 512;
+
+/// Everything a card needs to describe a puzzle
+struct PuzzleCard {
+    puzzle: objects::Puzzle,
+    role: models::Role,
+    tags: Vec<models::Tag>,
+    thumbnail: Option<String>,
+    /// Whether the play redirectors can open this puzzle
+    playable: bool,
+}
+
+impl PuzzleCard {
+    async fn load(db: &mut Connection, uuid: &str) -> Result<Self, StatusCode> {
+        let puzzle = models::Puzzle::by_uuid(db, uuid)
+            .await
+            .map_err(database_error)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let role = models::Role::by_uuid(db, &puzzle.owner)
+            .await
+            .map_err(database_error)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        // Anything which cannot be seen anonymously may as well not exist
+        let puzzle = activity::puzzle::into_api_object(db, None, puzzle)
+            .await
+            .map_err(|_| StatusCode::NOT_FOUND)?;
+
+        let latest = |visibility| {
+            puzzle
+                .states
+                .iter()
+                .rev()
+                .find(|state| state.visibility == visibility)
+        };
+        let (thumbnail, playable) = match latest(Visibility::Published)
+            .or_else(|| latest(Visibility::Public))
+            .map(|state| &state.data)
+        {
+            Some(PuzzleData::FPuzzles(data)) => {
+                (Some(puzzleutils::fpuzzles::grid_url_png(data)), true)
+            }
+            _ => (None, false),
+        };
+
+        let mut tags = vec![];
+        for uuid in &puzzle.tags {
+            if let Some(tag) = models::Tag::by_uuid(db, uuid)
+                .await
+                .map_err(database_error)?
+            {
+                tags.push(tag);
+            }
+        }
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            puzzle,
+            role,
+            tags,
+            thumbnail,
+            playable,
+        })
+    }
+}
+
+fn database_error(e: impl std::fmt::Debug) -> StatusCode {
+    warn!("Database error while embedding: {e:?}");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Is the URL on the same host and port as the site?
+///
+/// The scheme may differ, since links are often shared without `https`.
+fn same_site(url: &Url, base: &Url) -> bool {
+    url.host_str() == base.host_str() && url.port() == base.port()
+}
+
+/// Work out which puzzle a Linkdoku URL refers to, if any
+///
+/// Both permalinks (`/puzzle/:uuid`) and shortcuts (`/:role/:puzzle`) are
+/// understood.
+async fn puzzle_for_url(
+    config: &ConfigState,
+    db: &mut Connection,
+    url: &str,
+) -> Result<String, StatusCode> {
+    let url = Url::parse(url).map_err(|_| StatusCode::NOT_FOUND)?;
+    if !same_site(&url, &config.base_url) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let path = url
+        .path()
+        .strip_prefix(config.base_url.path().trim_end_matches('/'))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments[..] {
+        ["puzzle", uuid] => Ok(uuid.to_string()),
+        [role, puzzle] => activity::puzzle::lookup(db, role, puzzle, None)
+            .await
+            .map_err(|_| StatusCode::NOT_FOUND),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// The response to an oEmbed request, see <https://oembed.com/>
+#[derive(Serialize)]
+struct OEmbed {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    version: &'static str,
+    title: String,
+    author_name: String,
+    author_url: String,
+    provider_name: &'static str,
+    provider_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail_height: Option<u32>,
+    html: String,
+    width: u32,
+    height: u32,
+}
+
+#[tracing::instrument(skip(config, db))]
+async fn oembed(
+    Query(query): Query<HashMap<String, String>>,
+    State(config): State<ConfigState>,
+    mut db: Connection,
+) -> Result<Json<OEmbed>, StatusCode> {
+    if query.get("format").is_some_and(|format| format != "json") {
+        return Err(StatusCode::NOT_IMPLEMENTED);
+    }
+    let url = query.get("url").ok_or(StatusCode::BAD_REQUEST)?;
+    let uuid = puzzle_for_url(&config, &mut db, url).await?;
+    let card = PuzzleCard::load(&mut db, &uuid).await?;
+
+    let limit = |name: &str, size: u32| {
+        query
+            .get(name)
+            .and_then(|max| max.parse().ok())
+            .map_or(size, |max: u32| size.min(max))
+    };
+    // Cards cannot be laid out any smaller, so those asking for less must
+    // put up with some overflow
+    let width = limit("maxwidth", CARD_WIDTH).max(MIN_CARD_WIDTH);
+    let height = limit("maxheight", CARD_HEIGHT).max(MIN_CARD_HEIGHT);
+
+    let mut src = format!("/embed/puzzle/{}?width={width}", card.puzzle.uuid);
+    if let Some(theme) = query.get("theme").and_then(|theme| CardTheme::parse(theme)) {
+        src.push_str(&format!("&theme={}", theme.name()));
+    }
+    let title = escape(&card.puzzle.display_name);
+    let mut html = format!(
+        "<iframe src=\"{}\" width=\"{width}\" height=\"{height}\" title=\"{title}\" style=\"border: 0\"></iframe>",
+        escape(&config.site_url(&src))
+    );
+    // Readers whose sites strip iframes still get somewhere to play
+    if card.playable {
+        let play =
+            |redir| escape(&config.site_url(&format!("/puzzle/{}/{redir}", card.puzzle.uuid)));
+        html.push_str(&format!(
+            "<p><a href=\"{}\">Play {title} on SudokuPad</a> | <a href=\"{}\">Play {title} on f-puzzles</a></p>",
+            play("sudokupad"),
+            play("fpuzzles"),
+        ));
+    }
+
+    let has_thumbnail = card.thumbnail.is_some();
+    Ok(Json(OEmbed {
+        kind: "rich",
+        version: "1.0",
+        title: card.puzzle.display_name,
+        author_name: card.role.display_name,
+        author_url: config.site_url(&format!("/role/{}", card.role.uuid)),
+        provider_name: "Linkdoku",
+        provider_url: config.site_url("/"),
+        thumbnail_url: card.thumbnail,
+        thumbnail_width: has_thumbnail.then_some(THUMBNAIL_SIZE),
+        thumbnail_height: has_thumbnail.then_some(THUMBNAIL_SIZE),
+        html,
+        width,
+        height,
+    }))
+}
+
+#[derive(Clone, Copy)]
+enum CardTheme {
+    Light,
+    Dark,
+}
+
+impl CardTheme {
+    fn parse(theme: &str) -> Option<Self> {
+        match theme {
+            "light" => Some(CardTheme::Light),
+            "dark" => Some(CardTheme::Dark),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            CardTheme::Light => "light",
+            CardTheme::Dark => "dark",
+        }
+    }
+
+    /// The background, text, border, and link colours
+    fn colours(self) -> [&'static str; 4] {
+        match self {
+            CardTheme::Light => ["#ffffff", "#4a4a4a", "#dbdbdb", "#485fc7"],
+            CardTheme::Dark => ["#1f2229", "#f5f5f5", "#4a4a4a", "#8ea2f5"],
+        }
+    }
+}
+
+/// A compact card describing a puzzle, meant to be shown in an iframe
+///
+/// This is deliberately plain HTML, with none of the application, so that
+/// it is quick to load on other people's sites.  The `theme` (`light` or
+/// `dark`) and `width` (in pixels) query parameters adjust its appearance.
+#[tracing::instrument(skip(config, db))]
+async fn puzzle_card(
+    Path(uuid): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    State(config): State<ConfigState>,
+    mut db: Connection,
+) -> Result<Response, StatusCode> {
+    let card = PuzzleCard::load(&mut db, &uuid).await?;
+    let theme = query
+        .get("theme")
+        .and_then(|theme| CardTheme::parse(theme))
+        .unwrap_or(CardTheme::Light);
+    let width = query
+        .get("width")
+        .and_then(|width| width.parse().ok())
+        .map_or(CARD_WIDTH, |width: u32| {
+            width.clamp(MIN_CARD_WIDTH, MAX_CARD_WIDTH)
+        });
+    let [background, text, border, link] = theme.colours();
+
+    let title = escape(&card.puzzle.display_name);
+    let puzzle_url = escape(&config.site_url(&format!("/puzzle/{}", card.puzzle.uuid)));
+    let role_url = escape(&config.site_url(&format!("/role/{}", card.role.uuid)));
+    let role_name = escape(&card.role.display_name);
+
+    let thumbnail = match &card.thumbnail {
+        Some(thumbnail) => format!(
+            r#"<a href="{puzzle_url}" target="_blank" rel="noopener"><img class="thumbnail" src="{}" alt=""></a>"#,
+            escape(thumbnail)
+        ),
+        None => String::new(),
+    };
+    let tags: String = card
+        .tags
+        .iter()
+        .map(|tag| {
+            format!(
+                r#"<span class="tag" style="background: {}; color: {}">{}</span>"#,
+                escape(&tag.colour),
+                if tag.black_text { "#000000" } else { "#ffffff" },
+                escape(&tag.name)
+            )
+        })
+        .collect();
+    let buttons = if card.playable {
+        let play =
+            |redir| escape(&config.site_url(&format!("/puzzle/{}/{redir}", card.puzzle.uuid)));
+        format!(
+            r#"<a class="button" href="{}" target="_blank" rel="noopener">Play on SudokuPad</a><a class="button" href="{}" target="_blank" rel="noopener">Play on f-puzzles</a>"#,
+            play("sudokupad"),
+            play("fpuzzles"),
+        )
+    } else {
+        String::new()
+    };
+
+    let body = format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ margin: 0; font-family: sans-serif; background: transparent; }}
+a {{ color: {link}; text-decoration: none; }}
+.card {{ box-sizing: border-box; display: flex; gap: 0.75em; max-width: {width}px; height: 100vh; padding: 0.75em; overflow: hidden; background: {background}; color: {text}; border: 1px solid {border}; border-radius: 6px; }}
+.thumbnail {{ width: 7em; height: 7em; border-radius: 4px; }}
+.details {{ min-width: 0; }}
+.details h1 {{ font-size: 1.1em; margin: 0 0 0.25em 0; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }}
+.details p {{ font-size: 0.9em; margin: 0 0 0.5em 0; }}
+.tag {{ display: inline-block; font-size: 0.75em; padding: 0.1em 0.6em; margin: 0 0.3em 0.3em 0; border-radius: 1em; }}
+.button {{ display: inline-block; font-size: 0.85em; padding: 0.3em 0.75em; margin: 0.3em 0.5em 0 0; border: 1px solid {link}; border-radius: 4px; }}
+</style>
+</head>
+<body>
+<div class="card">
+{thumbnail}
+<div class="details">
+<h1><a href="{puzzle_url}" target="_blank" rel="noopener">{title}</a></h1>
+<p>A puzzle by <a href="{role_url}" target="_blank" rel="noopener">{role_name}</a> on Linkdoku</p>
+<div>{tags}</div>
+<div>{buttons}</div>
+</div>
+</div>
+</body>
+</html>
+"#
+    );
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/html"),
+            (header::CACHE_CONTROL, "public, max-age=300"),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+pub fn router() -> Router<BackendState> {
+    Router::new()
+        .route("/oembed", get(oembed))
+        .route("/embed/puzzle/:uuid", get(puzzle_card))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_site() {
+        let base = Url::parse("https://linkdoku.example/").unwrap();
+        let url = |u| Url::parse(u).unwrap();
+        assert!(same_site(&url("https://linkdoku.example/puzzle/x"), &base));
+        assert!(same_site(&url("http://linkdoku.example/puzzle/x"), &base));
+        assert!(same_site(&url("https://linkdoku.example:443/a/b"), &base));
+        assert!(!same_site(&url("https://linkdoku.example:8443/a/b"), &base));
+        assert!(!same_site(&url("https://evil.example/a/b"), &base));
+
+        let dev = Url::parse("http://localhost:3000/").unwrap();
+        assert!(same_site(&url("http://localhost:3000/puzzle/x"), &dev));
+        assert!(!same_site(&url("http://localhost/puzzle/x"), &dev));
+        assert!(!same_site(&url("http://localhost:3001/puzzle/x"), &dev));
+    }
+}
@@ -0,0 +1,268 @@
+//! Bulk import of puzzles from a manifest
+//!
+//! Each entry in a manifest is checked on its own, and each valid one is
+//! created in its own transaction, so one bad row never stops the rest.
+//! The result is a report with one row per entry.
+
+use std::path::Path;
+
+use common::{
+    clean_short_name,
+    objects::{PuzzleData, PuzzleState},
+    public::puzzle::import::{Manifest, ManifestEntry, Outcome, Row},
+    APIError, APIResult,
+};
+use database::{activity, AsyncPgConnection};
+use puzzleutils::{
+    convert::convert,
+    formats::{self, Decoded, Format},
+};
+
+use crate::{shortener::Expander, sudokupad::PuzzleFetcher};
+
+/// Read a manifest file, which is JSON if it is named `*.json` and YAML otherwise
+pub fn read_manifest(path: &Path) -> Result<Manifest, Box<dyn std::error::Error + Send + Sync>> {
+    let text = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        Ok(serde_json::from_str(&text)?)
+    } else {
+        Ok(serde_yaml::from_str(&text)?)
+    }
+}
+
+/// Find the puzzle a link leads to, returning where it ended up too
+///
+/// Links are decoded directly where possible, otherwise SudokuPad short
+/// links are fetched and shortened links are expanded.
+pub async fn resolve_link(
+    expander: &Expander,
+    fetcher: &PuzzleFetcher,
+    link: &str,
+) -> APIResult<(String, Decoded)> {
+    if let Ok(decoded) = formats::decode(link, None) {
+        return Ok((link.to_string(), decoded));
+    }
+    // SudokuPad short links name stored puzzles, and may themselves be
+    // the target of a shortened link
+    if let Some(id) = fetcher.short_id(link) {
+        return Ok((link.to_string(), fetcher.fetch(&id).await?));
+    }
+    let url = expander.expand(link).await?;
+    let decoded = match fetcher.short_id(url.as_str()) {
+        Some(id) => fetcher.fetch(&id).await?,
+        None => formats::decode(url.as_str(), None)
+            .map_err(|_| APIError::Generic(format!("No puzzle found at {url}")))?,
+    };
+    Ok((url.to_string(), decoded))
+}
+
+/// An entry which has passed every check not needing the database
+struct Checked {
+    short_name: String,
+    state: PuzzleState,
+    warnings: Vec<String>,
+}
+
+async fn check(
+    expander: &Expander,
+    fetcher: &PuzzleFetcher,
+    entry: &ManifestEntry,
+) -> Result<Checked, String> {
+    let short_name = clean_short_name(&entry.short_name, false)
+        .map_err(|e| APIError::BadShortName(e).to_string())?;
+    if entry.display_name.trim().is_empty() {
+        return Err("No display name given".into());
+    }
+    let (_, decoded) = resolve_link(expander, fetcher, &entry.url)
+        .await
+        .map_err(|e| e.to_string())?;
+    let conversion = convert(&decoded, Format::FPuzzles)
+        .map_err(|e| format!("Unable to convert puzzle: {e}"))?;
+
+    Ok(Checked {
+        short_name,
+        state: PuzzleState {
+            description: entry.description.clone(),
+            data: PuzzleData::FPuzzles(conversion.value),
+            ..Default::default()
+        },
+        warnings: conversion.unmapped,
+    })
+}
+
+/// Every tag an entry names must already exist
+fn check_tags(wanted: &[String], known: &[&str]) -> Result<(), String> {
+    let missing: Vec<&str> = wanted
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !known.contains(name))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Unknown tag(s): {}", missing.join(", ")))
+    }
+}
+
+async fn import_entry(
+    conn: &mut AsyncPgConnection,
+    expander: &Expander,
+    fetcher: &PuzzleFetcher,
+    actor: &str,
+    owner: &str,
+    entry: &ManifestEntry,
+    dry_run: bool,
+) -> Result<(Outcome, Vec<String>), String> {
+    let checked = check(expander, fetcher, entry).await?;
+
+    let tags = activity::tag::by_names(conn, &entry.tags)
+        .await
+        .map_err(|e| APIError::from(e).to_string())?;
+    let known: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+    check_tags(&entry.tags, &known)?;
+
+    if dry_run {
+        return Ok((Outcome::Valid, checked.warnings));
+    }
+
+    let tags: Vec<String> = tags.into_iter().map(|tag| tag.uuid).collect();
+    let puzzle = activity::puzzle::create(
+        conn,
+        actor,
+        owner,
+        &checked.short_name,
+        entry.display_name.trim(),
+        &checked.state,
+        &tags,
+    )
+    .await
+    .map_err(|e| APIError::from(e).to_string())?;
+
+    Ok((Outcome::Created(puzzle.uuid), checked.warnings))
+}
+
+/// Import every entry of the manifest into the owning role, as the actor
+pub async fn import(
+    conn: &mut AsyncPgConnection,
+    expander: &Expander,
+    fetcher: &PuzzleFetcher,
+    actor: &str,
+    owner: &str,
+    manifest: &Manifest,
+    dry_run: bool,
+) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(manifest.puzzles.len());
+    for entry in &manifest.puzzles {
+        let (outcome, warnings) =
+            match import_entry(conn, expander, fetcher, actor, owner, entry, dry_run).await {
+                Ok(done) => done,
+                Err(reason) => (Outcome::Failed(reason), vec![]),
+            };
+        rows.push(Row {
+            short_name: entry.short_name.clone(),
+            outcome,
+            warnings,
+        });
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+    use crate::config::{Shorteners, SudokuPad};
+
+    fn puzzle_url() -> String {
+        let grid = vec![vec![serde_json::json!({}); 4]; 4];
+        formats::app_url(
+            formats::App::FPuzzles,
+            &serde_json::json!({ "size": 4, "grid": grid }),
+        )
+    }
+
+    fn entry(short_name: &str, display_name: &str, url: &str) -> ManifestEntry {
+        ManifestEntry {
+            short_name: short_name.into(),
+            display_name: display_name.into(),
+            description: String::new(),
+            tags: vec![],
+            url: url.into(),
+        }
+    }
+
+    /// Write a manifest where only this test will find it
+    fn manifest_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("import-{}-{name}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn read(name: &str, content: &str) -> Option<Manifest> {
+        let path = manifest_file(name, content);
+        let ret = read_manifest(&path).ok();
+        fs::remove_file(path).unwrap();
+        ret
+    }
+
+    #[test]
+    fn manifests_are_json_or_yaml() {
+        let url = puzzle_url();
+        let yaml = format!(
+            "puzzles:\n  - short_name: first\n    display_name: First\n    tags: [Killer]\n    url: {url}\n"
+        );
+        let from_yaml = read("manifest.yaml", &yaml).unwrap();
+        let mut expected = entry("first", "First", &url);
+        expected.tags = vec!["Killer".into()];
+        assert_eq!(from_yaml.puzzles, vec![expected]);
+
+        let json = serde_json::to_string(&from_yaml).unwrap();
+        assert_eq!(read("manifest.json", &json), Some(from_yaml.clone()));
+        // YAML is a superset of JSON, so any other name will do too
+        assert_eq!(read("manifest.txt", &json), Some(from_yaml));
+
+        assert_eq!(read("manifest.json", &yaml), None);
+        assert_eq!(read("manifest.yaml", "puzzles: 7"), None);
+    }
+
+    #[test]
+    fn unknown_tags_fail_the_row() {
+        let wanted = vec!["Killer".to_string(), "Thermo".into(), "Nope".into()];
+        assert_eq!(check_tags(&[], &[]), Ok(()));
+        assert_eq!(check_tags(&wanted[..2], &["Thermo", "Killer"]), Ok(()));
+        assert_eq!(
+            check_tags(&wanted, &["Killer"]),
+            Err("Unknown tag(s): Thermo, Nope".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_rows_fail_alone() {
+        let expander = Expander::new(&Shorteners::default()).unwrap();
+        let fetcher = PuzzleFetcher::new(&SudokuPad::default()).unwrap();
+        let url = puzzle_url();
+        let check_entry = |entry: ManifestEntry| {
+            let (expander, fetcher) = (&expander, &fetcher);
+            async move { check(expander, fetcher, &entry).await }
+        };
+
+        for short_name in ["", "has spaces", "sh", "x".repeat(100).as_str()] {
+            let err = check_entry(entry(short_name, "Fine", &url)).await.err();
+            assert!(err.is_some(), "{short_name:?} was accepted");
+        }
+        assert_eq!(
+            check_entry(entry("no-name", "  ", &url)).await.err(),
+            Some("No display name given".into())
+        );
+        assert!(
+            check_entry(entry("elsewhere", "Fine", "https://example.com/"))
+                .await
+                .is_err()
+        );
+
+        let checked = check_entry(entry("fine", "Fine", &url)).await.unwrap();
+        assert_eq!(checked.short_name, "fine");
+        assert!(matches!(checked.state.data, PuzzleData::FPuzzles(_)));
+    }
+}
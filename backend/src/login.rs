@@ -6,7 +6,7 @@ use std::{error::Error, sync::Arc};
 use axum::{
     async_trait,
     extract::{FromRef, FromRequestParts, State},
-    http::{request::Parts, StatusCode},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
     routing::{get, post},
     Json, RequestPartsExt, Router,
 };
@@ -14,12 +14,13 @@ use common::{
     internal::{
         login::{begin, complete, providers},
         logout,
+        token::TokenScope,
     },
     public::userinfo,
     APIError, APIResult,
 };
 use cookie::{Cookie, Key, SameSite};
-use database::{activity, models};
+use database::{activity, models, Connection, Pool};
 use linked_hash_map::LinkedHashMap;
 use openidconnect::{
     core::{CoreAuthenticationFlow, CoreClient, CoreGenderClaim, CoreProviderMetadata},
@@ -127,6 +128,9 @@ impl LoginFlowUserData {
 pub struct LoginFlowStatus {
     flow: Option<LoginFlowSetup>,
     user: Option<LoginFlowUserData>,
+    /// Set when the user came from an API token rather than the login cookie
+    #[serde(skip)]
+    scope: Option<TokenScope>,
 }
 
 impl LoginFlowStatus {
@@ -137,6 +141,35 @@ impl LoginFlowStatus {
     pub fn user_uuid(&self) -> Option<&str> {
         self.user.as_ref().map(|u| u.identity().uuid.as_str())
     }
+
+    /// The user of a logged in browser session, ignoring API tokens
+    ///
+    /// Anything which could be used to gain further access, such as
+    /// managing tokens or administration, is limited to sessions.
+    pub fn session_user_uuid(&self) -> Option<&str> {
+        match self.scope {
+            None => self.user_uuid(),
+            Some(_) => None,
+        }
+    }
+
+    /// The scope of the API token this request was made with, if any
+    pub fn token_scope(&self) -> Option<&TokenScope> {
+        self.scope.as_ref()
+    }
+
+    /// May the caller make changes on behalf of the given role?
+    ///
+    /// Logged in browsers may change anything their identity can, whereas
+    /// API tokens are limited by their scope.  Either way, the usual
+    /// ownership checks still have to be made.
+    pub fn may_change(&self, role: &str) -> bool {
+        match &self.scope {
+            None => true,
+            Some(TokenScope::ReadOnly) => false,
+            Some(TokenScope::Publish(scope_role)) => scope_role == role,
+        }
+    }
 }
 
 // ---- Private cookies stuff ----
@@ -144,6 +177,7 @@ impl LoginFlowStatus {
 pub struct PrivateCookies {
     key: Key,
     cookies: Cookies,
+    token: BearerToken,
 }
 
 #[async_trait]
@@ -151,16 +185,19 @@ impl<S> FromRequestParts<S> for PrivateCookies
 where
     S: Send + Sync,
     ConfigState: FromRef<S>,
+    Pool: FromRef<S>,
 {
     type Rejection = (StatusCode, &'static str);
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let cookies: Cookies = parts.extract().await?;
+        let token: BearerToken = parts.extract_with_state(state).await?;
         let config = ConfigState::from_ref(state);
 
         Ok(Self {
             key: Key::derive_from(config.cookie_secret.as_bytes()),
             cookies,
+            token,
         })
     }
 }
@@ -175,8 +212,75 @@ impl PrivateCookies {
     }
 }
 
+// ---- API tokens ----
+
+/// The identity and scope of a personal API token, if the request came
+/// with an `Authorization: Bearer` header
+///
+/// Requests naming an unknown token are rejected outright rather than
+/// being treated as logged out, so that scripts fail loudly.
+pub struct BearerToken(Option<(models::Identity, TokenScope)>);
+
+/// The secret from an `Authorization: Bearer` header, if there is one
+fn bearer_secret(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|secret| secret.trim().to_string())
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for BearerToken
+where
+    S: Send + Sync,
+    Pool: FromRef<S>,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Some(secret) = bearer_secret(&parts.headers) else {
+            return Ok(Self(None));
+        };
+
+        const CHECK_FAILED: (StatusCode, &str) = (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Unable to check API token",
+        );
+        let mut db: Connection = parts
+            .extract_with_state(state)
+            .await
+            .map_err(|_| CHECK_FAILED)?;
+        match activity::token::authenticate(&mut db, &secret).await {
+            Ok(Some(found)) => Ok(Self(Some(found))),
+            Ok(None) => Err((StatusCode::UNAUTHORIZED, "Unknown API token")),
+            Err(_) => Err(CHECK_FAILED),
+        }
+    }
+}
+
 // ----- Login flow stuff -----
+
+/// The login status of the caller, from their API token if they sent one,
+/// otherwise from their login cookie
 pub async fn login_flow_status(cookies: &PrivateCookies) -> LoginFlowStatus {
+    match &cookies.token.0 {
+        Some((identity, scope)) => LoginFlowStatus {
+            flow: None,
+            user: Some(LoginFlowUserData {
+                identity: identity.clone(),
+            }),
+            scope: Some(scope.clone()),
+        },
+        None => cookie_login_flow_status(cookies).await,
+    }
+}
+
+/// The login status held in the login cookie
+///
+/// The login flow itself must use this, so that an API token can never be
+/// turned into a login cookie.
+async fn cookie_login_flow_status(cookies: &PrivateCookies) -> LoginFlowStatus {
     serde_json::from_str(
         &cookies
             .get()
@@ -216,6 +320,7 @@ async fn handle_userinfo(
                     gravatar_hash: user.identity.gravatar_hash.clone(),
                     roles: roles.into_iter().map(|role| role.uuid).collect(),
                     default_role: user.identity.default_role_uuid(),
+                    is_admin: user.identity.is_admin,
                 }),
             }),
             Err(e) => Err(APIError::DatabaseError(e.to_string())),
@@ -225,7 +330,7 @@ async fn handle_userinfo(
 }
 
 async fn handle_logout(cookies: PrivateCookies) -> APIResult<logout::Response> {
-    let mut flow = login_flow_status(&cookies).await;
+    let mut flow = cookie_login_flow_status(&cookies).await;
     flow.flow = None;
     flow.user = None;
     set_login_flow_status(&cookies, &flow).await;
@@ -252,7 +357,7 @@ async fn handle_start_auth(
     State(config): State<ConfigState>,
     Json(request): Json<begin::Request>,
 ) -> APIResult<begin::Response> {
-    let mut flow = login_flow_status(&cookies).await;
+    let mut flow = cookie_login_flow_status(&cookies).await;
     // First up, if we're already logged in, just redirect the user to the root of the app
     if flow.user.is_some() {
         return Ok(begin::Response::LoggedIn);
@@ -311,7 +416,7 @@ async fn handle_login_continue(
     State(config): State<ConfigState>,
     Json(params): Json<complete::Request>,
 ) -> APIResult<complete::Response> {
-    let mut flow = login_flow_status(&cookies).await;
+    let mut flow = cookie_login_flow_status(&cookies).await;
     // First up, if we're already logged in, just redirect the user to the root of the app
     if let Some(user) = &flow.user {
         let roles = match user.identity.roles(&mut db).await {
@@ -328,6 +433,7 @@ async fn handle_login_continue(
                 gravatar_hash: user.identity.gravatar_hash.clone(),
                 roles: roles.into_iter().map(|role| role.uuid).collect(),
                 default_role: user.identity.default_role_uuid(),
+                is_admin: user.identity.is_admin,
             },
             is_first_login: false,
         });
@@ -449,6 +555,7 @@ async fn handle_login_continue(
                         gravatar_hash,
                         roles: roles.into_iter().map(|role| role.uuid).collect(),
                         default_role: identity.default_role_uuid(),
+                        is_admin: identity.is_admin,
                     };
                     flow.user = Some(LoginFlowUserData { identity });
                     set_login_flow_status(&cookies, &flow).await;
@@ -466,7 +573,7 @@ async fn handle_login_continue(
                 }
             }
         } else {
-            let mut flow = login_flow_status(&cookies).await;
+            let mut flow = cookie_login_flow_status(&cookies).await;
             flow.flow = None;
             set_login_flow_status(&cookies, &flow).await;
             Err(APIError::UnknownLoginProvider(setup.provider.clone()))
@@ -489,3 +596,40 @@ pub fn internal_router() -> Router<BackendState> {
 pub fn public_router() -> Router<BackendState> {
     Router::new().route(userinfo::URI, get(handle_userinfo))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn scoped(scope: Option<TokenScope>) -> LoginFlowStatus {
+        LoginFlowStatus {
+            scope,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn may_change() {
+        assert!(scoped(None).may_change("role"));
+        assert!(!scoped(Some(TokenScope::ReadOnly)).may_change("role"));
+        let publish = scoped(Some(TokenScope::Publish("role".into())));
+        assert!(publish.may_change("role"));
+        assert!(!publish.may_change("other"));
+    }
+
+    #[test]
+    fn bearer_header() {
+        let secret = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, HeaderValue::from_static(value));
+            bearer_secret(&headers)
+        };
+        assert_eq!(bearer_secret(&HeaderMap::new()), None);
+        assert_eq!(secret("Bearer abc123"), Some("abc123".into()));
+        assert_eq!(secret("Bearer  abc123 "), Some("abc123".into()));
+        assert_eq!(secret("Basic dXNlcjpwYXNz"), None);
+        assert_eq!(secret("bearer abc123"), None);
+    }
+}
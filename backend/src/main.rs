@@ -2,7 +2,7 @@
 //!
 //!
 
-use std::convert::identity;
+use std::{convert::identity, net::SocketAddr};
 
 use axum::{routing::get, Router};
 use clap::Parser;
@@ -22,11 +22,19 @@ use crate::state::BackendState;
 
 mod api;
 mod cli;
+mod commands;
 mod config;
+mod embed;
+mod import;
 mod login;
+mod ratelimit;
 mod redirectors;
+mod shortener;
+mod sitemap;
 mod spa;
+mod ssrcache;
 mod state;
+mod sudokupad;
 
 git_testament!(VERSION);
 
@@ -118,10 +126,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
         database::apply_migrations_async(config.database_url.as_str()).await?;
     }
 
+    if let Some(command) = cli.command.as_ref() {
+        return commands::run(&config, command).await;
+    }
+
     // Now prepare context/state we need to get going
     info!("Construct openid-connect providers");
     let providers = login::load_providers(&config).await?;
 
+    info!("Prepare short link expander");
+    let expander = shortener::Expander::new(&config.shorteners)?;
+    let fetcher = sudokupad::PuzzleFetcher::new(&config.sudokupad)?;
+
     info!("Establish database pool");
     let pool = database::create_pool(config.database_url.as_str()).await?;
 
@@ -129,6 +145,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
     let app = Router::new()
         .nest("/api", api::router())
         .nest("/", redirectors::router())
+        .merge(sitemap::router())
+        .merge(embed::router())
         .route("/assets/:filename", get(spa::serve_file))
         .fallback(spa::spa_handler)
         .layer(CookieManagerLayer::new())
@@ -150,11 +168,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>
 
     // and provide all the state to it
     let port = config.port;
-    let app = app.with_state(BackendState::new(cli, config, pool, providers));
+    let app = app.with_state(BackendState::new(
+        cli, config, pool, providers, expander, fetcher,
+    ));
 
     info!("Launching server on port {port}");
     axum::Server::bind(&format!("0.0.0.0:{port}").parse().unwrap())
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await?;
 
     Ok(())
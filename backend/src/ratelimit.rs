@@ -0,0 +1,144 @@
+//! Simple in-process rate limiting
+//!
+//! Limits are tracked per key (usually an identity UUID) using fixed windows.
+//! This is deliberately simple since we only run a single backend process.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::http::HeaderMap;
+
+/// Once we are tracking this many keys we take the time to prune stale ones
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    inner: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+    limit: u32,
+    window: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HashMap::new())),
+            limit,
+            window,
+        }
+    }
+
+    /// Record an attempt by the given key, returning whether or not
+    /// the attempt is permitted.
+    pub fn check(&self, key: &str) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> bool {
+        let mut map = self.inner.lock().unwrap();
+        if map.len() > PRUNE_THRESHOLD {
+            map.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+        let entry = map.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+        if entry.1 >= self.limit {
+            false
+        } else {
+            entry.1 += 1;
+            true
+        }
+    }
+}
+
+/// The rate limiting key for a caller who is not logged in
+///
+/// Behind a reverse proxy every connection comes from the proxy, so if the
+/// configuration names a header which the proxy sets then we use the last
+/// address in it, which is the one the proxy itself saw.  Anything earlier
+/// in that header was supplied by the client and cannot be trusted.
+pub fn anonymous_key(headers: &HeaderMap, forwarded_for: Option<&str>, addr: SocketAddr) -> String {
+    let ip = forwarded_for
+        .and_then(|name| headers.get_all(name).iter().next_back())
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse::<IpAddr>().ok())
+        .unwrap_or_else(|| addr.ip());
+    format!("anonymous:{ip}")
+}
+
+/// The rate limiters used by the various APIs
+#[derive(Clone)]
+pub struct RateLimits {
+    pub check_solution: RateLimiter,
+    /// Content reports, per identity
+    pub report: RateLimiter,
+    /// Bulk imports, per identity, since each may fetch many links
+    pub import: RateLimiter,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            check_solution: RateLimiter::new(10, Duration::from_secs(60)),
+            report: RateLimiter::new(5, Duration::from_secs(60 * 60)),
+            import: RateLimiter::new(10, Duration::from_secs(60 * 60)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn limits_each_key() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check_at("a", now));
+        assert!(limiter.check_at("a", now));
+        assert!(!limiter.check_at("a", now));
+        assert!(limiter.check_at("b", now));
+    }
+
+    #[test]
+    fn window_resets() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+        assert!(limiter.check_at("a", now));
+        assert!(!limiter.check_at("a", now + Duration::from_secs(59)));
+        assert!(limiter.check_at("a", now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn anonymous_keys() {
+        let addr: SocketAddr = "10.0.0.1:1234".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("192.0.2.1, 203.0.113.7"),
+        );
+
+        assert_eq!(anonymous_key(&headers, None, addr), "anonymous:10.0.0.1");
+        assert_eq!(
+            anonymous_key(&headers, Some("X-Forwarded-For"), addr),
+            "anonymous:203.0.113.7"
+        );
+        assert_eq!(
+            anonymous_key(&headers, Some("X-Real-IP"), addr),
+            "anonymous:10.0.0.1"
+        );
+
+        headers.insert("x-forwarded-for", HeaderValue::from_static("nonsense"));
+        assert_eq!(
+            anonymous_key(&headers, Some("X-Forwarded-For"), addr),
+            "anonymous:10.0.0.1"
+        );
+    }
+}
@@ -84,6 +84,11 @@ async fn puzzle_redirector(
         )
         .0;
 
+    // States which hide their solution were stripped of it by into_api_object
+    // for anyone but the owner, and go out with no answer-check data at all.
+    // Neither f-puzzles nor SudokuPad document a format for checking answers
+    // against an obfuscated solution, so rather than invent one which the
+    // apps would ignore, solvers are left to the solution checking API.
     match &puzzle.states[display_index].data {
         PuzzleData::FPuzzles(v) => {
             let fpuzzles_str = puzzleutils::fpuzzles::encode(v);
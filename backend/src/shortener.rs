@@ -0,0 +1,286 @@
+//! Expanding shortened puzzle links
+//!
+//! Puzzles are often shared through URL shorteners.  We follow redirects
+//! from the configured shortener hosts until we reach a link which one of
+//! the `puzzleutils` extractors understands, or a host we do not trust.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use common::APIError;
+use puzzleutils::formats;
+use reqwest::{header::LOCATION, redirect::Policy, Client};
+use tracing::info;
+use url::Url;
+
+use crate::config::Shorteners;
+
+/// Once the cache holds this many links we take the time to prune stale ones
+const PRUNE_THRESHOLD: usize = 1_000;
+
+#[derive(Debug)]
+pub enum ExpandError {
+    BadUrl(String),
+    NotAllowed(String),
+    TooManyHops,
+    Http(String),
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::BadUrl(url) => write!(f, "Unable to parse URL {url}"),
+            ExpandError::NotAllowed(host) => write!(f, "Links from {host} cannot be expanded"),
+            ExpandError::TooManyHops => write!(f, "Link redirected too many times"),
+            ExpandError::Http(e) => write!(f, "Unable to follow link: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+impl From<ExpandError> for APIError {
+    fn from(e: ExpandError) -> Self {
+        match e {
+            ExpandError::BadUrl(_) => APIError::BadInput,
+            e => APIError::Generic(e.to_string()),
+        }
+    }
+}
+
+/// Is the URL a web link to one of the given (lowercase) hosts or their subdomains?
+pub fn on_hosts(url: &Url, hosts: &[String]) -> bool {
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+        return false;
+    };
+    hosts.iter().any(|allowed| {
+        host == *allowed
+            || host
+                .strip_suffix(allowed.as_str())
+                .map(|rest| rest.ends_with('.'))
+                .unwrap_or(false)
+    })
+}
+
+struct Inner {
+    client: Client,
+    hosts: Vec<String>,
+    max_hops: usize,
+    timeout: Duration,
+    cache_for: Duration,
+    cache: Mutex<HashMap<String, (Instant, Url)>>,
+}
+
+#[derive(Clone)]
+pub struct Expander {
+    inner: Arc<Inner>,
+}
+
+impl Expander {
+    pub fn new(config: &Shorteners) -> reqwest::Result<Self> {
+        let client = Client::builder().redirect(Policy::none()).build()?;
+        Ok(Self::with_client(client, config))
+    }
+
+    /// Build an expander around the given client
+    ///
+    /// The client must not follow redirects itself, since each one has to
+    /// be checked against the permitted hosts.
+    pub fn with_client(client: Client, config: &Shorteners) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client,
+                hosts: config
+                    .hosts
+                    .iter()
+                    .map(|h| h.to_ascii_lowercase())
+                    .collect(),
+                max_hops: config.max_hops,
+                timeout: Duration::from_secs(config.timeout_secs),
+                cache_for: Duration::from_secs(config.cache_secs),
+                cache: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    fn allowed(&self, url: &Url) -> bool {
+        on_hosts(url, &self.inner.hosts)
+    }
+
+    fn cached(&self, link: &str) -> Option<Url> {
+        let cache = self.inner.cache.lock().unwrap();
+        cache
+            .get(link)
+            .filter(|(when, _)| when.elapsed() < self.inner.cache_for)
+            .map(|(_, url)| url.clone())
+    }
+
+    fn remember(&self, link: &str, url: &Url) {
+        let now = Instant::now();
+        let mut cache = self.inner.cache.lock().unwrap();
+        if cache.len() > PRUNE_THRESHOLD {
+            cache.retain(|_, (when, _)| now.duration_since(*when) < self.inner.cache_for);
+        }
+        cache.insert(link.to_string(), (now, url.clone()));
+    }
+
+    /// Follow the given link to wherever it ends up
+    ///
+    /// We stop as soon as the link is one `puzzleutils` can decode, when it
+    /// leads off the permitted hosts, or when a host stops redirecting.
+    pub async fn expand(&self, link: &str) -> Result<Url, ExpandError> {
+        if let Some(url) = self.cached(link) {
+            return Ok(url);
+        }
+
+        let mut url = Url::parse(link).map_err(|_| ExpandError::BadUrl(link.to_string()))?;
+        if !self.allowed(&url) {
+            return Err(ExpandError::NotAllowed(
+                url.host_str().unwrap_or_default().to_string(),
+            ));
+        }
+
+        let mut hops = 0;
+        while self.allowed(&url) && formats::decode(url.as_str(), None).is_err() {
+            if hops == self.inner.max_hops {
+                return Err(ExpandError::TooManyHops);
+            }
+            hops += 1;
+            let response = self
+                .inner
+                .client
+                .get(url.clone())
+                .timeout(self.inner.timeout)
+                .send()
+                .await
+                .map_err(|e| ExpandError::Http(e.to_string()))?;
+            if !response.status().is_redirection() {
+                break;
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| ExpandError::Http("Redirect without a location".into()))?;
+            url = url
+                .join(location)
+                .map_err(|_| ExpandError::BadUrl(location.to_string()))?;
+        }
+
+        info!("Expanded {link} in {hops} hop(s)");
+        self.remember(link, &url);
+        Ok(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use axum::{
+        http::{header::HOST, HeaderMap},
+        response::Redirect,
+        routing::get,
+        Router,
+    };
+
+    use super::*;
+
+    /// Serve redirects as every configured shortener would
+    ///
+    /// `/hop` redirects to `/end`, which leaves the shorteners for a page
+    /// named after the host the request was made to.
+    async fn stub_server() -> SocketAddr {
+        let app = Router::new()
+            .route("/hop", get(|| async { Redirect::temporary("/end") }))
+            .route(
+                "/end",
+                get(|headers: HeaderMap| async move {
+                    let host = headers
+                        .get(HOST)
+                        .and_then(|h| h.to_str().ok())
+                        .and_then(|h| h.split(':').next())
+                        .unwrap_or_default()
+                        .to_string();
+                    Redirect::temporary(&format!("https://example.com/{host}"))
+                }),
+            )
+            .route("/loop", get(|| async { Redirect::temporary("/loop") }))
+            .route("/page", get(|| async { "Not a redirect" }));
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    async fn expander() -> (Expander, u16) {
+        let config = Shorteners::default();
+        let addr = stub_server().await;
+        let client = config
+            .hosts
+            .iter()
+            .fold(
+                Client::builder().redirect(Policy::none()),
+                |builder, host| {
+                    builder
+                        .resolve(host, addr)
+                        .resolve(&format!("www.{host}"), addr)
+                },
+            )
+            .build()
+            .unwrap();
+        (Expander::with_client(client, &config), addr.port())
+    }
+
+    #[tokio::test]
+    async fn follows_each_configured_shortener() {
+        let (expander, port) = expander().await;
+        let hosts = Shorteners::default().hosts.into_iter();
+        for host in hosts.flat_map(|host| [format!("www.{host}"), host]) {
+            let url = expander
+                .expand(&format!("http://{host}:{port}/hop"))
+                .await
+                .unwrap_or_else(|e| panic!("{host}: {e}"));
+            assert_eq!(url.as_str(), format!("https://example.com/{host}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_other_hosts() {
+        let (expander, _) = expander().await;
+        for link in [
+            "https://example.com/hop",
+            "https://nottinyurl.com/hop",
+            "https://tinyurl.com.example.com/hop",
+            "ftp://tinyurl.com/hop",
+        ] {
+            assert!(
+                matches!(expander.expand(link).await, Err(ExpandError::NotAllowed(_))),
+                "{link} was expanded"
+            );
+        }
+        assert!(matches!(
+            expander.expand("not a link").await,
+            Err(ExpandError::BadUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stops_at_pages_and_loops() {
+        let (expander, port) = expander().await;
+        let page = format!("http://bit.ly:{port}/page");
+        assert_eq!(expander.expand(&page).await.unwrap().as_str(), page);
+        assert!(matches!(
+            expander.expand(&format!("http://bit.ly:{port}/loop")).await,
+            Err(ExpandError::TooManyHops)
+        ));
+    }
+}
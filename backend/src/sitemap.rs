@@ -0,0 +1,233 @@
+//! robots.txt and sitemaps, so that search engines can find published puzzles
+//!
+//! The sitemap at `/sitemap.xml` is an index of pages which live under
+//! `/sitemap/`, which is why `sitemap` is a reserved role name.
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use common::{APIError, APIResult};
+use database::{models, Connection};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{config::ConfigState, state::BackendState};
+
+/// How many URLs each page of the sitemap contains, the protocol permits
+/// up to 50,000 but smaller pages are kinder to the database
+const PAGE_SIZE: i64 = 5_000;
+
+/// Crawlers are welcome everywhere except the API and pages which only make
+/// sense when logged in
+const DISALLOWED: &[&str] = &["/api/", "/settings", "/admin", "/-/", "/puzzle/_new"];
+
+#[derive(Debug, PartialEq, Eq)]
+enum Section {
+    Roles,
+    Puzzles,
+}
+
+impl Section {
+    fn name(&self) -> &'static str {
+        match self {
+            Section::Roles => "roles",
+            Section::Puzzles => "puzzles",
+        }
+    }
+
+    /// Parse a page name, such as `puzzles-3.xml`, pages count from 1
+    fn parse(page: &str) -> Option<(Self, i64)> {
+        let (section, number) = page.strip_suffix(".xml")?.rsplit_once('-')?;
+        let section = match section {
+            "roles" => Section::Roles,
+            "puzzles" => Section::Puzzles,
+            _ => return None,
+        };
+        match number.parse() {
+            Ok(number) if number > 0 => Some((section, number)),
+            _ => None,
+        }
+    }
+}
+
+/// Escape text for XML, which is equally good for HTML
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_response(body: String) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/xml"),
+            (header::CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn page_count(entries: i64) -> i64 {
+    (entries + PAGE_SIZE - 1) / PAGE_SIZE
+}
+
+async fn robots_txt(State(config): State<ConfigState>) -> Response {
+    let mut body = String::from("User-agent: *\n");
+    for path in DISALLOWED {
+        body.push_str(&format!("Disallow: {path}\n"));
+    }
+    body.push_str(&format!("\nSitemap: {}\n", config.site_url("/sitemap.xml")));
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/plain"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[tracing::instrument(skip_all)]
+async fn sitemap_index(
+    State(config): State<ConfigState>,
+    mut db: Connection,
+) -> APIResult<Response> {
+    let roles = models::Role::count(&mut db)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?;
+    let puzzles = models::Puzzle::count_published(&mut db)
+        .await
+        .map_err(|e| APIError::DatabaseError(e.to_string()))?;
+
+    let mut body = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for (section, entries) in [(Section::Roles, roles), (Section::Puzzles, puzzles)] {
+        for page in 1..=page_count(entries) {
+            let loc = config.site_url(&format!("/sitemap/{}-{page}.xml", section.name()));
+            body.push_str(&format!(
+                "  <sitemap><loc>{}</loc></sitemap>\n",
+                escape(&loc)
+            ));
+        }
+    }
+    body.push_str("</sitemapindex>\n");
+
+    Ok(xml_response(body))
+}
+
+#[tracing::instrument(skip(config, db))]
+async fn sitemap_page(
+    Path(page): Path<String>,
+    State(config): State<ConfigState>,
+    mut db: Connection,
+) -> APIResult<Response> {
+    let (section, number) = Section::parse(&page).ok_or(APIError::ObjectNotFound)?;
+    let offset = (number - 1) * PAGE_SIZE;
+
+    // Each entry is the path to the page, and when it was last changed
+    let entries: Vec<(String, Option<OffsetDateTime>)> = match section {
+        Section::Roles => models::Role::page_with_last_published(&mut db, offset, PAGE_SIZE)
+            .await
+            .map_err(|e| APIError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|(role, updated)| (format!("/role/{}", role.uuid), updated))
+            .collect(),
+        Section::Puzzles => models::Puzzle::published_page(&mut db, offset, PAGE_SIZE)
+            .await
+            .map_err(|e| APIError::DatabaseError(e.to_string()))?
+            .into_iter()
+            .map(|puzzle| (format!("/puzzle/{}", puzzle.uuid), Some(puzzle.updated_at)))
+            .collect(),
+    };
+    if entries.is_empty() {
+        return Err(APIError::ObjectNotFound);
+    }
+
+    let mut body = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for (path, updated) in entries {
+        body.push_str("  <url><loc>");
+        body.push_str(&escape(&config.site_url(&path)));
+        body.push_str("</loc>");
+        if let Some(updated) = updated {
+            body.push_str("<lastmod>");
+            body.push_str(
+                &updated
+                    .format(&Rfc3339)
+                    .map_err(|e| APIError::Generic(e.to_string()))?,
+            );
+            body.push_str("</lastmod>");
+        }
+        body.push_str("</url>\n");
+    }
+    body.push_str("</urlset>\n");
+
+    Ok(xml_response(body))
+}
+
+pub fn router() -> Router<BackendState> {
+    Router::new()
+        .route("/robots.txt", get(robots_txt))
+        .route("/sitemap.xml", get(sitemap_index))
+        .route("/sitemap/:page", get(sitemap_page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn section_pages() {
+        assert_eq!(Section::parse("roles-1.xml"), Some((Section::Roles, 1)));
+        assert_eq!(
+            Section::parse("puzzles-12.xml"),
+            Some((Section::Puzzles, 12))
+        );
+        for bad in [
+            "roles-0.xml",
+            "roles--1.xml",
+            "roles-1",
+            "roles-one.xml",
+            "roles.xml",
+            "tags-1.xml",
+            "-1.xml",
+            "puzzles-1.xml.gz",
+            "",
+        ] {
+            assert_eq!(Section::parse(bad), None, "{bad:?} parsed");
+        }
+        for section in [Section::Roles, Section::Puzzles] {
+            let page = format!("{}-3.xml", section.name());
+            assert_eq!(Section::parse(&page), Some((section, 3)));
+        }
+    }
+
+    #[test]
+    fn page_counts() {
+        assert_eq!(page_count(0), 0);
+        assert_eq!(page_count(1), 1);
+        assert_eq!(page_count(PAGE_SIZE), 1);
+        assert_eq!(page_count(PAGE_SIZE + 1), 2);
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("plain text"), "plain text");
+        assert_eq!(
+            escape("<a href=\"x?a=1&b='2'\">"),
+            "&lt;a href=&quot;x?a=1&amp;b=&apos;2&apos;&quot;&gt;"
+        );
+        // Ampersands are escaped first, so entities are not mangled
+        assert_eq!(escape("&lt;"), "&amp;lt;");
+    }
+}
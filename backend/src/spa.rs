@@ -4,8 +4,9 @@
 use std::{collections::HashMap, ffi::OsStr};
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::{StatusCode, Uri},
+    http::{HeaderMap, StatusCode, Uri},
     response::{IntoResponse, Response},
 };
 
@@ -21,6 +22,7 @@ use url::Url;
 use crate::{
     config::ConfigState,
     login::{login_flow_status, PrivateCookies},
+    ssrcache::SsrCache,
 };
 
 static SPA_FILES: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/../frontend/dist");
@@ -88,7 +90,7 @@ async fn ssr_render(
     base: &Url,
     login: Option<&str>,
     userinfo: Option<UserInfo>,
-) -> Response {
+) -> String {
     // Acquire index.html
     let all_html = SPA_FILES
         .get_file("index.html")
@@ -156,23 +158,15 @@ async fn ssr_render(
     full_body.push_str("</body>");
     full_body.push_str(rest);
 
-    (
-        Response::builder()
-            .header("Content-Type", "text/html")
-            .header("Content-Length", format!("{}", full_body.len()))
-            .header("Cache-Control", "no-store")
-            .status(StatusCode::OK)
-            .body(())
-            .unwrap(),
-        full_body,
-    )
-        .into_response()
+    full_body
 }
 
 pub async fn spa_handler(
     uri: Uri,
+    headers: HeaderMap,
     Query(query): Query<HashMap<String, String>>,
     State(config): State<ConfigState>,
+    State(cache): State<SsrCache>,
     cookies: Cookies,
     privatecookies: PrivateCookies,
     mut db: Connection,
@@ -191,6 +185,7 @@ pub async fn spa_handler(
                 gravatar_hash: user.identity().gravatar_hash.clone(),
                 roles: roles.into_iter().map(|r| r.uuid).collect(),
                 default_role: user.identity().default_role_uuid(),
+                is_admin: user.identity().is_admin,
             }),
             Err(e) => {
                 warn!("Unable to read role data during SSR: {e:?}");
@@ -198,12 +193,64 @@ pub async fn spa_handler(
             }
         },
     };
-    ssr_render(
-        uri,
-        query,
-        &config.base_url,
-        login_cookie.as_ref().map(|cookie| cookie.value()),
-        userinfo,
+
+    if login_cookie.is_some() || userinfo.is_some() {
+        let body = ssr_render(
+            uri,
+            query,
+            &config.base_url,
+            login_cookie.as_ref().map(|cookie| cookie.value()),
+            userinfo,
+        )
+        .await;
+        return (
+            Response::builder()
+                .header("Content-Type", "text/html")
+                .header("Content-Length", format!("{}", body.len()))
+                .header("Cache-Control", "no-store")
+                .status(StatusCode::OK)
+                .body(())
+                .unwrap(),
+            body,
+        )
+            .into_response();
+    }
+
+    // Logged out visitors all see the same thing, so may share a cached page
+    let key = uri
+        .path_and_query()
+        .map(|pq| pq.as_str().to_string())
+        .unwrap_or_else(|| uri.path().to_string());
+    let page = match cache.get(&key) {
+        Some(page) => page,
+        None => {
+            let generation = cache.generation();
+            let body = ssr_render(uri, query, &config.base_url, None, None).await;
+            cache.insert(&key, body, generation)
+        }
+    };
+
+    let mut response = Response::builder()
+        .header("Cache-Control", "no-cache")
+        .header("Vary", "Cookie");
+    for (name, value) in page.validators() {
+        response = response.header(name, value);
+    }
+    if page.is_fresh_for(&headers) {
+        return response
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap()
+            .into_response();
+    }
+    (
+        response
+            .header("Content-Type", "text/html")
+            .header("Content-Length", format!("{}", page.body.len()))
+            .status(StatusCode::OK)
+            .body(())
+            .unwrap(),
+        page.body.clone(),
     )
-    .await
+        .into_response()
 }
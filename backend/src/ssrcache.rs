@@ -0,0 +1,255 @@
+//! Cache of server side rendered pages for logged out visitors
+//!
+//! Everyone who is logged out sees the same page for any given path and
+//! query, so there is no need to render it more than once.  Pages show lists
+//! of puzzles, roles, and tags, and we cannot cheaply tell which page shows
+//! what, so any change announced by an activity empties the whole cache.
+//! Pages also expire after a few minutes, in case a change is ever missed.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::http::{header, HeaderMap, HeaderValue};
+use database::changes;
+use time::{format_description::FormatItem, macros::format_description, Duration, OffsetDateTime};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+/// Once this many pages are cached, the oldest is dropped to make room
+const MAX_PAGES: usize = 1_000;
+
+/// How long a page may be served from the cache before it is rendered again
+const MAX_AGE: Duration = Duration::minutes(5);
+
+/// The format of dates in HTTP headers, which are always in GMT
+const HTTP_DATE: &[FormatItem<'static>] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+pub struct CachedPage {
+    pub body: String,
+    pub etag: String,
+    pub rendered_at: OffsetDateTime,
+}
+
+impl CachedPage {
+    fn new(body: String) -> Self {
+        let etag = format!("\"{:x}\"", md5::compute(&body));
+        // HTTP dates only have whole seconds
+        let rendered_at = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+        Self {
+            body,
+            etag,
+            rendered_at,
+        }
+    }
+
+    fn expired_at(&self, now: OffsetDateTime) -> bool {
+        now - self.rendered_at >= MAX_AGE
+    }
+
+    pub fn last_modified(&self) -> String {
+        self.rendered_at
+            .format(HTTP_DATE)
+            .expect("Unable to format HTTP date")
+    }
+
+    /// Does the request show that the client already has this page?
+    ///
+    /// `If-None-Match` takes precedence over `If-Modified-Since`, as
+    /// required by RFC 9110.
+    pub fn is_fresh_for(&self, headers: &HeaderMap) -> bool {
+        if let Some(tags) = headers.get(header::IF_NONE_MATCH) {
+            return tags.to_str().is_ok_and(|tags| {
+                tags.split(',')
+                    .map(str::trim)
+                    .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.etag)
+            });
+        }
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|since| since.to_str().ok())
+            .and_then(|since| time::PrimitiveDateTime::parse(since, HTTP_DATE).ok())
+            .is_some_and(|since| self.rendered_at <= since.assume_utc())
+    }
+
+    /// The headers which let clients revalidate the page later
+    pub fn validators(&self) -> [(header::HeaderName, HeaderValue); 2] {
+        [
+            (
+                header::ETAG,
+                HeaderValue::from_str(&self.etag).expect("ETag is always valid"),
+            ),
+            (
+                header::LAST_MODIFIED,
+                HeaderValue::from_str(&self.last_modified()).expect("Dates are always valid"),
+            ),
+        ]
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    pages: HashMap<String, Arc<CachedPage>>,
+    /// Bumped every time the cache is emptied, so that pages rendered from
+    /// data which changed during rendering are not kept
+    generation: u64,
+}
+
+#[derive(Clone)]
+pub struct SsrCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl SsrCache {
+    /// Create the cache, and start listening for changes.  This must be
+    /// called from within the runtime.
+    pub fn new() -> Self {
+        let cache = Self {
+            inner: Arc::new(Mutex::new(Inner::default())),
+        };
+        let mut changes = changes::subscribe();
+        tokio::spawn({
+            let cache = cache.clone();
+            async move {
+                loop {
+                    match changes.recv().await {
+                        Ok(change) => {
+                            debug!("Emptying SSR cache because of {change:?}");
+                            cache.clear();
+                        }
+                        Err(RecvError::Lagged(missed)) => {
+                            warn!("SSR cache missed {missed} changes, emptying it");
+                            cache.clear();
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            }
+        });
+        cache
+    }
+
+    fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pages.clear();
+        inner.generation += 1;
+    }
+
+    /// The current generation, which must be read before rendering a page
+    /// which is to be passed to [SsrCache::insert]
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().unwrap().generation
+    }
+
+    pub fn get(&self, key: &str) -> Option<Arc<CachedPage>> {
+        let now = OffsetDateTime::now_utc();
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pages.get(key)?.expired_at(now) {
+            inner.pages.remove(key);
+            return None;
+        }
+        inner.pages.get(key).cloned()
+    }
+
+    /// Remember a freshly rendered page, unless anything changed while it
+    /// was being rendered
+    pub fn insert(&self, key: &str, body: String, generation: u64) -> Arc<CachedPage> {
+        let page = Arc::new(CachedPage::new(body));
+        let mut inner = self.inner.lock().unwrap();
+        if inner.generation == generation {
+            if inner.pages.len() >= MAX_PAGES {
+                let now = page.rendered_at;
+                inner.pages.retain(|_, cached| !cached.expired_at(now));
+            }
+            if inner.pages.len() >= MAX_PAGES {
+                if let Some(oldest) = inner
+                    .pages
+                    .iter()
+                    .min_by_key(|(_, page)| page.rendered_at)
+                    .map(|(key, _)| key.clone())
+                {
+                    inner.pages.remove(&oldest);
+                }
+            }
+            inner.pages.insert(key.to_string(), page.clone());
+        }
+        page
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page() -> CachedPage {
+        CachedPage {
+            body: "<html></html>".into(),
+            etag: "\"abc123\"".into(),
+            rendered_at: time::macros::datetime!(2023-06-15 12:30:00 UTC),
+        }
+    }
+
+    fn fresh(headers: &[(header::HeaderName, &'static str)]) -> bool {
+        let headers: HeaderMap = headers
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_static(value)))
+            .collect();
+        page().is_fresh_for(&headers)
+    }
+
+    #[test]
+    fn if_none_match() {
+        use header::IF_NONE_MATCH;
+        assert!(fresh(&[(IF_NONE_MATCH, "\"abc123\"")]));
+        assert!(fresh(&[(IF_NONE_MATCH, "W/\"abc123\"")]));
+        assert!(fresh(&[(IF_NONE_MATCH, "\"other\", W/\"abc123\"")]));
+        assert!(fresh(&[(IF_NONE_MATCH, "*")]));
+        assert!(!fresh(&[(IF_NONE_MATCH, "\"other\"")]));
+        assert!(!fresh(&[(IF_NONE_MATCH, "abc123")]));
+        assert!(!fresh(&[(IF_NONE_MATCH, "W/\"abc1234\"")]));
+        assert!(!fresh(&[]));
+    }
+
+    #[test]
+    fn if_modified_since() {
+        use header::IF_MODIFIED_SINCE;
+        assert_eq!(page().last_modified(), "Thu, 15 Jun 2023 12:30:00 GMT");
+        assert!(fresh(&[(
+            IF_MODIFIED_SINCE,
+            "Thu, 15 Jun 2023 12:30:00 GMT"
+        )]));
+        assert!(fresh(&[(
+            IF_MODIFIED_SINCE,
+            "Fri, 16 Jun 2023 08:00:00 GMT"
+        )]));
+        assert!(!fresh(&[(
+            IF_MODIFIED_SINCE,
+            "Thu, 15 Jun 2023 12:29:59 GMT"
+        )]));
+        assert!(!fresh(&[(IF_MODIFIED_SINCE, "yesterday")]));
+    }
+
+    #[test]
+    fn if_none_match_wins() {
+        use header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+        assert!(!fresh(&[
+            (IF_NONE_MATCH, "\"other\""),
+            (IF_MODIFIED_SINCE, "Fri, 16 Jun 2023 08:00:00 GMT"),
+        ]));
+        assert!(fresh(&[
+            (IF_NONE_MATCH, "\"abc123\""),
+            (IF_MODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT"),
+        ]));
+    }
+
+    #[test]
+    fn pages_expire() {
+        let page = page();
+        assert!(!page.expired_at(page.rendered_at));
+        assert!(!page.expired_at(page.rendered_at + MAX_AGE - Duration::SECOND));
+        assert!(page.expired_at(page.rendered_at + MAX_AGE));
+    }
+}
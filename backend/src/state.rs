@@ -4,7 +4,10 @@
 use axum::extract::FromRef;
 use database::Pool;
 
-use crate::{cli::Cli, config::ConfigState, login::Providers};
+use crate::{
+    cli::Cli, config::ConfigState, login::Providers, ratelimit::RateLimits, shortener::Expander,
+    ssrcache::SsrCache, sudokupad::PuzzleFetcher,
+};
 
 #[derive(Clone, FromRef)]
 pub struct BackendState {
@@ -12,15 +15,30 @@ pub struct BackendState {
     pool: Pool,
     providers: Providers,
     cli: Cli,
+    limits: RateLimits,
+    expander: Expander,
+    fetcher: PuzzleFetcher,
+    ssr_cache: SsrCache,
 }
 
 impl BackendState {
-    pub fn new(cli: Cli, config: ConfigState, pool: Pool, providers: Providers) -> Self {
+    pub fn new(
+        cli: Cli,
+        config: ConfigState,
+        pool: Pool,
+        providers: Providers,
+        expander: Expander,
+        fetcher: PuzzleFetcher,
+    ) -> Self {
         Self {
             config,
             pool,
             providers,
             cli,
+            limits: RateLimits::default(),
+            expander,
+            fetcher,
+            ssr_cache: SsrCache::new(),
         }
     }
 }
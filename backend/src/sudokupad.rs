@@ -0,0 +1,257 @@
+//! Fetching puzzles stored by SudokuPad
+//!
+//! SudokuPad links such as `https://sudokupad.app/<id>` do not carry the
+//! puzzle in the link, instead the ID names a puzzle stored by SudokuPad.
+//! We fetch such puzzles from a configurable endpoint and decode them.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use common::APIError;
+use puzzleutils::formats::{self, Decoded, Format};
+use reqwest::{Client, StatusCode};
+use tracing::info;
+use url::Url;
+
+use crate::{config::SudokuPad, shortener::on_hosts};
+
+/// Stored puzzles are a few kilobytes, anything much larger is not a puzzle
+const MAX_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug)]
+pub enum FetchError {
+    NotFound(String),
+    Http(String),
+    TooLarge(String),
+    Undecodable(String),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NotFound(id) => write!(f, "SudokuPad has no puzzle {id}"),
+            FetchError::Http(e) => write!(f, "Unable to fetch puzzle from SudokuPad: {e}"),
+            FetchError::TooLarge(id) => write!(f, "SudokuPad puzzle {id} is too large"),
+            FetchError::Undecodable(id) => write!(f, "Unable to decode SudokuPad puzzle {id}"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<FetchError> for APIError {
+    fn from(e: FetchError) -> Self {
+        match e {
+            FetchError::NotFound(_) => APIError::ObjectNotFound,
+            e => APIError::Generic(e.to_string()),
+        }
+    }
+}
+
+struct Inner {
+    client: Client,
+    hosts: Vec<String>,
+    fetch_url: String,
+    timeout: Duration,
+}
+
+#[derive(Clone)]
+pub struct PuzzleFetcher {
+    inner: Arc<Inner>,
+}
+
+/// Is this a character SudokuPad uses in puzzle IDs?
+fn is_id_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '-' || ch == '_'
+}
+
+/// Decode a stored puzzle, which is prefixed with its format much as
+/// SudokuPad links are
+fn decode_payload(payload: &str) -> Option<Decoded> {
+    let payload = payload.trim();
+    let (data, format) = if let Some(data) = payload.strip_prefix("fpuzzles") {
+        (data, Some(Format::FPuzzles))
+    } else if let Some(data) = payload
+        .strip_prefix("ctc")
+        .or_else(|| payload.strip_prefix("scl"))
+    {
+        (data, Some(Format::Ctc))
+    } else {
+        (payload, None)
+    };
+    formats::decode(data, format).ok()
+}
+
+impl PuzzleFetcher {
+    pub fn new(config: &SudokuPad) -> reqwest::Result<Self> {
+        Ok(Self::with_client(Client::builder().build()?, config))
+    }
+
+    /// Build a fetcher around the given client
+    pub fn with_client(client: Client, config: &SudokuPad) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client,
+                hosts: config
+                    .hosts
+                    .iter()
+                    .map(|h| h.to_ascii_lowercase())
+                    .collect(),
+                fetch_url: config.fetch_url.clone(),
+                timeout: Duration::from_secs(config.timeout_secs),
+            }),
+        }
+    }
+
+    /// The stored puzzle ID in a SudokuPad link, if it has one
+    ///
+    /// Links carrying their puzzle inline are not short links, and nor is
+    /// anything `puzzleutils` can already decode.
+    pub fn short_id(&self, link: &str) -> Option<String> {
+        let url = Url::parse(link.trim()).ok()?;
+        if !on_hosts(&url, &self.inner.hosts) || formats::decode(link, None).is_ok() {
+            return None;
+        }
+        let id = url
+            .query_pairs()
+            .find(|(key, _)| key == "puzzleid")
+            .map(|(_, value)| value.to_string())
+            .or_else(|| {
+                url.path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .map(String::from)
+            })?;
+        let inline = ["fpuzzles", "ctc", "scl"]
+            .iter()
+            .any(|prefix| id.starts_with(prefix));
+        (!id.is_empty() && !inline && id.chars().all(is_id_char)).then_some(id)
+    }
+
+    /// Fetch and decode the puzzle SudokuPad stores under the given ID
+    pub async fn fetch(&self, id: &str) -> Result<Decoded, FetchError> {
+        let url = self.inner.fetch_url.replace("{id}", id);
+        let mut response = self
+            .inner
+            .client
+            .get(url)
+            .timeout(self.inner.timeout)
+            .send()
+            .await
+            .map_err(|e| FetchError::Http(e.to_string()))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => return Err(FetchError::NotFound(id.to_string())),
+            status if !status.is_success() => return Err(FetchError::Http(status.to_string())),
+            _ => {}
+        }
+        let too_large = || FetchError::TooLarge(id.to_string());
+        if response.content_length().unwrap_or(0) > MAX_PAYLOAD_BYTES as u64 {
+            return Err(too_large());
+        }
+        // The length may be missing or wrong, so check as the body arrives
+        let mut payload = vec![];
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| FetchError::Http(e.to_string()))?
+        {
+            if payload.len() + chunk.len() > MAX_PAYLOAD_BYTES {
+                return Err(too_large());
+            }
+            payload.extend_from_slice(&chunk);
+        }
+        let payload =
+            String::from_utf8(payload).map_err(|_| FetchError::Undecodable(id.to_string()))?;
+        let decoded =
+            decode_payload(&payload).ok_or_else(|| FetchError::Undecodable(id.to_string()))?;
+        info!("Fetched SudokuPad puzzle {id} as {}", decoded.format);
+        Ok(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use axum::{routing::get, Router};
+
+    use super::*;
+
+    const PAYLOAD: &str = "fpuzzlesN4IgzglgXgpiBcBOANCA5gJwgEwQbT1ADcBDAGwFc54BWVNCImAOwQBcMqBfZY8qhAGZ6jFu04wewKVJClK1AOwimreB268ZWrQF1khOf2oA2FWPUTt03kYUIAjObUbJt+QKTPxmvvdrelr7WXPqEsh7UKOiiLlbuxggAHIGuITo2fp5mMao+btJhWdQpuRZpGdZ22anxmVWRQrXcRdXUACzNBQ2J8KUMeUHdCf7CZXHBGW2OXaEGxQjK4/npmdPwAEyzlVON8DkD5VZFEb0HsSs79SOeW8tDp/79Fw9686vrnfcVC/BO33V1tFDhNhms9nQAS13ldHp5noMfj1/EsQZdfsCXmldLouEA===";
+
+    /// Serve stored puzzles as SudokuPad would
+    async fn stub_server() -> SocketAddr {
+        let huge = "x".repeat(MAX_PAYLOAD_BYTES + 1);
+        let app = Router::new()
+            .route("/api/puzzle/stored", get(|| async { PAYLOAD }))
+            .route("/api/puzzle/garbage", get(|| async { "Not a puzzle" }))
+            .route(
+                "/api/puzzle/huge",
+                get(move || {
+                    let huge = huge.clone();
+                    async move { huge }
+                }),
+            );
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    fn fetcher(fetch_url: String) -> PuzzleFetcher {
+        let config = SudokuPad {
+            fetch_url,
+            ..SudokuPad::default()
+        };
+        PuzzleFetcher::new(&config).unwrap()
+    }
+
+    async fn stub_fetcher() -> PuzzleFetcher {
+        let addr = stub_server().await;
+        fetcher(format!("http://{addr}/api/puzzle/{{id}}"))
+    }
+
+    #[test]
+    fn short_ids() {
+        let fetcher = fetcher(SudokuPad::default().fetch_url);
+        for (link, id) in [
+            ("https://sudokupad.app/abc-123_x", Some("abc-123_x")),
+            ("https://SudokuPad.app/abc", Some("abc")),
+            ("https://beta.sudokupad.app/abc", Some("abc")),
+            ("https://sudokupad.app/sudoku/abc", Some("abc")),
+            ("https://sudokupad.app/?puzzleid=abc", Some("abc")),
+            ("https://app.crackingthecryptic.com/sudoku/abc", Some("abc")),
+            ("https://sudokupad.app/", None),
+            ("https://sudokupad.app/a%20b", None),
+            ("https://sudokupad.app/fpuzzlesN4Igz", None),
+            ("https://sudokupad.app/ctcN4Igz", None),
+            ("https://example.com/abc", None),
+            ("not a link", None),
+        ] {
+            assert_eq!(fetcher.short_id(link).as_deref(), id, "{link}");
+        }
+    }
+
+    #[tokio::test]
+    async fn fetches_stored_puzzles() {
+        let fetcher = stub_fetcher().await;
+        let decoded = fetcher.fetch("stored").await.unwrap();
+        assert_eq!(decoded.format, Format::FPuzzles);
+    }
+
+    #[tokio::test]
+    async fn reports_failures() {
+        let fetcher = stub_fetcher().await;
+        assert!(matches!(
+            fetcher.fetch("missing").await,
+            Err(FetchError::NotFound(id)) if id == "missing"
+        ));
+        assert!(matches!(
+            fetcher.fetch("garbage").await,
+            Err(FetchError::Undecodable(_))
+        ));
+        assert!(matches!(
+            fetcher.fetch("huge").await,
+            Err(FetchError::TooLarge(_))
+        ));
+    }
+}
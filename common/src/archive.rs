@@ -0,0 +1,113 @@
+//! Portable archives of a role's puzzles
+//!
+//! An archive holds everything about a role's puzzles, including restricted
+//! states, as the same [objects] the rest of the API uses.  Tags are
+//! included in full so that an archive can be restored on another instance,
+//! where tag UUIDs will differ but tag names will not.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{clean_short_name, objects};
+
+/// The archive format written by this version of Linkdoku
+pub const ARCHIVE_FORMAT: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Archive {
+    pub format: u32,
+    pub exported_at: String,
+    pub role: objects::Role,
+    pub tags: Vec<objects::Tag>,
+    pub puzzles: Vec<objects::Puzzle>,
+}
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Unable to read archive: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported archive format {0}, expected at most {ARCHIVE_FORMAT}")]
+    UnsupportedFormat(u32),
+    #[error("Puzzle {0} cannot be restored")]
+    BadPuzzle(String),
+}
+
+/// A puzzle as a restore recreates it, everything but its UUIDs is kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoredPuzzle<'a> {
+    pub display_name: &'a str,
+    pub short_name: &'a str,
+    pub visibility: objects::Visibility,
+    pub created_at: &'a str,
+    pub updated_at: &'a str,
+    pub states: &'a [objects::PuzzleState],
+    pub tag_names: Vec<String>,
+}
+
+impl Archive {
+    pub fn new(
+        exported_at: String,
+        role: objects::Role,
+        tags: Vec<objects::Tag>,
+        puzzles: Vec<objects::Puzzle>,
+    ) -> Self {
+        Self {
+            format: ARCHIVE_FORMAT,
+            exported_at,
+            role,
+            tags,
+            puzzles,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Read an archive, refusing any written in a newer format
+    pub fn from_json(text: &str) -> Result<Self, ArchiveError> {
+        let archive: Self = serde_json::from_str(text)?;
+        if archive.format > ARCHIVE_FORMAT {
+            return Err(ArchiveError::UnsupportedFormat(archive.format));
+        }
+        Ok(archive)
+    }
+
+    /// The names of the tags on a puzzle, which is how they are matched up
+    /// when the archive is restored
+    pub fn tag_names(&self, puzzle: &objects::Puzzle) -> Vec<String> {
+        puzzle
+            .tags
+            .iter()
+            .filter_map(|uuid| self.tags.iter().find(|tag| &tag.uuid == uuid))
+            .map(|tag| tag.name.clone())
+            .collect()
+    }
+
+    /// What restoring the archive would create, in order
+    ///
+    /// Puzzles without states, or whose short names would not be accepted
+    /// when creating a puzzle, are refused.
+    pub fn restore_plan(&self) -> Result<Vec<RestoredPuzzle<'_>>, ArchiveError> {
+        self.puzzles
+            .iter()
+            .map(|puzzle| {
+                if puzzle.states.is_empty()
+                    || clean_short_name(&puzzle.short_name, false).as_ref()
+                        != Ok(&puzzle.short_name)
+                {
+                    return Err(ArchiveError::BadPuzzle(puzzle.short_name.clone()));
+                }
+                Ok(RestoredPuzzle {
+                    display_name: &puzzle.display_name,
+                    short_name: &puzzle.short_name,
+                    visibility: puzzle.visibility,
+                    created_at: &puzzle.created_at,
+                    updated_at: &puzzle.updated_at,
+                    states: &puzzle.states,
+                    tag_names: self.tag_names(puzzle),
+                })
+            })
+            .collect()
+    }
+}
@@ -27,16 +27,34 @@ into_resp!(crate::objects::Puzzle);
 into_resp!(crate::objects::Role);
 into_resp!(crate::objects::Tag);
 into_resp!(crate::objects::PuzzleMetadata);
+into_resp!(crate::archive::Archive);
 
+into_resp!(crate::internal::admin::Overview);
+into_resp!(crate::internal::admin::TagList);
+into_resp!(crate::internal::follow::list::Response);
 into_resp!(crate::internal::login::begin::Response);
 into_resp!(crate::internal::login::complete::Response);
 into_resp!(crate::internal::login::providers::Response);
 into_resp!(crate::internal::logout::Response);
-into_resp!(crate::internal::util::expand_tinyurl::Response);
+into_resp!(crate::internal::notification::list::Response);
+into_resp!(crate::internal::notification::unread::Response);
+into_resp!(crate::internal::report::file::Response);
+into_resp!(crate::internal::util::expand_url::Response);
+into_resp!(crate::internal::token::list::Response);
+into_resp!(crate::internal::token::create::Response);
+into_resp!(crate::internal::token::revoke::Response);
 
+into_resp!(crate::public::batch::Response<crate::objects::Puzzle>);
+into_resp!(crate::public::batch::Response<crate::objects::Role>);
+into_resp!(crate::public::batch::Response<crate::objects::Tag>);
+into_resp!(crate::public::batch::Response<crate::objects::PuzzleMetadata>);
 into_resp!(crate::public::puzzle::lookup::Response);
 into_resp!(crate::public::role::puzzles::Response);
+into_resp!(crate::public::role::restore::Response);
 into_resp!(crate::public::tag::list::Response);
+into_resp!(crate::public::tag::by_name::Response);
 into_resp!(crate::public::userinfo::Response);
 into_resp!(crate::public::scaffold::Response);
 into_resp!(crate::public::puzzle::recent_published::Response);
+into_resp!(crate::public::puzzle::check_solution::Response);
+into_resp!(crate::public::puzzle::import::Response);
@@ -2,8 +2,14 @@
 //! available from version to version.  Do not rely on these
 //! unless you are working on Linkdoku itself
 
+pub mod admin;
+pub mod changes;
+pub mod follow;
 pub mod login;
 pub mod logout;
+pub mod notification;
+pub mod report;
+pub mod token;
 pub mod util;
 
 pub const INTERNAL_SEGMENT: &str = "/internal";
@@ -0,0 +1,201 @@
+//! Administration and moderation
+//!
+//! Every call here needs a logged in browser session belonging to an
+//! administrator, API tokens are never accepted.  Identities are made
+//! administrators with the `grant-admin` maintenance command.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    internal::report::{ReportReason, ReportTarget},
+    objects,
+};
+
+/// What administrators are currently keeping an eye on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Overview {
+    pub hidden_puzzles: Vec<objects::PuzzleMetadata>,
+    pub locked_roles: Vec<objects::Role>,
+    /// The moderation queue, oldest first
+    pub reports: Vec<ReportedItem>,
+}
+
+/// A single open report, as seen in the moderation queue
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub uuid: String,
+    /// The display name of the identity which filed the report
+    pub reporter: String,
+    pub reason: ReportReason,
+    pub note: String,
+    pub created_at: String,
+}
+
+/// Everything which is open against one target
+///
+/// If the target has since been deleted then there is no name and nothing
+/// to link to, but the reports can still be closed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReportedItem {
+    pub target: ReportTarget,
+    pub display_name: Option<String>,
+    /// The puzzle to link to, for puzzles and their states
+    pub puzzle: Option<String>,
+    /// The role to link to, for roles
+    pub role: Option<String>,
+    pub reports: Vec<Report>,
+}
+
+/// Every tag, as returned after any change to tags
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TagList {
+    pub tags: Vec<objects::Tag>,
+}
+
+pub mod overview {
+    pub const URI: &str = "/admin/overview";
+
+    pub type Response = super::Overview;
+}
+
+pub mod tag {
+    pub mod create {
+        use serde::{Deserialize, Serialize};
+
+        pub const URI: &str = "/admin/tag/create";
+
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub name: String,
+            pub colour: String,
+            pub black_text: bool,
+            pub description: String,
+        }
+
+        pub type Response = super::super::TagList;
+    }
+
+    pub mod update {
+        use crate::objects;
+
+        pub const URI: &str = "/admin/tag/update";
+
+        /// The tag to update is found by UUID, everything else is replaced
+        pub type Request = objects::Tag;
+
+        pub type Response = super::super::TagList;
+    }
+
+    pub mod delete {
+        use serde::{Deserialize, Serialize};
+
+        pub const URI: &str = "/admin/tag/delete";
+
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub uuid: String,
+        }
+
+        pub type Response = super::super::TagList;
+    }
+
+    pub mod merge {
+        use serde::{Deserialize, Serialize};
+
+        pub const URI: &str = "/admin/tag/merge";
+
+        /// Puzzles tagged `from` are tagged `into` instead, and `from` is deleted
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub from: String,
+            pub into: String,
+        }
+
+        pub type Response = super::super::TagList;
+    }
+}
+
+pub mod puzzle {
+    pub mod hide {
+        use serde::{Deserialize, Serialize};
+
+        pub const URI: &str = "/admin/puzzle/hide";
+
+        /// Hidden puzzles can only be seen by their owners and administrators
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub uuid: String,
+            pub hidden: bool,
+        }
+
+        pub type Response = super::super::Overview;
+    }
+
+    pub mod unpublish {
+        use serde::{Deserialize, Serialize};
+
+        pub const URI: &str = "/admin/puzzle/unpublish";
+
+        /// Unpublished puzzles are restricted, but their owners may publish
+        /// them again
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub uuid: String,
+        }
+
+        pub type Response = super::super::Overview;
+    }
+}
+
+pub mod role {
+    pub mod lock {
+        use serde::{Deserialize, Serialize};
+
+        pub const URI: &str = "/admin/role/lock";
+
+        /// Locked roles, and their puzzles, cannot be changed by their owners
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub uuid: String,
+            pub locked: bool,
+        }
+
+        pub type Response = super::super::Overview;
+    }
+}
+
+pub mod report {
+    pub mod resolve {
+        use serde::{Deserialize, Serialize};
+
+        use crate::internal::report::ReportTarget;
+
+        pub const URI: &str = "/admin/report/resolve";
+
+        /// Closes every open report against the target, once it has been
+        /// dealt with
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub target: ReportTarget,
+        }
+
+        pub type Response = super::super::Overview;
+    }
+
+    pub mod dismiss {
+        use serde::{Deserialize, Serialize};
+
+        use crate::internal::report::ReportTarget;
+
+        pub const URI: &str = "/admin/report/dismiss";
+
+        /// Closes every open report against the target, without anything
+        /// having been done about them
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Request {
+            pub target: ReportTarget,
+        }
+
+        pub type Response = super::super::Overview;
+    }
+}
@@ -0,0 +1,22 @@
+//! Live announcements of changes to puzzles, roles, and tags
+//!
+//! Rather than a request and a response, this is a stream of server-sent
+//! events whose data are each a JSON encoded [`Change`].  Only UUIDs are
+//! announced, so anyone who cares about a change must fetch the object
+//! again, at which point the usual visibility rules apply.
+
+use serde::{Deserialize, Serialize};
+
+pub const URI: &str = "/changes";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Sent first on every connection, since after a reconnection
+    /// anything might have changed in the meantime
+    Connected,
+    Puzzle(String),
+    Role(String),
+    Tag(String),
+    /// Some announcements were missed, so anything might have changed
+    Resync,
+}
@@ -0,0 +1,30 @@
+//! Following roles
+//!
+//! Following a role means being notified whenever it publishes a puzzle,
+//! see [super::notification].
+
+pub mod list {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/follow/list";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Response {
+        /// The UUIDs of every role being followed, oldest follow first
+        pub roles: Vec<String>,
+    }
+}
+
+pub mod set {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/follow/set";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Request {
+        pub role: String,
+        pub follow: bool,
+    }
+
+    pub type Response = super::list::Response;
+}
@@ -0,0 +1,77 @@
+//! The notification inbox
+//!
+//! Notifications are only ever shown within Linkdoku, nothing is emailed.
+//! Like tokens, they belong to the identity of a logged in browser session.
+
+use serde::{Deserialize, Serialize};
+
+/// How many notifications are listed at once
+pub const PAGE_SIZE: usize = 20;
+
+/// What a notification is telling its identity about
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum NotificationEvent {
+    /// A followed role published a puzzle
+    ///
+    /// If the puzzle cannot be seen any more then there is no name for it.
+    RolePublished {
+        role: String,
+        role_name: String,
+        puzzle: String,
+        puzzle_name: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub uuid: String,
+    pub event: NotificationEvent,
+    pub created_at: String,
+    pub read: bool,
+}
+
+pub mod list {
+    use serde::{Deserialize, Serialize};
+
+    use super::Notification;
+
+    pub const URI: &str = "/notification/list";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Request {
+        /// Pages count from zero, newest first
+        pub page: usize,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Response {
+        pub notifications: Vec<Notification>,
+        pub total: usize,
+        pub unread: usize,
+    }
+}
+
+pub mod unread {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/notification/unread";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Response {
+        pub unread: usize,
+    }
+}
+
+pub mod mark_read {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/notification/mark-read";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Request {
+        /// The notifications to mark as read, or all of them if not given
+        pub uuids: Option<Vec<String>>,
+    }
+
+    pub type Response = super::unread::Response;
+}
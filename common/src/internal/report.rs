@@ -0,0 +1,72 @@
+//! Reporting problematic content to administrators
+//!
+//! Reports can only be filed from a logged in browser session, and each
+//! identity may only file a handful in any hour.  Reporting the same thing
+//! for the same reason again, while the first report is still open, simply
+//! replaces the note on the first report.
+
+use serde::{Deserialize, Serialize};
+
+/// What a report is about, by UUID
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReportTarget {
+    Puzzle(String),
+    PuzzleState(String),
+    Role(String),
+}
+
+/// Why something is being reported
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportReason {
+    /// A link in a puzzle no longer works
+    BrokenLink,
+    /// A solution or hint is given away without warning
+    Spoiler,
+    /// The puzzle is someone else's work
+    Plagiarism,
+    /// The content is abusive or breaks the code of conduct
+    Abuse,
+}
+
+impl ReportReason {
+    pub const ALL: [ReportReason; 4] = [
+        ReportReason::BrokenLink,
+        ReportReason::Spoiler,
+        ReportReason::Plagiarism,
+        ReportReason::Abuse,
+    ];
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            ReportReason::BrokenLink => "Broken link",
+            ReportReason::Spoiler => "Unmarked spoiler",
+            ReportReason::Plagiarism => "Plagiarism",
+            ReportReason::Abuse => "Abuse",
+        }
+    }
+}
+
+/// Notes longer than this are refused
+pub const MAX_NOTE_LENGTH: usize = 2000;
+
+pub mod file {
+    use serde::{Deserialize, Serialize};
+
+    use super::{ReportReason, ReportTarget};
+
+    pub const URI: &str = "/report/file";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Request {
+        pub target: ReportTarget,
+        pub reason: ReportReason,
+        pub note: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Response {
+        pub uuid: String,
+        /// Set if this updated a report the caller had already filed
+        pub updated: bool,
+    }
+}
@@ -0,0 +1,79 @@
+//! Personal API tokens
+//!
+//! Tokens let scripts act as an identity by sending an
+//! `Authorization: Bearer <secret>` header instead of a login cookie.
+//! They can only be managed from a logged in browser session, never by
+//! using another token.
+
+use serde::{Deserialize, Serialize};
+
+/// What a token is permitted to do
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum TokenScope {
+    /// See whatever the identity can see, but change nothing
+    ReadOnly,
+    /// Create and change puzzles belonging to the role with this UUID
+    Publish(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub uuid: String,
+    pub name: String,
+    pub scope: TokenScope,
+    pub created_at: String,
+    pub last_used: Option<String>,
+}
+
+pub mod list {
+    use serde::{Deserialize, Serialize};
+
+    use super::TokenInfo;
+
+    pub const URI: &str = "/token/list";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct Response {
+        pub tokens: Vec<TokenInfo>,
+    }
+}
+
+pub mod create {
+    use serde::{Deserialize, Serialize};
+
+    use super::{TokenInfo, TokenScope};
+
+    pub const URI: &str = "/token/create";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct Request {
+        pub name: String,
+        pub scope: TokenScope,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct Response {
+        pub token: TokenInfo,
+        /// The secret to send as the bearer token, this is never shown again
+        pub secret: String,
+    }
+}
+
+pub mod revoke {
+    use serde::{Deserialize, Serialize};
+
+    use super::TokenInfo;
+
+    pub const URI: &str = "/token/revoke";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct Request {
+        pub uuid: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct Response {
+        /// The tokens which remain
+        pub tokens: Vec<TokenInfo>,
+    }
+}
@@ -2,17 +2,23 @@
 //!
 //! You must be logged in to use these
 
-pub mod expand_tinyurl {
+pub mod expand_url {
     use serde::{Deserialize, Serialize};
-    pub const URI: &str = "/util/expand-tinyurl";
+    pub const URI: &str = "/util/expand-url";
 
     #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
     pub struct Request {
-        pub slug: String,
+        /// A shortened link, eg. https://tinyurl.com/e3xu5xb4
+        pub url: String,
     }
 
     #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
     pub struct Response {
+        /// Where the link ended up
+        pub url: String,
+        /// The puzzle found there, as f-puzzles data
         pub replacement: String,
+        /// Anything in the puzzle which f-puzzles could not represent
+        pub unmapped: Vec<String>,
     }
 }
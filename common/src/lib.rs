@@ -8,12 +8,16 @@
 //! linkdoku.  The "private" API is public in the sense that
 //! linkdoku is free software, but it is only intended to be
 //! used by the frontend and makes no stability guarantees.
+//!
+//! Other users of linkdoku should prefer the versioned REST API
+//! in [public::v1], which is described by an OpenAPI document.
 
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod archive;
 #[cfg(feature = "backend")]
 mod impls;
 pub mod internal;
@@ -21,6 +25,7 @@ pub mod objects;
 pub mod public;
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Error)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum APIError {
     /// Client problem, only generated client-side, never returned from the server
     #[error("{0}")]
@@ -64,6 +69,15 @@ pub enum APIError {
     /// Unable to create puzzle shortcut for some reason
     #[error("Cannot create puzzle shortcut")]
     CannotCreatePuzzleShortcut,
+    /// The puzzle state has no solution which can be checked against
+    #[error("No solution available to check against")]
+    NoSolutionAvailable,
+    /// Too many requests have been made recently, try again later
+    #[error("Too many requests, please try again later")]
+    RateLimited,
+    /// Another tag already has the name which was asked for
+    #[error("A tag with that name already exists")]
+    TagNameInUse,
 }
 
 // Every API call possible will return APIResult<Response>
@@ -96,6 +110,7 @@ impl<T> From<APIOutcome<T>> for APIResult<T> {
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum BadShortNameReason {
     NotUnique,
@@ -7,6 +7,7 @@ use serde_json::Value;
 ///
 /// Roles have owners themselves (identities) and names, descriptions, etc.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Role {
     pub uuid: String,
     pub owner: String,
@@ -27,16 +28,44 @@ impl Role {
 
 /// Puzzle data, this is only ever returned as part of [PuzzleState]
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum PuzzleData {
     #[default]
     Nothing,
     URLs(Vec<UrlEntry>),
     Pack(Vec<String>),
-    FPuzzles(Value),
+    FPuzzles(#[cfg_attr(feature = "openapi", schema(value_type = Object))] Value),
+}
+
+impl PuzzleData {
+    /// Remove any embedded solution from this puzzle data
+    pub fn without_solution(self) -> Self {
+        match self {
+            PuzzleData::FPuzzles(mut value) => {
+                if let Some(obj) = value.as_object_mut() {
+                    // f-puzzles keeps the solution as a top level array
+                    obj.remove("solution");
+                    // CtC keeps it as a metadata cage whose value is `solution: ...`
+                    if let Some(Value::Array(cages)) = obj.get_mut("ca") {
+                        cages.retain(|cage| {
+                            !cage
+                                .get("v")
+                                .and_then(Value::as_str)
+                                .map(|v| v.starts_with("solution:"))
+                                .unwrap_or(false)
+                        });
+                    }
+                }
+                PuzzleData::FPuzzles(value)
+            }
+            other => other,
+        }
+    }
 }
 
 /// URL Entries for URL list puzzle states, only ever part of [PuzzleData]
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UrlEntry {
     pub title: String,
     pub url: String,
@@ -51,6 +80,7 @@ pub struct UrlEntry {
 /// Even though a puzzle may be visible to someone, they may be unable to see
 /// some or all [PuzzleState]s depending on their [Visibility].
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Visibility {
     #[default]
     /// Only owner (TODO: and those granted access) can see this
@@ -60,15 +90,37 @@ pub enum Visibility {
     /// This is both [Visibility::Public] and also listed on role pages etc.
     Published,
 }
+
+/// How much feedback does checking a solution against a [PuzzleState] give?
+///
+/// This is chosen by the puzzle owner, since some setters are happy for
+/// solvers to know how many cells are wrong, and others are not.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum SolutionCheck {
+    #[default]
+    /// Solutions may not be checked at all
+    Disabled,
+    /// Solvers are told only whether their solution is correct
+    Verdict,
+    /// Solvers are told how many cells are wrong
+    CountWrong,
+}
+
 /// A state for a [Puzzle], this is only ever part of a Puzzle in the basic
 /// objects API.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct PuzzleState {
     pub uuid: String,
     pub description: String,
     pub data: PuzzleData,
     pub visibility: Visibility,
     pub updated_at: String,
+    #[serde(default)]
+    pub solution_check: SolutionCheck,
+    #[serde(default)]
+    pub hide_solution: bool,
 }
 
 /// Puzzles are what Linkdoku is all about.
@@ -78,6 +130,7 @@ pub struct PuzzleState {
 /// one being the one which is considered the current state of the
 /// puzzle.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Puzzle {
     pub uuid: String,
     pub owner: String,
@@ -94,6 +147,7 @@ pub struct Puzzle {
 /// metadata which a user might care about such as if a puzzle is good
 /// for streaming.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Tag {
     pub uuid: String,
     pub name: String,
@@ -110,6 +164,7 @@ pub struct Tag {
 /// list can be loaded in one fell-swoop without needing to also compute the full
 /// puzzle states visible to the caller.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct PuzzleMetadata {
     pub uuid: String,
     pub owner: String,
@@ -2,10 +2,12 @@
 //! about various things.  A public API is considered stable and has some
 //! amount of version numbering behind it.
 
+pub mod batch;
 pub mod puzzle;
 pub mod role;
 pub mod scaffold;
 pub mod tag;
 pub mod userinfo;
+pub mod v1;
 
 pub const PUBLIC_SEGMENT: &str = "/public";
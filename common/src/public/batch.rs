@@ -0,0 +1,28 @@
+//! Fetching many objects of one kind at once
+//!
+//! Every kind of object which can be fetched at `/{kind}/by-uuid/{uuid}`
+//! can also be fetched in batches by posting a [`Request`] to
+//! `/{kind}/batch`, where the kind is one of `role`, `puzzle`, `tag`, or
+//! `puzzle-metadata`.
+
+use serde::{Deserialize, Serialize};
+
+/// The most objects which may be asked for in one batch
+pub const MAX_BATCH: usize = 100;
+
+/// The URI to fetch batches of the given kind of object from
+pub fn uri(kind: &str) -> String {
+    format!("/{kind}/batch")
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Request {
+    pub uuids: Vec<String>,
+}
+
+/// Any UUIDs which do not correspond to objects which can be seen are
+/// simply omitted
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response<T> {
+    pub objects: Vec<T>,
+}
@@ -13,6 +13,8 @@ pub mod create {
         pub display_name: String,
         pub short_name: String,
         pub initial_state: objects::PuzzleState,
+        #[serde(default)]
+        pub tags: Vec<String>,
     }
 
     pub type Response = objects::Puzzle;
@@ -143,3 +145,89 @@ pub mod recent_published {
         pub puzzles: Vec<objects::PuzzleMetadata>,
     }
 }
+
+pub mod check_solution {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/puzzle/check-solution";
+
+    /// The candidate grid is given in row-major order, with `None` for
+    /// any cells which the solver has not filled in.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct Request {
+        pub state: String,
+        pub grid: Vec<Option<u8>>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Response {
+        Correct,
+        Incorrect,
+        WrongCells(usize),
+    }
+}
+
+pub mod import {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/puzzle/import";
+
+    /// One puzzle to import
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct ManifestEntry {
+        pub short_name: String,
+        pub display_name: String,
+        /// Markdown description for the puzzle's initial state
+        #[serde(default)]
+        pub description: String,
+        /// Tag names (not UUIDs), all of which must already exist
+        #[serde(default)]
+        pub tags: Vec<String>,
+        /// A link to the puzzle which `puzzleutils` can decode, possibly
+        /// via a shortened or SudokuPad short link
+        pub url: String,
+    }
+
+    /// A list of puzzles to import, as found in a JSON or YAML file
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    pub struct Manifest {
+        pub puzzles: Vec<ManifestEntry>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct Request {
+        /// The role to import the puzzles into
+        pub owner: String,
+        #[serde(flatten)]
+        pub manifest: Manifest,
+        /// Only validate the manifest, do not create anything
+        #[serde(default)]
+        pub dry_run: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Outcome {
+        /// The puzzle was created with this UUID
+        Created(String),
+        /// The entry is valid, but this was a dry run
+        Valid,
+        /// The entry could not be imported for this reason
+        Failed(String),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Row {
+        pub short_name: String,
+        pub outcome: Outcome,
+        /// Anything in the puzzle which could not be kept
+        pub warnings: Vec<String>,
+    }
+
+    /// One row per manifest entry, in the same order
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Response {
+        pub rows: Vec<Row>,
+    }
+}
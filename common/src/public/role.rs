@@ -26,13 +26,78 @@ pub mod puzzles {
 
     pub const URI: &str = "/role/puzzles";
 
+    /// How many puzzles are listed at once
+    pub const PAGE_SIZE: usize = 25;
+
+    /// How many tags a listing may be filtered by at once
+    pub const MAX_TAGS: usize = 10;
+
+    /// The order in which puzzles are listed, puzzles have no ratings yet
+    /// so they cannot be sorted by them
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum PuzzleSort {
+        /// Most recently created first
+        #[default]
+        Newest,
+        /// Most recently updated first
+        Updated,
+        /// Alphabetically by display name
+        Name,
+    }
+
     #[derive(Serialize, Deserialize, Debug)]
     pub struct Request {
         pub uuid: String,
+        /// Pages count from zero
+        #[serde(default)]
+        pub page: usize,
+        #[serde(default)]
+        pub sort: PuzzleSort,
+        /// Only puzzles which have every one of these tags are listed,
+        /// there may be at most [`MAX_TAGS`] of them
+        #[serde(default)]
+        pub tags: Vec<String>,
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
     pub struct Response {
         pub puzzles: Vec<objects::PuzzleMetadata>,
+        /// How many puzzles there are across every page
+        pub total: usize,
+    }
+}
+
+pub mod export {
+    use serde::{Deserialize, Serialize};
+
+    pub const URI: &str = "/role/export";
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Request {
+        pub uuid: String,
+    }
+
+    pub type Response = crate::archive::Archive;
+}
+
+pub mod restore {
+    use serde::{Deserialize, Serialize};
+
+    use crate::archive::Archive;
+
+    pub const URI: &str = "/role/restore";
+
+    /// Restore the puzzles of an archive into a role, which need not be
+    /// the role they were exported from
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Request {
+        pub uuid: String,
+        pub archive: Archive,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Response {
+        /// The UUIDs of the restored puzzles, in archive order
+        pub puzzles: Vec<String>,
     }
 }
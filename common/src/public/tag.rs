@@ -17,3 +17,22 @@ pub mod list {
         pub tags: Vec<Tag>,
     }
 }
+
+pub mod by_name {
+    use serde::{Deserialize, Serialize};
+
+    use crate::objects::Tag;
+
+    pub const URI: &str = "/tag/by-name";
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Request {
+        pub names: Vec<String>,
+    }
+
+    /// Any names which do not correspond to tags are simply omitted
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Response {
+        pub tags: Vec<Tag>,
+    }
+}
@@ -13,6 +13,8 @@ pub struct UserInfo {
     pub gravatar_hash: String,
     pub roles: Vec<String>,
     pub default_role: String,
+    #[serde(default)]
+    pub is_admin: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
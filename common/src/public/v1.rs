@@ -0,0 +1,114 @@
+//! Version 1 of the public REST API
+//!
+//! Unlike the older public APIs, these are plain `GET` resources rooted at
+//! [`V1_SEGMENT`] under `/api`.  Successful calls return the object itself
+//! with a `200 OK`, and failures return an [`ErrorBody`] with a suitable
+//! HTTP status code (see [`status_code`]) rather than an [`APIOutcome`].
+//!
+//! The shapes of these responses will not change within a version, new
+//! versions will be rooted elsewhere.
+//!
+//! [`APIOutcome`]: crate::APIOutcome
+
+use serde::{Deserialize, Serialize};
+
+use crate::{objects, APIError};
+
+pub const V1_SEGMENT: &str = "/v1";
+
+/// A role, by UUID or short name
+pub const ROLE: &str = "/roles/:role";
+/// The puzzles of a role which the caller can see
+pub const ROLE_PUZZLES: &str = "/roles/:role/puzzles";
+/// A puzzle, by its role's and its own short names
+pub const ROLE_PUZZLE: &str = "/roles/:role/puzzles/:puzzle";
+/// A puzzle, by UUID
+pub const PUZZLE: &str = "/puzzles/:puzzle";
+/// A single puzzle state, by UUID
+pub const STATE: &str = "/states/:state";
+/// All tags, optionally filtered with `?pattern=`
+pub const TAGS: &str = "/tags";
+/// A tag, by UUID
+pub const TAG: &str = "/tags/:tag";
+/// Search roles, published puzzles, and tags with `?q=`
+pub const SEARCH: &str = "/search";
+/// The OpenAPI document describing this version of the API
+pub const OPENAPI: &str = "/openapi.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PuzzleList {
+    pub puzzles: Vec<objects::PuzzleMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct TagQuery {
+    /// Only tags whose names contain this are listed
+    #[serde(default)]
+    pub pattern: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TagList {
+    pub tags: Vec<objects::Tag>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct SearchQuery {
+    /// Text to look for in names
+    pub q: String,
+}
+
+/// Search results, only published puzzles are ever included
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SearchResults {
+    pub roles: Vec<objects::Role>,
+    pub puzzles: Vec<objects::PuzzleMetadata>,
+    pub tags: Vec<objects::Tag>,
+}
+
+/// The body of any unsuccessful response
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorBody {
+    pub error: APIError,
+    /// A human readable description of the error
+    pub message: String,
+}
+
+impl From<APIError> for ErrorBody {
+    fn from(error: APIError) -> Self {
+        Self {
+            message: error.to_string(),
+            error,
+        }
+    }
+}
+
+/// The HTTP status code which goes with an error
+pub fn status_code(error: &APIError) -> u16 {
+    match error {
+        APIError::ClientIssue(_)
+        | APIError::BadShortName(_)
+        | APIError::BadInput
+        | APIError::TagNameInUse
+        | APIError::UnknownLoginProvider(_)
+        | APIError::BadLoginStateToken => 400,
+        APIError::PermissionDenied => 403,
+        APIError::ObjectNotFound | APIError::NoSolutionAvailable => 404,
+        APIError::RateLimited => 429,
+        APIError::LoginCodeExchangeFailed
+        | APIError::LoginFlowError(_)
+        | APIError::NoIdentityToken
+        | APIError::BadIdentityToken => 502,
+        APIError::Generic(_)
+        | APIError::DatabaseError(_)
+        | APIError::CannotCreatePuzzleShortcut => 500,
+    }
+}
@@ -0,0 +1,208 @@
+//! Round-trip tests for role archives
+//!
+//! An archive must restore exactly the puzzles which were exported, so
+//! every [Puzzle] in one has to survive being written and read back.
+
+use common::{
+    archive::{Archive, ArchiveError, RestoredPuzzle, ARCHIVE_FORMAT},
+    objects::{Puzzle, PuzzleData, PuzzleState, Role, SolutionCheck, Tag, UrlEntry, Visibility},
+};
+use serde_json::{json, Value};
+
+fn tag(uuid: &str, name: &str) -> Tag {
+    Tag {
+        uuid: uuid.into(),
+        name: name.into(),
+        colour: "#00ff00".into(),
+        black_text: true,
+        description: format!("{name} puzzles"),
+    }
+}
+
+fn archive() -> Archive {
+    let published = Puzzle {
+        uuid: "puzzle-one".into(),
+        owner: "role-uuid".into(),
+        display_name: "First puzzle".into(),
+        short_name: "first".into(),
+        visibility: Visibility::Published,
+        created_at: "2023-01-02T03:04:05.000000000Z".into(),
+        updated_at: "2023-01-03T03:04:05.000000000Z".into(),
+        states: vec![
+            PuzzleState {
+                uuid: "state-draft".into(),
+                description: "Work in progress".into(),
+                data: PuzzleData::FPuzzles(json!({"size": 9, "solution": [1, 2, 3]})),
+                visibility: Visibility::Restricted,
+                updated_at: "2023-01-02T03:04:05.000000000Z".into(),
+                solution_check: SolutionCheck::CountWrong,
+                hide_solution: true,
+            },
+            PuzzleState {
+                uuid: "state-final".into(),
+                description: "Finished".into(),
+                data: PuzzleData::URLs(vec![UrlEntry {
+                    title: "Play".into(),
+                    url: "https://example.com/play".into(),
+                }]),
+                visibility: Visibility::Published,
+                updated_at: "2023-01-03T03:04:05.000000000Z".into(),
+                solution_check: SolutionCheck::Verdict,
+                hide_solution: false,
+            },
+        ],
+        tags: vec!["tag-killer".into(), "tag-thermo".into()],
+    };
+    let restricted = Puzzle {
+        uuid: "puzzle-two".into(),
+        owner: "role-uuid".into(),
+        display_name: "Secret puzzle".into(),
+        short_name: "secret".into(),
+        visibility: Visibility::Restricted,
+        created_at: "2023-02-02T03:04:05.000000000Z".into(),
+        updated_at: "2023-02-02T03:04:05.000000000Z".into(),
+        states: vec![PuzzleState {
+            uuid: "state-secret".into(),
+            description: String::new(),
+            data: PuzzleData::Nothing,
+            visibility: Visibility::Restricted,
+            updated_at: "2023-02-02T03:04:05.000000000Z".into(),
+            solution_check: SolutionCheck::Disabled,
+            hide_solution: false,
+        }],
+        tags: vec![],
+    };
+
+    Archive::new(
+        "2023-03-01T00:00:00.000000000Z".into(),
+        Role {
+            uuid: "role-uuid".into(),
+            owner: "identity-uuid".into(),
+            short_name: "setter".into(),
+            display_name: "A Setter".into(),
+            description: "Sets puzzles".into(),
+        },
+        vec![tag("tag-killer", "Killer"), tag("tag-thermo", "Thermo")],
+        vec![published, restricted],
+    )
+}
+
+#[test]
+fn archive_round_trips() {
+    let original = archive();
+    let restored = Archive::from_json(&original.to_json().unwrap()).unwrap();
+    assert_eq!(restored, original);
+    assert_eq!(restored.format, ARCHIVE_FORMAT);
+}
+
+#[test]
+fn archived_puzzles_are_plain_puzzles() {
+    let original = archive();
+    let value: Value = serde_json::from_str(&original.to_json().unwrap()).unwrap();
+    let puzzles: Vec<Puzzle> = serde_json::from_value(value["puzzles"].clone()).unwrap();
+    assert_eq!(puzzles, original.puzzles);
+    // Restricted states and hidden solutions are kept, they are the owner's data
+    assert_eq!(puzzles[0].states[0].visibility, Visibility::Restricted);
+    assert_eq!(
+        puzzles[0].states[0].data,
+        PuzzleData::FPuzzles(json!({"size": 9, "solution": [1, 2, 3]}))
+    );
+    assert_eq!(puzzles[1].visibility, Visibility::Restricted);
+}
+
+#[test]
+fn tags_are_named() {
+    let original = archive();
+    assert_eq!(
+        original.tag_names(&original.puzzles[0]),
+        vec!["Killer".to_string(), "Thermo".to_string()]
+    );
+    assert!(original.tag_names(&original.puzzles[1]).is_empty());
+}
+
+#[test]
+fn newer_formats_are_refused() {
+    let mut newer = archive();
+    newer.format = ARCHIVE_FORMAT + 1;
+    match Archive::from_json(&newer.to_json().unwrap()) {
+        Err(ArchiveError::UnsupportedFormat(format)) => assert_eq!(format, ARCHIVE_FORMAT + 1),
+        other => panic!("expected an unsupported format, got {other:?}"),
+    }
+}
+
+#[test]
+fn restores_keep_everything_but_uuids() {
+    let original = archive();
+    let plan = original.restore_plan().unwrap();
+    assert_eq!(plan.len(), original.puzzles.len());
+    for (restored, puzzle) in plan.iter().zip(&original.puzzles) {
+        assert_eq!(
+            *restored,
+            RestoredPuzzle {
+                display_name: &puzzle.display_name,
+                short_name: &puzzle.short_name,
+                visibility: puzzle.visibility,
+                created_at: &puzzle.created_at,
+                updated_at: &puzzle.updated_at,
+                states: &puzzle.states,
+                tag_names: original.tag_names(puzzle),
+            }
+        );
+    }
+
+    // And spelled out, for the parts which matter most
+    assert_eq!(plan[0].visibility, Visibility::Published);
+    assert_eq!(plan[0].created_at, "2023-01-02T03:04:05.000000000Z");
+    assert_eq!(plan[0].updated_at, "2023-01-03T03:04:05.000000000Z");
+    assert_eq!(plan[0].tag_names, ["Killer", "Thermo"]);
+    let states: Vec<_> = plan[0]
+        .states
+        .iter()
+        .map(|state| {
+            (
+                state.visibility,
+                state.updated_at.as_str(),
+                state.hide_solution,
+            )
+        })
+        .collect();
+    assert_eq!(
+        states,
+        [
+            (
+                Visibility::Restricted,
+                "2023-01-02T03:04:05.000000000Z",
+                true
+            ),
+            (
+                Visibility::Published,
+                "2023-01-03T03:04:05.000000000Z",
+                false
+            ),
+        ]
+    );
+    assert_eq!(plan[1].visibility, Visibility::Restricted);
+    assert!(plan[1].tag_names.is_empty());
+}
+
+#[test]
+fn unknown_tags_are_dropped_by_name() {
+    let mut original = archive();
+    original.tags.retain(|tag| tag.name != "Thermo");
+    let plan = original.restore_plan().unwrap();
+    assert_eq!(plan[0].tag_names, ["Killer"]);
+}
+
+#[test]
+fn unrestorable_puzzles_are_refused() {
+    let mut stateless = archive();
+    stateless.puzzles[1].states.clear();
+    let mut badly_named = archive();
+    badly_named.puzzles[0].short_name = "Not Clean".into();
+    for broken in [stateless, badly_named] {
+        assert!(matches!(
+            broken.restore_plan(),
+            Err(ArchiveError::BadPuzzle(_))
+        ));
+    }
+}
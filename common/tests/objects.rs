@@ -0,0 +1,55 @@
+//! Tests for helpers on the common objects
+
+use common::objects::{PuzzleData, UrlEntry};
+use serde_json::{json, Value};
+
+fn stripped(value: Value) -> Value {
+    match PuzzleData::FPuzzles(value).without_solution() {
+        PuzzleData::FPuzzles(value) => value,
+        other => panic!("Puzzle data changed kind: {other:?}"),
+    }
+}
+
+#[test]
+fn strips_fpuzzles_solution() {
+    let value = stripped(json!({
+        "size": 4,
+        "title": "Tiny",
+        "solution": [1, 2, 3, 4, 3, 4, 1, 2, 2, 1, 4, 3, 4, 3, 2, 1],
+    }));
+    assert_eq!(value, json!({ "size": 4, "title": "Tiny" }));
+}
+
+#[test]
+fn strips_ctc_solution() {
+    let value = stripped(json!({
+        "ce": [[{}, {}], [{}, {}]],
+        "ca": [
+            { "v": "title: Tiny" },
+            { "v": "solution: 1221" },
+            { "c": [[0, 0], [0, 1]], "v": "3" },
+        ],
+    }));
+    assert_eq!(
+        value,
+        json!({
+            "ce": [[{}, {}], [{}, {}]],
+            "ca": [
+                { "v": "title: Tiny" },
+                { "c": [[0, 0], [0, 1]], "v": "3" },
+            ],
+        })
+    );
+}
+
+#[test]
+fn leaves_puzzles_without_solutions_alone() {
+    let value = json!({ "size": 9, "ca": [{ "v": "rules: Normal sudoku" }] });
+    assert_eq!(stripped(value.clone()), value);
+
+    let urls = PuzzleData::URLs(vec![UrlEntry {
+        title: "Elsewhere".into(),
+        url: "https://example.com/".into(),
+    }]);
+    assert_eq!(urls.clone().without_solution(), urls);
+}
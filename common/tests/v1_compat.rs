@@ -0,0 +1,267 @@
+//! Compatibility tests for the v1 public API
+//!
+//! Each response type is pinned to the JSON it produced when v1 was
+//! published.  If one of these fails then a response shape has changed,
+//! which must not happen within a version of the API.
+
+use common::{
+    objects::{
+        Puzzle, PuzzleData, PuzzleMetadata, PuzzleState, Role, SolutionCheck, Tag, UrlEntry,
+        Visibility,
+    },
+    public::v1::{self, ErrorBody, PuzzleList, SearchResults, TagList},
+    APIError, BadShortNameReason,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+/// Check that the value serialises to exactly the expected JSON, and that
+/// the JSON deserialises back to the value
+fn check<T>(value: T, expected: Value)
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let actual = serde_json::to_value(&value).unwrap();
+    assert_eq!(actual, expected, "serialised shape changed");
+    let back: T = serde_json::from_value(expected).unwrap();
+    assert_eq!(back, value, "deserialised value changed");
+}
+
+fn role() -> Role {
+    Role {
+        uuid: "role-uuid".into(),
+        owner: "identity-uuid".into(),
+        short_name: "setter".into(),
+        display_name: "A Setter".into(),
+        description: "Sets puzzles".into(),
+    }
+}
+
+fn role_json() -> Value {
+    json!({
+        "uuid": "role-uuid",
+        "owner": "identity-uuid",
+        "short_name": "setter",
+        "display_name": "A Setter",
+        "description": "Sets puzzles",
+    })
+}
+
+fn tag() -> Tag {
+    Tag {
+        uuid: "tag-uuid".into(),
+        name: "Killer".into(),
+        colour: "#ff0000".into(),
+        black_text: false,
+        description: "Killer cages".into(),
+    }
+}
+
+fn tag_json() -> Value {
+    json!({
+        "uuid": "tag-uuid",
+        "name": "Killer",
+        "colour": "#ff0000",
+        "black_text": false,
+        "description": "Killer cages",
+    })
+}
+
+fn metadata() -> PuzzleMetadata {
+    PuzzleMetadata {
+        uuid: "puzzle-uuid".into(),
+        owner: "role-uuid".into(),
+        display_name: "A Puzzle".into(),
+        short_name: "a-puzzle".into(),
+        visibility: Visibility::Published,
+        updated_at: "2023-02-01T12:00:00.000000000Z".into(),
+    }
+}
+
+fn metadata_json() -> Value {
+    json!({
+        "uuid": "puzzle-uuid",
+        "owner": "role-uuid",
+        "display_name": "A Puzzle",
+        "short_name": "a-puzzle",
+        "visibility": "Published",
+        "updated_at": "2023-02-01T12:00:00.000000000Z",
+    })
+}
+
+#[test]
+fn role_shape() {
+    check(role(), role_json());
+}
+
+#[test]
+fn tag_shape() {
+    check(tag(), tag_json());
+}
+
+#[test]
+fn puzzle_metadata_shape() {
+    check(metadata(), metadata_json());
+}
+
+#[test]
+fn puzzle_shape() {
+    let puzzle = Puzzle {
+        uuid: "puzzle-uuid".into(),
+        owner: "role-uuid".into(),
+        display_name: "A Puzzle".into(),
+        short_name: "a-puzzle".into(),
+        visibility: Visibility::Public,
+        created_at: "2023-01-01T12:00:00.000000000Z".into(),
+        updated_at: "2023-02-01T12:00:00.000000000Z".into(),
+        states: vec![
+            PuzzleState {
+                uuid: "state-1".into(),
+                description: "Links".into(),
+                data: PuzzleData::URLs(vec![UrlEntry {
+                    title: "Play".into(),
+                    url: "https://example.com/".into(),
+                }]),
+                visibility: Visibility::Public,
+                updated_at: "2023-01-01T12:00:00.000000000Z".into(),
+                solution_check: SolutionCheck::Verdict,
+                hide_solution: false,
+            },
+            PuzzleState {
+                uuid: "state-2".into(),
+                description: "Grid".into(),
+                data: PuzzleData::FPuzzles(json!({ "size": 9 })),
+                visibility: Visibility::Restricted,
+                updated_at: "2023-02-01T12:00:00.000000000Z".into(),
+                solution_check: SolutionCheck::CountWrong,
+                hide_solution: true,
+            },
+        ],
+        tags: vec!["tag-uuid".into()],
+    };
+    check(
+        puzzle,
+        json!({
+            "uuid": "puzzle-uuid",
+            "owner": "role-uuid",
+            "display_name": "A Puzzle",
+            "short_name": "a-puzzle",
+            "visibility": "Public",
+            "created_at": "2023-01-01T12:00:00.000000000Z",
+            "updated_at": "2023-02-01T12:00:00.000000000Z",
+            "states": [
+                {
+                    "uuid": "state-1",
+                    "description": "Links",
+                    "data": { "URLs": [{ "title": "Play", "url": "https://example.com/" }] },
+                    "visibility": "Public",
+                    "updated_at": "2023-01-01T12:00:00.000000000Z",
+                    "solution_check": "Verdict",
+                    "hide_solution": false,
+                },
+                {
+                    "uuid": "state-2",
+                    "description": "Grid",
+                    "data": { "FPuzzles": { "size": 9 } },
+                    "visibility": "Restricted",
+                    "updated_at": "2023-02-01T12:00:00.000000000Z",
+                    "solution_check": "CountWrong",
+                    "hide_solution": true,
+                },
+            ],
+            "tags": ["tag-uuid"],
+        }),
+    );
+}
+
+#[test]
+fn puzzle_data_shapes() {
+    check(PuzzleData::Nothing, json!("Nothing"));
+    check(
+        PuzzleData::Pack(vec!["puzzle-uuid".into()]),
+        json!({ "Pack": ["puzzle-uuid"] }),
+    );
+}
+
+#[test]
+fn list_shapes() {
+    check(
+        PuzzleList {
+            puzzles: vec![metadata()],
+        },
+        json!({ "puzzles": [metadata_json()] }),
+    );
+    check(
+        TagList { tags: vec![tag()] },
+        json!({ "tags": [tag_json()] }),
+    );
+    check(
+        SearchResults {
+            roles: vec![role()],
+            puzzles: vec![metadata()],
+            tags: vec![tag()],
+        },
+        json!({
+            "roles": [role_json()],
+            "puzzles": [metadata_json()],
+            "tags": [tag_json()],
+        }),
+    );
+}
+
+#[test]
+fn error_shapes() {
+    check(
+        ErrorBody::from(APIError::ObjectNotFound),
+        json!({ "error": "ObjectNotFound", "message": "Object not found" }),
+    );
+    check(
+        ErrorBody::from(APIError::BadShortName(BadShortNameReason::TooShort)),
+        json!({
+            "error": { "BadShortName": "too-short" },
+            "message": "Bad short name, minimum length is 3 characters",
+        }),
+    );
+}
+
+#[test]
+fn error_status_codes() {
+    assert_eq!(v1::status_code(&APIError::BadInput), 400);
+    assert_eq!(v1::status_code(&APIError::TagNameInUse), 400);
+    assert_eq!(v1::status_code(&APIError::PermissionDenied), 403);
+    assert_eq!(v1::status_code(&APIError::ObjectNotFound), 404);
+    assert_eq!(v1::status_code(&APIError::RateLimited), 429);
+    assert_eq!(
+        v1::status_code(&APIError::DatabaseError("oops".into())),
+        500
+    );
+}
+
+#[test]
+fn paths() {
+    assert_eq!(v1::V1_SEGMENT, "/v1");
+    assert_eq!(
+        [
+            v1::ROLE,
+            v1::ROLE_PUZZLES,
+            v1::ROLE_PUZZLE,
+            v1::PUZZLE,
+            v1::STATE,
+            v1::TAGS,
+            v1::TAG,
+            v1::SEARCH,
+            v1::OPENAPI,
+        ],
+        [
+            "/roles/:role",
+            "/roles/:role/puzzles",
+            "/roles/:role/puzzles/:puzzle",
+            "/puzzles/:puzzle",
+            "/states/:state",
+            "/tags",
+            "/tags/:tag",
+            "/search",
+            "/openapi.json",
+        ]
+    );
+}
@@ -17,6 +17,7 @@ use yew_router::prelude::*;
 use yew_toastrack::{use_toaster, Toast, ToastLevel};
 
 use crate::{
+    notification::NotificationBell,
     role::Role,
     user::{LoginButton, LoginStatus},
 };
@@ -82,6 +83,10 @@ fn main_menu_render() -> Html {
                     {"Create Puzzle"}
                 </Link<Route>>
             });
+
+            blocks.push(html! {
+                <NotificationBell />
+            });
         }
     };
 
@@ -97,9 +102,15 @@ fn main_menu_render() -> Html {
 
 git_testament!(VERSION);
 
+/// The version hash of this frontend, which matches the backend's scaffold
+/// version hash when they were built from the same source
+pub fn frontend_version_hash() -> String {
+    hash_version_info(&VERSION)
+}
+
 #[function_component(VersionChecker)]
 pub fn version_checker_render() -> Html {
-    let my_hash = hash_version_info(&VERSION);
+    let my_hash = frontend_version_hash();
     let api = use_apiprovider();
     let toaster = use_toaster();
     let found_data = use_state_eq(|| None::<scaffold::Response>);
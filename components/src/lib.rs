@@ -1,6 +1,8 @@
 pub mod layout;
 pub mod modals;
+pub mod notification;
 pub mod puzzle;
+pub mod report;
 pub mod role;
 pub mod tag;
 pub mod user;
@@ -0,0 +1,162 @@
+//! Notifications, and following the roles which cause them
+//!
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use apiprovider::use_apiprovider;
+use bounce::{use_atom, Atom};
+use frontend_core::{component::icon::*, Route};
+use futures_util::stream::StreamExt;
+use gloo::timers::future::IntervalStream;
+use tracing::warn;
+use yew::{platform::spawn_local, prelude::*};
+use yew_router::prelude::*;
+use yew_toastrack::{use_toaster, Toast, ToastLevel};
+
+use crate::user::LoginStatus;
+
+/// How many notifications are waiting to be read, shared so that reading
+/// them updates the bell straight away
+#[derive(Atom, Default, PartialEq, Eq, Clone, Copy)]
+pub struct UnreadNotifications(pub usize);
+
+/// How often the bell checks for new notifications
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A menu entry for the notification inbox, which shows how many are unread
+/// and pops up a toast when more arrive
+#[function_component(NotificationBell)]
+pub fn notification_bell_render() -> Html {
+    let user_info = use_context::<LoginStatus>().unwrap();
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let unread = use_atom::<UnreadNotifications>();
+
+    use_effect_with_deps(
+        {
+            let unread = unread.clone();
+            move |logged_in: &bool| {
+                let running = Rc::new(Cell::new(*logged_in));
+                if *logged_in {
+                    let running = running.clone();
+                    spawn_local(async move {
+                        let mut ticks = IntervalStream::new(POLL_INTERVAL.as_millis() as u32);
+                        let mut known = None;
+                        while running.get() {
+                            match api.unread_notifications().await {
+                                Ok(found) => {
+                                    if known.is_some_and(|known| found.unread > known) {
+                                        toaster.toast(
+                                            Toast::new("You have new notifications")
+                                                .with_level(ToastLevel::Info)
+                                                .with_lifetime(5000),
+                                        );
+                                    }
+                                    known = Some(found.unread);
+                                    unread.set(UnreadNotifications(found.unread));
+                                }
+                                Err(e) => warn!("Unable to check for notifications: {e}"),
+                            }
+                            if ticks.next().await.is_none() {
+                                break;
+                            }
+                        }
+                    });
+                }
+                move || running.set(false)
+            }
+        },
+        user_info.is_logged_in(),
+    );
+
+    let count = unread.0;
+    html! {
+        <Link<Route> to={Route::Notifications} classes={"panel-block"}>
+            <Icon class={"panel-icon"} icon={if count > 0 { UnreadNotificationIcon } else { NotificationIcon }}/>
+            {"Notifications"}
+            if count > 0 {
+                <span class="tag is-danger is-rounded ml-2">{count}</span>
+            }
+        </Link<Route>>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct FollowButtonProps {
+    pub role: AttrValue,
+}
+
+/// Lets logged in users follow a role, so that they are notified when it
+/// publishes a puzzle.  Nothing is shown to the role's owner, or to anyone
+/// who is not logged in.
+#[function_component(FollowButton)]
+pub fn follow_button_render(props: &FollowButtonProps) -> Html {
+    let user_info = use_context::<LoginStatus>().unwrap();
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let following = use_state_eq(|| None::<bool>);
+
+    let own_role = user_info.roles().iter().any(|r| r == props.role.as_str());
+    let show = user_info.is_logged_in() && !own_role;
+
+    use_effect_with_deps(
+        {
+            let api = api.clone();
+            let following = following.setter();
+            move |(role, show): &(AttrValue, bool)| {
+                if *show {
+                    let role = role.clone();
+                    spawn_local(async move {
+                        match api.followed_roles().await {
+                            Ok(found) => {
+                                following.set(Some(found.roles.iter().any(|r| r == role.as_str())))
+                            }
+                            Err(e) => warn!("Unable to list followed roles: {e}"),
+                        }
+                    });
+                }
+                || ()
+            }
+        },
+        (props.role.clone(), show),
+    );
+
+    let is_following = match (*following, show) {
+        (Some(is_following), true) => is_following,
+        _ => return html! {},
+    };
+
+    let onclick = Callback::from({
+        let role = props.role.clone();
+        let following = following.setter();
+        move |_| {
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let following = following.clone();
+            let role = role.clone();
+            spawn_local(async move {
+                match api.set_following(role.as_str(), !is_following).await {
+                    Ok(found) => {
+                        following.set(Some(found.roles.iter().any(|r| r == role.as_str())))
+                    }
+                    Err(e) => toaster.toast(
+                        Toast::new(format!(
+                            "Unable to change whether you follow this role: {e}"
+                        ))
+                        .with_level(ToastLevel::Danger)
+                        .with_lifetime(5000),
+                    ),
+                }
+            });
+        }
+    });
+
+    html! {
+        <button class={classes!("button", "is-small", is_following.then_some("is-success"))} onclick={onclick}>
+            <span class="icon-text">
+                <Icon icon={if is_following { FollowingIcon } else { FollowIcon }} />
+                <span>{if is_following { "Following" } else { "Follow" }}</span>
+            </span>
+        </button>
+    }
+}
@@ -1,7 +1,5 @@
 //! Puzzle related components
 
-use std::rc::Rc;
-
 use apiprovider::{use_apiprovider, use_cached_value};
 use common::{
     objects::{self, Visibility},
@@ -16,11 +14,12 @@ use crate::{role::Role, utils::NiceDate};
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct PuzzleListProps {
-    pub role: Option<AttrValue>,
     #[prop_or_default]
     pub show_role: bool,
 }
 
+/// The most recently published puzzles, role pages page through their
+/// puzzles instead
 #[function_component(PuzzleList)]
 pub fn puzzle_list_render(props: &PuzzleListProps) -> Html {
     let fallback = html! {};
@@ -45,16 +44,8 @@ fn puzzle_list_inner_render(props: &PuzzleListInnerProps) -> HtmlResult {
     let nav = use_navigator().unwrap();
 
     let list: UseFutureHandle<APIResult<Vec<objects::PuzzleMetadata>>> = use_future_with_deps(
-        {
-            |role: Rc<Option<AttrValue>>| async move {
-                Ok(if let Some(role) = &*role {
-                    api.published_puzzle_list(role.as_str()).await?.puzzles
-                } else {
-                    api.recently_published_puzzles().await?.puzzles
-                })
-            }
-        },
-        props.role.clone(),
+        |_| async move { Ok(api.recently_published_puzzles().await?.puzzles) },
+        (),
     )?;
 
     let list = match list.as_ref() {
@@ -0,0 +1,173 @@
+//! Reporting problematic content to the administrators
+//!
+
+use apiprovider::use_apiprovider;
+use common::internal::report::{ReportReason, ReportTarget, MAX_NOTE_LENGTH};
+use frontend_core::component::icon::*;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_toastrack::{use_toaster, Toast, ToastLevel};
+
+use crate::user::LoginStatus;
+
+#[derive(Properties, PartialEq)]
+pub struct ReportButtonProps {
+    pub target: ReportTarget,
+    /// What the target is called, such as "puzzle"
+    pub noun: AttrValue,
+    /// The puzzle state being shown, if the report might be about just that
+    #[prop_or_default]
+    pub state: Option<AttrValue>,
+}
+
+/// A flag which lets logged in users report the target, nothing is shown
+/// to anyone else
+#[function_component(ReportButton)]
+pub fn report_button_render(props: &ReportButtonProps) -> Html {
+    let user_info = use_context::<LoginStatus>().unwrap();
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let open = use_state_eq(|| false);
+    let sending = use_state_eq(|| false);
+    let reason_ref = use_node_ref();
+    let note_ref = use_node_ref();
+    let state_ref = use_node_ref();
+
+    if !user_info.is_logged_in() {
+        return html! {};
+    }
+
+    let show = Callback::from({
+        let open = open.setter();
+        move |_| open.set(true)
+    });
+    let hide = Callback::from({
+        let open = open.setter();
+        move |_| open.set(false)
+    });
+
+    let submit = Callback::from({
+        let open = open.setter();
+        let sending = sending.setter();
+        let target = props.target.clone();
+        let state = props.state.clone();
+        let reason_ref = reason_ref.clone();
+        let note_ref = note_ref.clone();
+        let state_ref = state_ref.clone();
+        move |_| {
+            let reason = match reason_ref
+                .cast::<HtmlSelectElement>()
+                .and_then(|select| ReportReason::ALL.get(select.selected_index() as usize))
+            {
+                Some(reason) => *reason,
+                None => return,
+            };
+            let note = note_ref
+                .cast::<HtmlTextAreaElement>()
+                .map(|note| note.value())
+                .unwrap_or_default();
+            let target = match &state {
+                Some(state)
+                    if state_ref
+                        .cast::<HtmlInputElement>()
+                        .is_some_and(|check| check.checked()) =>
+                {
+                    ReportTarget::PuzzleState(state.to_string())
+                }
+                _ => target.clone(),
+            };
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let open = open.clone();
+            let sending = sending.clone();
+            sending.set(true);
+            spawn_local(async move {
+                match api.file_report(target, reason, note).await {
+                    Ok(filed) => {
+                        let message = if filed.updated {
+                            "Your earlier report has been updated"
+                        } else {
+                            "Thank you, your report has been passed to the administrators"
+                        };
+                        toaster.toast(
+                            Toast::new(message)
+                                .with_level(ToastLevel::Success)
+                                .with_lifetime(5000),
+                        );
+                        open.set(false);
+                    }
+                    Err(e) => {
+                        toaster.toast(
+                            Toast::new(format!("Unable to send report: {e}"))
+                                .with_level(ToastLevel::Danger)
+                                .with_lifetime(5000),
+                        );
+                    }
+                }
+                sending.set(false);
+            });
+        }
+    });
+
+    let options = ReportReason::ALL.iter().map(|reason| {
+        html! {
+            <option>{reason.describe()}</option>
+        }
+    });
+
+    html! {
+        <>
+            <span class="has-text-grey" title={format!("Report this {}", props.noun)}>
+                <Icon icon={ReportIcon} onclick={show} size={IconSize::Medium} />
+            </span>
+            if *open {
+                <div class="modal is-active">
+                    <div class="modal-background"></div>
+                    <div class="modal-card">
+                        <header class="modal-card-head">
+                            <p class="modal-card-title">{format!("Report this {}", props.noun)}</p>
+                        </header>
+                        <section class="modal-card-body">
+                            <div class="field">
+                                <label class="label">{"Reason"}</label>
+                                <div class="control">
+                                    <div class="select">
+                                        <select ref={reason_ref}>{for options}</select>
+                                    </div>
+                                </div>
+                            </div>
+                            if props.state.is_some() {
+                                <div class="field">
+                                    <label class="checkbox">
+                                        <input type="checkbox" ref={state_ref} />
+                                        {" This is only about the puzzle state being shown"}
+                                    </label>
+                                </div>
+                            }
+                            <div class="field">
+                                <label class="label">{"Note for the administrators"}</label>
+                                <div class="control">
+                                    <textarea class="textarea" ref={note_ref} maxlength={MAX_NOTE_LENGTH.to_string()} />
+                                </div>
+                            </div>
+                        </section>
+                        <footer class="modal-card-foot">
+                            <button class="button is-danger" onclick={submit} disabled={*sending}>
+                                <span class="icon-text">
+                                    <Icon icon={if *sending { SpinnerIcon } else { ReportIcon }} />
+                                    <span>{"Send report"}</span>
+                                </span>
+                            </button>
+                            <button class="button" onclick={hide}>
+                                <span class="icon-text">
+                                    <Icon icon={CancelIcon} />
+                                    <span>{"Cancel"}</span>
+                                </span>
+                            </button>
+                        </footer>
+                    </div>
+                </div>
+            }
+        </>
+    }
+}
@@ -23,6 +23,7 @@ pub enum LoginStatus {
         gravatar_hash: String,
         roles: Vec<String>,
         role: String,
+        is_admin: bool,
     },
 }
 
@@ -35,6 +36,7 @@ impl LoginStatus {
                 display_name,
                 gravatar_hash,
                 roles,
+                is_admin,
                 ..
             } => Self::LoggedIn {
                 uuid: uuid.clone(),
@@ -42,6 +44,7 @@ impl LoginStatus {
                 gravatar_hash: gravatar_hash.clone(),
                 roles: roles.clone(),
                 role,
+                is_admin: *is_admin,
             },
         }
     }
@@ -63,6 +66,10 @@ impl LoginStatus {
             _ => None,
         }
     }
+
+    pub fn is_admin(&self) -> bool {
+        matches! {self, Self::LoggedIn{is_admin: true, ..}}
+    }
 }
 
 #[derive(Debug)]
@@ -74,6 +81,7 @@ pub enum LoginStatusAction {
         gravatar_hash: String,
         roles: Vec<String>,
         default_role: String,
+        is_admin: bool,
     },
     ChosenRole(String),
 }
@@ -91,12 +99,14 @@ impl Reducible for LoginStatus {
                 gravatar_hash,
                 roles,
                 default_role,
+                is_admin,
             } => LoginStatus::LoggedIn {
                 uuid,
                 display_name,
                 gravatar_hash,
                 roles,
                 role: default_role,
+                is_admin,
             },
             LoginStatusAction::ChosenRole(role) => self.choose_role(role),
         }
@@ -125,6 +135,7 @@ pub fn login_user_provider(props: &UserProviderProps) -> Html {
                     role: u.default_role.clone(),
                     uuid: u.uuid.clone(),
                     roles: u.roles.clone(),
+                    is_admin: u.is_admin,
                 }
             })
             .unwrap_or_else(|| {
@@ -317,6 +328,7 @@ pub fn user_menu_button() -> Html {
             gravatar_hash,
             roles,
             role,
+            is_admin,
             ..
         } => {
             let roles = roles
@@ -333,6 +345,16 @@ pub fn user_menu_button() -> Html {
                 })
                 .collect::<Html>();
 
+            let admin = if is_admin {
+                html! {
+                    <Link<Route> to={Route::Admin} classes={"navbar-item"}>
+                        {"Administration"}
+                    </Link<Route>>
+                }
+            } else {
+                html! {}
+            };
+
             html! {
                 <div class={"navbar-item has-dropdown is-hoverable"}>
                     <a class={"navbar-link"}>
@@ -343,6 +365,10 @@ pub fn user_menu_button() -> Html {
                     <div class={"navbar-dropdown is-right"}>
                         {roles}
                         <hr class={"navbar-divider"} />
+                        <Link<Route> to={Route::Settings} classes={"navbar-item"}>
+                            {"Settings"}
+                        </Link<Route>>
+                        {admin}
                         <div class={"navbar-item"}>
                             <div class={"buttons"}>
                                 <LogoutButton />
@@ -0,0 +1,6 @@
+-- Remove solution checking settings
+
+ALTER TABLE puzzle_state
+    DROP COLUMN solution_check;
+
+DROP TYPE solution_check;
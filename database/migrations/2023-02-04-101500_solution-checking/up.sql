@@ -0,0 +1,6 @@
+-- Allow puzzle owners to decide how much feedback solution checking gives
+
+CREATE TYPE solution_check AS ENUM ('disabled', 'verdict', 'count_wrong');
+
+ALTER TABLE puzzle_state
+    ADD COLUMN solution_check solution_check NOT NULL DEFAULT 'disabled';
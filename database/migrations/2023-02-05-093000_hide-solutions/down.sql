@@ -0,0 +1,4 @@
+-- Remove the option to hide embedded solutions
+
+ALTER TABLE puzzle_state
+    DROP COLUMN hide_solution;
@@ -0,0 +1,4 @@
+-- Allow puzzle owners to hide embedded solutions from solvers
+
+ALTER TABLE puzzle_state
+    ADD COLUMN hide_solution BOOLEAN NOT NULL DEFAULT FALSE;
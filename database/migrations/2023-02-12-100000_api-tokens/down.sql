@@ -0,0 +1,3 @@
+-- Remove personal API tokens
+
+DROP TABLE api_token;
@@ -0,0 +1,16 @@
+-- Personal API tokens, so that scripts can act as an identity
+--
+-- Only a SHA-256 hash of each token's secret is stored.  Publishing tokens
+-- are limited to the role they name, read-only tokens have no role.
+
+CREATE TABLE api_token (
+    uuid VARCHAR PRIMARY KEY,
+    owner VARCHAR NOT NULL REFERENCES identity (uuid) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    token_hash VARCHAR NOT NULL UNIQUE,
+    role VARCHAR REFERENCES role (uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_used TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX api_token_by_owner ON api_token(owner);
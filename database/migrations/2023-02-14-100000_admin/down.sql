@@ -0,0 +1,10 @@
+-- Remove administrators and moderation flags
+
+ALTER TABLE role
+  DROP COLUMN locked;
+
+ALTER TABLE puzzle
+  DROP COLUMN hidden;
+
+ALTER TABLE identity
+  DROP COLUMN is_admin;
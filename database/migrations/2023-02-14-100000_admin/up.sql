@@ -0,0 +1,13 @@
+-- Administrators, and the moderation flags they control
+
+ALTER TABLE identity
+  ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Hidden puzzles can only be seen by their owners and administrators,
+-- and owners cannot unhide them
+ALTER TABLE puzzle
+  ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Locked roles, and their puzzles, cannot be changed by their owners
+ALTER TABLE role
+  ADD COLUMN locked BOOLEAN NOT NULL DEFAULT FALSE;
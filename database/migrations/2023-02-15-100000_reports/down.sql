@@ -0,0 +1,6 @@
+-- Remove content reports
+
+DROP TABLE report;
+DROP TYPE report_status;
+DROP TYPE report_reason;
+DROP TYPE report_target;
@@ -0,0 +1,26 @@
+-- Reports of problematic content, filed by users for administrators
+--
+-- Targets are not foreign keys, so that reports outlive what they are about.
+-- Each identity may only have one open report of a given reason about any
+-- given thing, further reports simply update that one.
+
+CREATE TYPE report_target AS ENUM ('puzzle', 'puzzle_state', 'role');
+CREATE TYPE report_reason AS ENUM ('broken_link', 'spoiler', 'plagiarism', 'abuse');
+CREATE TYPE report_status AS ENUM ('open', 'resolved', 'dismissed');
+
+CREATE TABLE report (
+    uuid VARCHAR PRIMARY KEY,
+    reporter VARCHAR NOT NULL REFERENCES identity (uuid) ON DELETE CASCADE,
+    target_kind report_target NOT NULL,
+    target VARCHAR NOT NULL,
+    reason report_reason NOT NULL,
+    note TEXT NOT NULL,
+    status report_status NOT NULL DEFAULT 'open',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    closed_by VARCHAR REFERENCES identity (uuid) ON DELETE SET NULL,
+    closed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE UNIQUE INDEX report_open_once ON report(reporter, target_kind, target, reason)
+    WHERE status = 'open';
+CREATE INDEX report_by_target ON report(target_kind, target);
@@ -0,0 +1,5 @@
+-- Remove follows and notifications
+
+DROP TABLE notification;
+DROP TYPE notification_kind;
+DROP TABLE role_follow;
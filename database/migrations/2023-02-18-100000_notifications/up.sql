@@ -0,0 +1,28 @@
+-- Following roles, and the notifications which identities receive
+--
+-- Each identity is told about any given thing at most once, so that
+-- republishing a puzzle does not notify its followers all over again.
+
+CREATE TABLE role_follow (
+    identity VARCHAR NOT NULL REFERENCES identity (uuid) ON DELETE CASCADE,
+    role VARCHAR NOT NULL REFERENCES role (uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (identity, role)
+);
+
+CREATE INDEX role_follow_by_role ON role_follow(role);
+
+CREATE TYPE notification_kind AS ENUM ('role_published');
+
+CREATE TABLE notification (
+    uuid VARCHAR PRIMARY KEY,
+    identity VARCHAR NOT NULL REFERENCES identity (uuid) ON DELETE CASCADE,
+    kind notification_kind NOT NULL,
+    role VARCHAR NOT NULL REFERENCES role (uuid) ON DELETE CASCADE,
+    puzzle VARCHAR REFERENCES puzzle (uuid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    read BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE UNIQUE INDEX notification_once ON notification(identity, kind, role, puzzle);
+CREATE INDEX notification_by_identity ON notification(identity, created_at);
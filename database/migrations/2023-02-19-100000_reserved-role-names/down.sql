@@ -0,0 +1,3 @@
+-- Renamed roles keep their new names, the old ones remain reserved
+
+SELECT 1;
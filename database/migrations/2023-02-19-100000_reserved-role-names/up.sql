@@ -0,0 +1,12 @@
+-- Rename roles whose short names now clash with top level pages
+--
+-- Names are only reserved when roles are created or renamed, so a role may
+-- already hold one.  Such roles get a suffix from their UUID, which keeps
+-- them unique and within the permitted characters and length.
+
+UPDATE role
+   SET short_name = short_name || '-' || LEFT(MD5(uuid), 6)
+ WHERE short_name IN (
+    'puzzle', 'role', 'settings', 'admin', 'linkdoku', 'api', 'assets',
+    'sitemap', 'embed', 'oembed', 'notifications'
+ );
@@ -4,15 +4,21 @@
 
 use common::{APIError, BadShortNameReason};
 
+pub mod admin;
+pub mod archive;
 pub mod login;
+pub mod notification;
 pub mod puzzle;
+pub mod report;
 pub mod role;
 pub mod tag;
+pub mod token;
 
 pub enum ActivityError {
     PermissionDenied,
     InvalidInput,
     ShortNameInUse,
+    TagNameInUse,
     NotFound,
     Error(diesel::result::Error),
     JsonError(serde_json::Error),
@@ -25,6 +31,7 @@ impl From<ActivityError> for APIError {
     fn from(value: ActivityError) -> Self {
         match value {
             ActivityError::ShortNameInUse => APIError::BadShortName(BadShortNameReason::NotUnique),
+            ActivityError::TagNameInUse => APIError::TagNameInUse,
             ActivityError::NotFound => APIError::ObjectNotFound,
             ActivityError::PermissionDenied => APIError::PermissionDenied,
             ActivityError::InvalidInput => APIError::BadInput,
@@ -0,0 +1,380 @@
+//! Administrative activities such as tag management and moderation
+//!
+//! Every activity here checks that the actor is an administrator before
+//! doing anything else.
+
+use std::collections::BTreeMap;
+
+use common::{
+    internal::{
+        admin::{Overview, Report, ReportedItem, TagList},
+        report::ReportTarget,
+    },
+    objects,
+};
+use diesel_async::AsyncPgConnection;
+use time::format_description::well_known::Iso8601;
+
+use crate::{
+    changes::{announce, announce_puzzle, ChangeEvent},
+    models::{self, Identity, Puzzle, ReportStatus, Role, Visibility},
+};
+
+use super::{report, role, tag, ActivityError, ActivityResult};
+
+async fn require_admin(conn: &mut AsyncPgConnection, actor: &str) -> ActivityResult<()> {
+    if Identity::uuid_is_admin(conn, actor).await? {
+        Ok(())
+    } else {
+        Err(ActivityError::PermissionDenied)
+    }
+}
+
+async fn overview_of(conn: &mut AsyncPgConnection) -> ActivityResult<Overview> {
+    let mut hidden_puzzles = vec![];
+    for puzzle in Puzzle::all_hidden(conn).await? {
+        hidden_puzzles.push(objects::PuzzleMetadata {
+            updated_at: puzzle.updated_at.format(&Iso8601::DEFAULT)?,
+            uuid: puzzle.uuid,
+            owner: puzzle.owner,
+            display_name: puzzle.display_name,
+            short_name: puzzle.short_name,
+            visibility: puzzle.visibility.into(),
+        });
+    }
+    let locked_roles = Role::all_locked(conn)
+        .await?
+        .into_iter()
+        .map(role::into_api_object)
+        .collect();
+
+    Ok(Overview {
+        hidden_puzzles,
+        locked_roles,
+        reports: report_queue(conn).await?,
+    })
+}
+
+/// The name of a reported target, and the puzzle or role to link to
+async fn describe_target(
+    conn: &mut AsyncPgConnection,
+    target: &ReportTarget,
+) -> ActivityResult<ReportedItem> {
+    let (display_name, puzzle, role) = match target {
+        ReportTarget::Puzzle(uuid) => match Puzzle::by_uuid(conn, uuid).await? {
+            Some(puzzle) => (Some(puzzle.display_name), Some(puzzle.uuid), None),
+            None => (None, None, None),
+        },
+        ReportTarget::PuzzleState(uuid) => match models::PuzzleState::by_uuid(conn, uuid).await? {
+            Some(state) => match Puzzle::by_uuid(conn, &state.puzzle).await? {
+                Some(puzzle) => (
+                    Some(format!("{} ({})", puzzle.display_name, state.description)),
+                    Some(puzzle.uuid),
+                    None,
+                ),
+                None => (None, None, None),
+            },
+            None => (None, None, None),
+        },
+        ReportTarget::Role(uuid) => match Role::by_uuid(conn, uuid).await? {
+            Some(role) => (Some(role.display_name), None, Some(role.uuid)),
+            None => (None, None, None),
+        },
+    };
+    Ok(ReportedItem {
+        target: target.clone(),
+        display_name,
+        puzzle,
+        role,
+        reports: vec![],
+    })
+}
+
+/// Every open report, grouped by target with the longest waiting first
+async fn report_queue(conn: &mut AsyncPgConnection) -> ActivityResult<Vec<ReportedItem>> {
+    let mut queue: Vec<ReportedItem> = vec![];
+    let mut reporters: BTreeMap<String, String> = BTreeMap::new();
+    for found in models::Report::all_open(conn).await? {
+        let target = report::target_from_parts(found.target_kind, found.target);
+        let index = match queue.iter().position(|item| item.target == target) {
+            Some(index) => index,
+            None => {
+                queue.push(describe_target(conn, &target).await?);
+                queue.len() - 1
+            }
+        };
+        if !reporters.contains_key(&found.reporter) {
+            let name = Identity::from_uuid(conn, &found.reporter)
+                .await?
+                .map(|identity| identity.display_name)
+                .unwrap_or_default();
+            reporters.insert(found.reporter.clone(), name);
+        }
+        queue[index].reports.push(Report {
+            uuid: found.uuid,
+            reporter: reporters[&found.reporter].clone(),
+            reason: found.reason.into(),
+            note: found.note,
+            created_at: found.created_at.format(&Iso8601::DEFAULT)?,
+        });
+    }
+    Ok(queue)
+}
+
+async fn all_tags(conn: &mut AsyncPgConnection) -> ActivityResult<TagList> {
+    let tags = models::Tag::get_all(conn, "")
+        .await?
+        .into_iter()
+        .map(tag::into_api_object)
+        .collect();
+    Ok(TagList { tags })
+}
+
+/// Tags need a name, and a colour of the form `#rrggbb`
+fn check_tag(name: &str, colour: &str) -> ActivityResult<()> {
+    let colour_ok = colour.len() == 7
+        && colour.starts_with('#')
+        && colour[1..].chars().all(|ch| ch.is_ascii_hexdigit());
+    if name.trim().is_empty() || !colour_ok {
+        Err(ActivityError::InvalidInput)
+    } else {
+        Ok(())
+    }
+}
+
+/// Is the name used by any tag other than the given one?
+async fn tag_name_taken(
+    conn: &mut AsyncPgConnection,
+    name: &str,
+    except: Option<&str>,
+) -> ActivityResult<bool> {
+    Ok(models::Tag::by_names(conn, &[name.to_string()])
+        .await?
+        .iter()
+        .any(|tag| Some(tag.uuid.as_str()) != except))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn overview(conn: &mut AsyncPgConnection, actor: &str) -> ActivityResult<Overview> {
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                overview_of(txn).await
+            })
+        })
+        .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn create_tag(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    name: &str,
+    colour: &str,
+    black_text: bool,
+    description: &str,
+) -> ActivityResult<TagList> {
+    let (uuid, tags) = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let name = name.trim();
+                check_tag(name, colour)?;
+                if tag_name_taken(txn, name, None).await? {
+                    return Err(ActivityError::TagNameInUse);
+                }
+                let tag = models::Tag::create(txn, name, colour, black_text, description).await?;
+                Ok((tag.uuid, all_tags(txn).await?))
+            })
+        })
+        .await?;
+    announce(ChangeEvent::Tag(uuid));
+    Ok(tags)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn update_tag(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    update: &objects::Tag,
+) -> ActivityResult<TagList> {
+    let tags = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let mut tag = models::Tag::by_uuid(txn, &update.uuid)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                let name = update.name.trim();
+                check_tag(name, &update.colour)?;
+                if tag_name_taken(txn, name, Some(&tag.uuid)).await? {
+                    return Err(ActivityError::TagNameInUse);
+                }
+                tag.name = name.to_string();
+                tag.colour = update.colour.clone();
+                tag.black_text = update.black_text;
+                tag.description = update.description.clone();
+                tag.save(txn).await?;
+                all_tags(txn).await
+            })
+        })
+        .await?;
+    announce(ChangeEvent::Tag(update.uuid.clone()));
+    Ok(tags)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn delete_tag(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    uuid: &str,
+) -> ActivityResult<TagList> {
+    let tags = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let tag = models::Tag::by_uuid(txn, uuid)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                tag.delete(txn).await?;
+                all_tags(txn).await
+            })
+        })
+        .await?;
+    announce(ChangeEvent::Tag(uuid.to_string()));
+    Ok(tags)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn merge_tags(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    from: &str,
+    into: &str,
+) -> ActivityResult<TagList> {
+    if from == into {
+        return Err(ActivityError::InvalidInput);
+    }
+    let tags = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let from = models::Tag::by_uuid(txn, from)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                let into = models::Tag::by_uuid(txn, into)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                from.merge_into(txn, &into).await?;
+                all_tags(txn).await
+            })
+        })
+        .await?;
+    announce(ChangeEvent::Tag(from.to_string()));
+    announce(ChangeEvent::Tag(into.to_string()));
+    Ok(tags)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn set_puzzle_hidden(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    puzzle: &str,
+    hidden: bool,
+) -> ActivityResult<Overview> {
+    let overview = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let puzzle = Puzzle::by_uuid(txn, puzzle)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                puzzle.set_hidden(txn, hidden).await?;
+                overview_of(txn).await
+            })
+        })
+        .await?;
+    announce_puzzle(conn, puzzle).await;
+    Ok(overview)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn unpublish_puzzle(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    puzzle: &str,
+) -> ActivityResult<Overview> {
+    let overview = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let puzzle = Puzzle::by_uuid(txn, puzzle)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                puzzle.set_visibility(txn, Visibility::Restricted).await?;
+                overview_of(txn).await
+            })
+        })
+        .await?;
+    announce_puzzle(conn, puzzle).await;
+    Ok(overview)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn set_role_locked(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    role: &str,
+    locked: bool,
+) -> ActivityResult<Overview> {
+    let overview = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let role = Role::by_uuid(txn, role)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                role.set_locked(txn, locked).await?;
+                overview_of(txn).await
+            })
+        })
+        .await?;
+    announce(ChangeEvent::Role(role.to_string()));
+    Ok(overview)
+}
+
+/// Close every open report against the target, either as resolved once
+/// something has been done about it, or as dismissed
+#[tracing::instrument(skip_all)]
+pub async fn close_reports(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    target: &ReportTarget,
+    resolved: bool,
+) -> ActivityResult<Overview> {
+    let status = if resolved {
+        ReportStatus::Resolved
+    } else {
+        ReportStatus::Dismissed
+    };
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                require_admin(txn, actor).await?;
+                let (kind, uuid) = report::target_parts(target);
+                if models::Report::close_all(txn, kind, uuid, status, actor).await? == 0 {
+                    return Err(ActivityError::NotFound);
+                }
+                overview_of(txn).await
+            })
+        })
+        .await
+}
@@ -0,0 +1,166 @@
+//! Export and restore of role archives
+//!
+//! Exports contain every state of every puzzle, so they are only available
+//! to those who can modify the role.  Restores create fresh UUIDs but keep
+//! the original timestamps, and either succeed entirely or not at all.
+
+use std::collections::BTreeMap;
+
+use common::{archive::Archive, objects};
+use diesel_async::AsyncPgConnection;
+use time::{format_description::well_known::Iso8601, OffsetDateTime};
+use tracing::warn;
+
+use crate::{
+    changes::announce_puzzle,
+    models::{self, NewPuzzle, NewPuzzleState},
+    utils::{random_uuid, random_uuid_within},
+};
+
+use super::{puzzle, role, tag, ActivityError, ActivityResult};
+
+#[tracing::instrument(skip_all)]
+pub async fn export(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    role: &str,
+) -> ActivityResult<Archive> {
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                let role = models::Role::by_uuid(txn, role)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                // Owners of locked roles may still take their puzzles away
+                if !role.can_view_all(txn, actor).await? {
+                    return Err(ActivityError::PermissionDenied);
+                }
+
+                let mut puzzles = vec![];
+                // Oldest first, so that restoring recreates them in order
+                for found in role
+                    .visible_puzzles(txn, Some(actor))
+                    .await?
+                    .into_iter()
+                    .rev()
+                {
+                    puzzles.push(puzzle::into_api_object(txn, Some(actor), found).await?);
+                }
+
+                let mut tags: BTreeMap<String, objects::Tag> = BTreeMap::new();
+                for uuid in puzzles.iter().flat_map(|puzzle| puzzle.tags.iter()) {
+                    if tags.contains_key(uuid) {
+                        continue;
+                    }
+                    if let Some(found) = models::Tag::by_uuid(txn, uuid).await? {
+                        tags.insert(uuid.clone(), tag::into_api_object(found));
+                    }
+                }
+
+                Ok(Archive::new(
+                    OffsetDateTime::now_utc().format(&Iso8601::DEFAULT)?,
+                    role::into_api_object(role),
+                    tags.into_values().collect(),
+                    puzzles,
+                ))
+            })
+        })
+        .await
+}
+
+fn parse_time(when: &str) -> ActivityResult<OffsetDateTime> {
+    OffsetDateTime::parse(when, &Iso8601::DEFAULT).map_err(|_| ActivityError::InvalidInput)
+}
+
+/// Restore every puzzle in the archive into the owning role
+///
+/// Tags are matched by name, and any the instance does not know are dropped.
+/// Returns the UUIDs of the new puzzles.
+#[tracing::instrument(skip_all)]
+pub async fn restore(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    owner: &str,
+    archive: &Archive,
+) -> ActivityResult<Vec<String>> {
+    let restored = conn
+        .build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                let role = models::Role::by_uuid(txn, owner)
+                    .await?
+                    .ok_or(ActivityError::NotFound)?;
+                if !role.can_modify(txn, actor).await? {
+                    return Err(ActivityError::PermissionDenied);
+                }
+
+                let names: Vec<String> = archive.tags.iter().map(|tag| tag.name.clone()).collect();
+                let known: BTreeMap<String, String> = models::Tag::by_names(txn, &names)
+                    .await?
+                    .into_iter()
+                    .map(|tag| (tag.name, tag.uuid))
+                    .collect();
+
+                let plan = archive
+                    .restore_plan()
+                    .map_err(|_| ActivityError::InvalidInput)?;
+                let mut restored = vec![];
+                for entry in plan {
+                    if models::Puzzle::by_short_name(txn, owner, entry.short_name)
+                        .await?
+                        .is_some()
+                    {
+                        return Err(ActivityError::ShortNameInUse);
+                    }
+
+                    let uuid = random_uuid("puzzle");
+                    let puzzle = models::Puzzle::insert(
+                        txn,
+                        &NewPuzzle {
+                            uuid: &uuid,
+                            owner,
+                            display_name: entry.display_name,
+                            short_name: entry.short_name,
+                            visibility: entry.visibility.into(),
+                            created_at: parse_time(entry.created_at)?,
+                            updated_at: parse_time(entry.updated_at)?,
+                        },
+                    )
+                    .await?;
+
+                    for state in entry.states {
+                        models::PuzzleState::insert(
+                            txn,
+                            &NewPuzzleState {
+                                puzzle: &puzzle.uuid,
+                                description: &state.description,
+                                visibility: state.visibility.into(),
+                                updated_at: parse_time(&state.updated_at)?,
+                                data: &serde_json::to_string(&state.data)?,
+                                uuid: &random_uuid_within("puzzle_state", &puzzle.uuid),
+                                solution_check: state.solution_check.into(),
+                                hide_solution: state.hide_solution,
+                            },
+                        )
+                        .await?;
+                    }
+
+                    for name in &entry.tag_names {
+                        match known.get(name) {
+                            Some(tag) => puzzle.add_tag(txn, tag).await?,
+                            None => warn!("Dropping unknown tag {name} from {uuid}"),
+                        }
+                    }
+
+                    restored.push(puzzle.uuid);
+                }
+
+                Ok(restored)
+            })
+        })
+        .await?;
+    for puzzle in &restored {
+        announce_puzzle(conn, puzzle).await;
+    }
+    Ok(restored)
+}
@@ -5,7 +5,10 @@
 use diesel::QueryResult;
 use diesel_async::AsyncPgConnection;
 
-use crate::models;
+use crate::{
+    changes::{announce, ChangeEvent},
+    models,
+};
 
 /// Log into linkdoku.  This is called at the end of a successful openid-connect cycle and provides
 /// the caller with a database backed Identity etc.
@@ -17,7 +20,8 @@ pub async fn login_upsert(
     gravatar_hash: &str,
     display_name: &str,
 ) -> QueryResult<(models::Identity, Vec<models::Role>, bool)> {
-    conn.build_transaction()
+    let upserted: QueryResult<_> = conn
+        .build_transaction()
         .run(|conn| {
             Box::pin(async move {
                 let (new, mut identity) = match models::Identity::from_handle(conn, oidc_handle)
@@ -54,5 +58,12 @@ pub async fn login_upsert(
                 Ok((identity, roles, new))
             })
         })
-        .await
+        .await;
+    let (identity, roles, new) = upserted?;
+    if new {
+        for role in &roles {
+            announce(ChangeEvent::Role(role.uuid.clone()));
+        }
+    }
+    Ok((identity, roles, new))
 }
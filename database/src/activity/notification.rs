@@ -0,0 +1,159 @@
+//! Following roles, and the notifications which result
+//!
+//! Notifications are created inside whichever activity causes them, so
+//! that they only exist if that activity succeeds.
+
+use common::internal::notification::{list, unread, Notification, NotificationEvent, PAGE_SIZE};
+use diesel_async::AsyncPgConnection;
+use time::format_description::well_known::Iso8601;
+
+use crate::models::{self, NewNotification, NotificationKind};
+
+use super::{ActivityError, ActivityResult};
+
+/// Follow or stop following a role, returning every role now followed
+#[tracing::instrument(skip_all)]
+pub async fn follow(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    role: &str,
+    follow: bool,
+) -> ActivityResult<Vec<String>> {
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                if models::Role::by_uuid(txn, role).await?.is_none() {
+                    return Err(ActivityError::NotFound);
+                }
+                if follow {
+                    models::RoleFollow::follow(txn, actor, role).await?;
+                } else {
+                    models::RoleFollow::unfollow(txn, actor, role).await?;
+                }
+                Ok(models::RoleFollow::roles_followed_by(txn, actor).await?)
+            })
+        })
+        .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn followed_roles(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+) -> ActivityResult<Vec<String>> {
+    Ok(models::RoleFollow::roles_followed_by(conn, actor).await?)
+}
+
+/// Tell everyone who follows the puzzle's role that it has been published
+///
+/// The owner of the role is never told about their own puzzles.
+pub(crate) async fn notify_published(
+    txn: &mut AsyncPgConnection,
+    puzzle: &models::Puzzle,
+) -> ActivityResult<()> {
+    let role = models::Role::by_uuid(txn, &puzzle.owner)
+        .await?
+        .ok_or(ActivityError::NotFound)?;
+    let followers = models::RoleFollow::followers_of(txn, &role.uuid).await?;
+    let new: Vec<_> = followers
+        .iter()
+        .filter(|&identity| identity != &role.owner)
+        .map(|identity| {
+            NewNotification::new(
+                identity,
+                NotificationKind::RolePublished,
+                &role.uuid,
+                Some(&puzzle.uuid),
+            )
+        })
+        .collect();
+    if !new.is_empty() {
+        models::Notification::create_all(txn, &new).await?;
+    }
+    Ok(())
+}
+
+async fn into_api_object(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    notification: models::Notification,
+) -> ActivityResult<Notification> {
+    let role_name = models::Role::by_uuid(conn, &notification.role)
+        .await?
+        .map(|role| role.display_name)
+        .unwrap_or_default();
+    let event = match notification.kind {
+        NotificationKind::RolePublished => {
+            let puzzle = notification.puzzle.unwrap_or_default();
+            let puzzle_name = match models::Puzzle::by_uuid(conn, &puzzle).await? {
+                Some(found) if found.can_be_seen(conn, Some(actor)).await? => {
+                    Some(found.display_name)
+                }
+                _ => None,
+            };
+            NotificationEvent::RolePublished {
+                role: notification.role,
+                role_name,
+                puzzle,
+                puzzle_name,
+            }
+        }
+    };
+    Ok(Notification {
+        uuid: notification.uuid,
+        event,
+        created_at: notification.created_at.format(&Iso8601::DEFAULT)?,
+        read: notification.read,
+    })
+}
+
+/// List a page of the actor's notifications, newest first
+#[tracing::instrument(skip_all)]
+pub async fn list(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    page: usize,
+) -> ActivityResult<list::Response> {
+    let offset = page
+        .checked_mul(PAGE_SIZE)
+        .and_then(|offset| i64::try_from(offset).ok())
+        .ok_or(ActivityError::InvalidInput)?;
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                let found =
+                    models::Notification::for_identity(txn, actor, offset, PAGE_SIZE as i64)
+                        .await?;
+                let mut notifications = vec![];
+                for notification in found {
+                    notifications.push(into_api_object(txn, actor, notification).await?);
+                }
+                Ok(list::Response {
+                    notifications,
+                    total: models::Notification::count_for_identity(txn, actor, false).await?
+                        as usize,
+                    unread: models::Notification::count_for_identity(txn, actor, true).await?
+                        as usize,
+                })
+            })
+        })
+        .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn unread(conn: &mut AsyncPgConnection, actor: &str) -> ActivityResult<unread::Response> {
+    Ok(unread::Response {
+        unread: models::Notification::count_for_identity(conn, actor, true).await? as usize,
+    })
+}
+
+/// Mark some, or all, of the actor's notifications as read
+#[tracing::instrument(skip_all)]
+pub async fn mark_read(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    uuids: Option<&[String]>,
+) -> ActivityResult<unread::Response> {
+    models::Notification::mark_read(conn, actor, uuids).await?;
+    unread(conn, actor).await
+}
@@ -6,11 +6,12 @@ use diesel_async::AsyncPgConnection;
 use time::format_description::well_known::Iso8601;
 
 use crate::{
+    changes::announce_puzzle,
     models::{self, Puzzle, PuzzleState, Role, Visibility},
     utils::random_uuid,
 };
 
-use super::{ActivityError, ActivityResult};
+use super::{notification, ActivityError, ActivityResult};
 
 #[tracing::instrument(skip_all)]
 pub async fn create(
@@ -20,8 +21,10 @@ pub async fn create(
     short_name: &str,
     display_name: &str,
     initial_state: &objects::PuzzleState,
+    tags: &[String],
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let owning_role = models::Role::by_uuid(txn, owner)
@@ -54,12 +57,40 @@ pub async fn create(
                         &initial_state.description,
                         Visibility::Restricted,
                         &serde_json::to_string(&initial_state.data)?,
+                        initial_state.solution_check.into(),
+                        initial_state.hide_solution,
                     )
                     .await?;
+                for tag in tags {
+                    puzzle.add_tag(txn, tag).await?;
+                }
                 Ok(puzzle)
             })
         })
-        .await
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
+}
+
+fn state_into_api_object(
+    state: models::PuzzleState,
+    is_owner: bool,
+) -> ActivityResult<objects::PuzzleState> {
+    let data: objects::PuzzleData = serde_json::from_str(&state.data)?;
+    let data = if state.hide_solution && !is_owner {
+        data.without_solution()
+    } else {
+        data
+    };
+    Ok(objects::PuzzleState {
+        uuid: state.uuid,
+        description: state.description,
+        visibility: state.visibility.into(),
+        updated_at: state.updated_at.format(&Iso8601::DEFAULT)?,
+        data,
+        solution_check: state.solution_check.into(),
+        hide_solution: state.hide_solution,
+    })
 }
 
 #[tracing::instrument(skip_all)]
@@ -77,15 +108,15 @@ pub async fn into_api_object(
 
                 let mut states = vec![];
 
+                // Owners always get to see solutions, even if they are hidden from solvers
+                let is_owner = match actor {
+                    Some(actor) => puzzle.is_owned_by(txn, actor).await?,
+                    None => false,
+                };
+
                 for state in puzzle.all_states(txn).await? {
                     if state.can_be_seen(txn, &puzzle, actor).await? {
-                        states.push(objects::PuzzleState {
-                            uuid: state.uuid,
-                            description: state.description,
-                            visibility: state.visibility.into(),
-                            updated_at: state.updated_at.format(&Iso8601::DEFAULT)?,
-                            data: serde_json::from_str(&state.data)?,
-                        });
+                        states.push(state_into_api_object(state, is_owner)?);
                     }
                 }
 
@@ -146,7 +177,8 @@ pub async fn update_metadata(
     short_name: &str,
     display_name: &str,
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let puzzle = match Puzzle::by_uuid(txn, puzzle).await? {
@@ -163,7 +195,9 @@ pub async fn update_metadata(
                     .await?)
             })
         })
-        .await
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
 }
 
 #[tracing::instrument(skip_all)]
@@ -173,7 +207,8 @@ pub async fn update_state(
     puzzle: &str,
     state: &objects::PuzzleState,
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let puzzle = match Puzzle::by_uuid(txn, puzzle).await? {
@@ -199,13 +234,17 @@ pub async fn update_state(
                         txn,
                         &state.description,
                         &serde_json::to_string(&state.data)?,
+                        state.solution_check.into(),
+                        state.hide_solution,
                     )
                     .await?;
 
                 Ok(puzzle)
             })
         })
-        .await
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
 }
 
 #[tracing::instrument(skip_all)]
@@ -215,7 +254,8 @@ pub async fn add_state(
     puzzle: &str,
     state: &objects::PuzzleState,
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let puzzle = match Puzzle::by_uuid(txn, puzzle).await? {
@@ -233,13 +273,17 @@ pub async fn add_state(
                         &state.description,
                         Visibility::Restricted,
                         &serde_json::to_string(&state.data)?,
+                        state.solution_check.into(),
+                        state.hide_solution,
                     )
                     .await?;
 
                 Ok(puzzle)
             })
         })
-        .await
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
 }
 
 #[tracing::instrument(skip_all)]
@@ -250,7 +294,8 @@ pub async fn set_visibility(
     visibility: objects::Visibility,
     in_view_state: Option<&str>,
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let puzzle = match Puzzle::by_uuid(txn, puzzle).await? {
@@ -284,12 +329,21 @@ pub async fn set_visibility(
                     }
                 }
 
+                let was_published = matches!(puzzle.visibility, Visibility::Published);
                 let puzzle = puzzle.set_visibility(txn, visibility.into()).await?;
+                if !was_published
+                    && matches!(puzzle.visibility, Visibility::Published)
+                    && !puzzle.hidden
+                {
+                    notification::notify_published(txn, &puzzle).await?;
+                }
 
                 Ok(puzzle)
             })
         })
-        .await
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
 }
 
 #[tracing::instrument(skip_all)]
@@ -300,7 +354,8 @@ pub async fn set_state_visibility(
     state: &str,
     visibility: objects::Visibility,
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let puzzle = match Puzzle::by_uuid(txn, puzzle).await? {
@@ -326,7 +381,9 @@ pub async fn set_state_visibility(
                 Ok(puzzle)
             })
         })
-        .await
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
 }
 
 #[tracing::instrument(skip_all)]
@@ -337,7 +394,8 @@ pub async fn edit_puzzle_tags(
     to_add: &[String],
     to_remove: &[String],
 ) -> ActivityResult<models::Puzzle> {
-    conn.build_transaction()
+    let puzzle = conn
+        .build_transaction()
         .run(|txn| {
             Box::pin(async move {
                 let puzzle = match Puzzle::by_uuid(txn, puzzle).await? {
@@ -360,5 +418,71 @@ pub async fn edit_puzzle_tags(
                 Ok(puzzle)
             })
         })
+        .await?;
+    announce_puzzle(conn, &puzzle.uuid).await;
+    Ok(puzzle)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn state_for_checking(
+    conn: &mut AsyncPgConnection,
+    actor: Option<&str>,
+    state: &str,
+) -> ActivityResult<models::PuzzleState> {
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                let state = match PuzzleState::by_uuid(txn, state).await? {
+                    Some(state) => state,
+                    None => return Err(ActivityError::NotFound),
+                };
+                let puzzle = match Puzzle::by_uuid(txn, &state.puzzle).await? {
+                    Some(puzzle) => puzzle,
+                    None => return Err(ActivityError::NotFound),
+                };
+                // Checking a solution is as good as seeing it, so treat invisible
+                // states as though they do not exist at all
+                if !puzzle.can_be_seen(txn, actor).await?
+                    || !state.can_be_seen(txn, &puzzle, actor).await?
+                {
+                    return Err(ActivityError::NotFound);
+                }
+                Ok(state)
+            })
+        })
+        .await
+}
+
+/// Retrieve a single puzzle state, treating states the actor cannot see as
+/// though they do not exist
+#[tracing::instrument(skip_all)]
+pub async fn visible_state(
+    conn: &mut AsyncPgConnection,
+    actor: Option<&str>,
+    state: &str,
+) -> ActivityResult<objects::PuzzleState> {
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                let state = match PuzzleState::by_uuid(txn, state).await? {
+                    Some(state) => state,
+                    None => return Err(ActivityError::NotFound),
+                };
+                let puzzle = match Puzzle::by_uuid(txn, &state.puzzle).await? {
+                    Some(puzzle) => puzzle,
+                    None => return Err(ActivityError::NotFound),
+                };
+                if !puzzle.can_be_seen(txn, actor).await?
+                    || !state.can_be_seen(txn, &puzzle, actor).await?
+                {
+                    return Err(ActivityError::NotFound);
+                }
+                let is_owner = match actor {
+                    Some(actor) => puzzle.is_owned_by(txn, actor).await?,
+                    None => false,
+                };
+                state_into_api_object(state, is_owner)
+            })
+        })
         .await
 }
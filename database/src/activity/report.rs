@@ -0,0 +1,94 @@
+//! Filing reports about problematic content
+//!
+//! Anyone may report anything they can see.  The moderation queue which
+//! administrators work through is part of [super::admin].
+
+use common::internal::report::{file, ReportReason, ReportTarget, MAX_NOTE_LENGTH};
+use diesel_async::AsyncPgConnection;
+
+use crate::models::{self, ReportTargetKind};
+
+use super::{ActivityError, ActivityResult};
+
+/// Split a target into how it is stored in the database
+pub(crate) fn target_parts(target: &ReportTarget) -> (ReportTargetKind, &str) {
+    match target {
+        ReportTarget::Puzzle(uuid) => (ReportTargetKind::Puzzle, uuid),
+        ReportTarget::PuzzleState(uuid) => (ReportTargetKind::PuzzleState, uuid),
+        ReportTarget::Role(uuid) => (ReportTargetKind::Role, uuid),
+    }
+}
+
+pub(crate) fn target_from_parts(kind: ReportTargetKind, uuid: String) -> ReportTarget {
+    match kind {
+        ReportTargetKind::Puzzle => ReportTarget::Puzzle(uuid),
+        ReportTargetKind::PuzzleState => ReportTarget::PuzzleState(uuid),
+        ReportTargetKind::Role => ReportTarget::Role(uuid),
+    }
+}
+
+/// Can the actor see the target at all?  If not, it cannot be reported.
+async fn can_see(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    target: &ReportTarget,
+) -> ActivityResult<bool> {
+    Ok(match target {
+        ReportTarget::Puzzle(uuid) => match models::Puzzle::by_uuid(conn, uuid).await? {
+            Some(puzzle) => puzzle.can_be_seen(conn, Some(actor)).await?,
+            None => false,
+        },
+        ReportTarget::PuzzleState(uuid) => match models::PuzzleState::by_uuid(conn, uuid).await? {
+            Some(state) => match models::Puzzle::by_uuid(conn, &state.puzzle).await? {
+                Some(puzzle) => {
+                    puzzle.can_be_seen(conn, Some(actor)).await?
+                        && state.can_be_seen(conn, &puzzle, Some(actor)).await?
+                }
+                None => false,
+            },
+            None => false,
+        },
+        ReportTarget::Role(uuid) => models::Role::by_uuid(conn, uuid).await?.is_some(),
+    })
+}
+
+/// File a report, or update the note on the matching open report if the
+/// actor has already filed one
+#[tracing::instrument(skip_all)]
+pub async fn file(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    target: &ReportTarget,
+    reason: ReportReason,
+    note: &str,
+) -> ActivityResult<file::Response> {
+    let note = note.trim();
+    if note.chars().count() > MAX_NOTE_LENGTH {
+        return Err(ActivityError::InvalidInput);
+    }
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                if !can_see(txn, actor, target).await? {
+                    return Err(ActivityError::NotFound);
+                }
+                let (kind, uuid) = target_parts(target);
+                if let Some(existing) =
+                    models::Report::open_by(txn, actor, kind, uuid, reason.into()).await?
+                {
+                    let existing = existing.set_note(txn, note).await?;
+                    return Ok(file::Response {
+                        uuid: existing.uuid,
+                        updated: true,
+                    });
+                }
+                let report =
+                    models::Report::create(txn, actor, kind, uuid, reason.into(), note).await?;
+                Ok(file::Response {
+                    uuid: report.uuid,
+                    updated: false,
+                })
+            })
+        })
+        .await
+}
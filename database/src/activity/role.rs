@@ -1,11 +1,25 @@
 //! Role based activity such as update/creation of roles
 
+use common::objects;
 use diesel_async::AsyncPgConnection;
 
-use crate::models;
+use crate::{
+    changes::{announce, ChangeEvent},
+    models,
+};
 
 use super::{ActivityError, ActivityResult};
 
+pub(crate) fn into_api_object(role: models::Role) -> objects::Role {
+    objects::Role {
+        uuid: role.uuid,
+        owner: role.owner,
+        short_name: role.short_name,
+        display_name: role.display_name,
+        description: role.description,
+    }
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn update(
     conn: &mut AsyncPgConnection,
@@ -30,5 +44,7 @@ pub async fn update(
                 }
             })
         })
-        .await
+        .await?;
+    announce(ChangeEvent::Role(role.uuid.clone()));
+    Ok(())
 }
@@ -8,6 +8,16 @@ use crate::models;
 
 use super::ActivityResult;
 
+pub(crate) fn into_api_object(tag: models::Tag) -> objects::Tag {
+    objects::Tag {
+        uuid: tag.uuid,
+        name: tag.name,
+        colour: tag.colour,
+        black_text: tag.black_text,
+        description: tag.description,
+    }
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn list(
     conn: &mut AsyncPgConnection,
@@ -18,17 +28,18 @@ pub async fn list(
             Box::pin(async move {
                 let tags = models::Tag::get_all(txn, pattern).await?;
 
-                Ok(tags
-                    .into_iter()
-                    .map(|tag| objects::Tag {
-                        uuid: tag.uuid,
-                        name: tag.name,
-                        colour: tag.colour,
-                        black_text: tag.black_text,
-                        description: tag.description,
-                    })
-                    .collect())
+                Ok(tags.into_iter().map(into_api_object).collect())
             })
         })
         .await
 }
+
+#[tracing::instrument(skip_all)]
+pub async fn by_names(
+    conn: &mut AsyncPgConnection,
+    names: &[String],
+) -> ActivityResult<Vec<objects::Tag>> {
+    let tags = models::Tag::by_names(conn, names).await?;
+
+    Ok(tags.into_iter().map(into_api_object).collect())
+}
@@ -0,0 +1,125 @@
+//! Personal API token activities
+//!
+//! Secrets are handed out exactly once, when a token is created, after
+//! which only their hashes are kept.
+
+use common::internal::token::{TokenInfo, TokenScope};
+use diesel_async::AsyncPgConnection;
+use time::format_description::well_known::Iso8601;
+
+use crate::{models, utils};
+
+use super::{ActivityError, ActivityResult};
+
+fn into_api_object(token: models::ApiToken) -> ActivityResult<TokenInfo> {
+    Ok(TokenInfo {
+        created_at: token.created_at.format(&Iso8601::DEFAULT)?,
+        last_used: token
+            .last_used
+            .map(|when| when.format(&Iso8601::DEFAULT))
+            .transpose()?,
+        scope: scope_of(&token),
+        uuid: token.uuid,
+        name: token.name,
+    })
+}
+
+fn scope_of(token: &models::ApiToken) -> TokenScope {
+    match &token.role {
+        Some(role) => TokenScope::Publish(role.clone()),
+        None => TokenScope::ReadOnly,
+    }
+}
+
+/// Create a token for the actor, returning it along with its secret
+///
+/// Publishing tokens may only be made for roles the actor can modify.
+#[tracing::instrument(skip_all)]
+pub async fn create(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    name: &str,
+    scope: &TokenScope,
+) -> ActivityResult<(TokenInfo, String)> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ActivityError::InvalidInput);
+    }
+
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                let role = match scope {
+                    TokenScope::ReadOnly => None,
+                    TokenScope::Publish(role) => {
+                        let role = models::Role::by_uuid(txn, role)
+                            .await?
+                            .ok_or(ActivityError::NotFound)?;
+                        if !role.can_modify(txn, actor).await? {
+                            return Err(ActivityError::PermissionDenied);
+                        }
+                        Some(role.uuid)
+                    }
+                };
+
+                let secret = utils::token_secret();
+                let token = models::ApiToken::create(
+                    txn,
+                    actor,
+                    name,
+                    &utils::token_hash(&secret),
+                    role.as_deref(),
+                )
+                .await?;
+
+                Ok((into_api_object(token)?, secret))
+            })
+        })
+        .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn list(conn: &mut AsyncPgConnection, actor: &str) -> ActivityResult<Vec<TokenInfo>> {
+    models::ApiToken::by_owner(conn, actor)
+        .await?
+        .into_iter()
+        .map(into_api_object)
+        .collect()
+}
+
+/// Revoke one of the actor's tokens, returning those which remain
+#[tracing::instrument(skip_all)]
+pub async fn revoke(
+    conn: &mut AsyncPgConnection,
+    actor: &str,
+    token: &str,
+) -> ActivityResult<Vec<TokenInfo>> {
+    conn.build_transaction()
+        .run(|txn| {
+            Box::pin(async move {
+                if !models::ApiToken::delete(txn, actor, token).await? {
+                    return Err(ActivityError::NotFound);
+                }
+                list(txn, actor).await
+            })
+        })
+        .await
+}
+
+/// Find the identity a token secret acts as, and what it may do
+///
+/// Unknown secrets give `Ok(None)`.  Known ones are marked as used.
+#[tracing::instrument(skip_all)]
+pub async fn authenticate(
+    conn: &mut AsyncPgConnection,
+    secret: &str,
+) -> ActivityResult<Option<(models::Identity, TokenScope)>> {
+    let Some(token) = models::ApiToken::by_hash(conn, &utils::token_hash(secret)).await? else {
+        return Ok(None);
+    };
+    let identity = models::Identity::from_uuid(conn, &token.owner)
+        .await?
+        .ok_or(ActivityError::NotFound)?;
+    token.touch(conn).await?;
+    Ok(Some((identity, scope_of(&token))))
+}
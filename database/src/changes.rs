@@ -0,0 +1,66 @@
+//! Announcements of changes made by activities
+//!
+//! Anything which keeps copies of what it has read from the database, such
+//! as the backend's cache of rendered pages, can subscribe to hear about
+//! changes to puzzles, roles, and tags.  Changes are only announced once the
+//! activity making them has committed, and only changes made within this
+//! process are ever seen.
+
+use diesel_async::AsyncPgConnection;
+use lazy_static::lazy_static;
+use tokio::sync::broadcast;
+
+use crate::models::{Puzzle, Visibility};
+
+/// How many announcements a slow subscriber may fall behind by before it
+/// starts to miss some
+const BACKLOG: usize = 256;
+
+/// Who may see a changed puzzle, worked out once as it is announced so
+/// that subscribers passing it on need not each ask the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Audience {
+    /// Anyone at all, logged in or not
+    Everyone,
+    /// Only some people, or nobody if the puzzle has gone, so each
+    /// subscriber has to check for itself
+    Restricted,
+}
+
+/// Something which was changed, by UUID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    Puzzle(String, Audience),
+    Role(String),
+    Tag(String),
+}
+
+lazy_static! {
+    static ref CHANGES: broadcast::Sender<ChangeEvent> = broadcast::channel(BACKLOG).0;
+}
+
+/// Hear about every change announced from now on
+///
+/// Subscribers which fall behind are told how many announcements they
+/// missed, and should assume that anything might have changed.
+pub fn subscribe() -> broadcast::Receiver<ChangeEvent> {
+    CHANGES.subscribe()
+}
+
+pub(crate) fn announce(event: ChangeEvent) {
+    // Nobody listening is not a problem
+    let _ = CHANGES.send(event);
+}
+
+/// Announce a change to a puzzle, along with who may now see it
+pub(crate) async fn announce_puzzle(conn: &mut AsyncPgConnection, uuid: &str) {
+    let audience = match Puzzle::by_uuid(conn, uuid).await {
+        Ok(Some(puzzle))
+            if !puzzle.hidden && !matches!(puzzle.visibility, Visibility::Restricted) =>
+        {
+            Audience::Everyone
+        }
+        _ => Audience::Restricted,
+    };
+    announce(ChangeEvent::Puzzle(uuid.to_string(), audience));
+}
@@ -11,12 +11,10 @@ use std::{fmt::Display, time::Duration};
 
 use ::bb8::ErrorSink;
 use diesel::{ConnectionError, ConnectionResult};
-use diesel_async::{
-    pooled_connection::{bb8, AsyncDieselConnectionManager, PoolError},
-    AsyncPgConnection,
-};
+use diesel_async::pooled_connection::{bb8, AsyncDieselConnectionManager, PoolError};
 
 pub use axum_link::Connection;
+pub use diesel_async::AsyncPgConnection;
 use futures::{future::BoxFuture, FutureExt};
 use lazy_static::lazy_static;
 use rustls::RootCertStore;
@@ -51,6 +49,7 @@ pub async fn apply_migrations_async(db_url: &str) -> diesel::migration::Result<(
 pub(crate) mod utils;
 
 pub mod activity;
+pub mod changes;
 pub mod models;
 pub mod schema;
 
@@ -5,8 +5,11 @@ pub mod sql_types;
 
 use std::{collections::BTreeMap, sync::Arc};
 
-pub use self::sql_types::Visibility;
+pub use self::sql_types::{
+    NotificationKind, ReportReason, ReportStatus, ReportTargetKind, SolutionCheck, Visibility,
+};
 
+use common::public::role::puzzles::PuzzleSort;
 use diesel::prelude::*;
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::{Deserialize, Serialize};
@@ -15,12 +18,15 @@ use tokio::sync::Mutex;
 
 use crate::utils;
 
-#[derive(Queryable, Serialize, Deserialize)]
+#[derive(Queryable, Serialize, Deserialize, Clone)]
 pub struct Identity {
     pub uuid: String,
     pub oidc_handle: String,
     pub display_name: String,
     pub gravatar_hash: String,
+    // Login cookies made before administrators existed lack this
+    #[serde(default)]
+    pub is_admin: bool,
 }
 
 #[derive(Insertable)]
@@ -114,6 +120,32 @@ impl Identity {
             .await
     }
 
+    /// Grant or revoke administrator rights for this identity
+    #[tracing::instrument(skip_all, name = "Identity::set_admin")]
+    pub async fn set_admin(
+        &self,
+        conn: &mut AsyncPgConnection,
+        is_admin: bool,
+    ) -> QueryResult<Self> {
+        use crate::schema::identity::dsl;
+        diesel::update(dsl::identity.find(&self.uuid))
+            .set(dsl::is_admin.eq(is_admin))
+            .get_result(conn)
+            .await
+    }
+
+    /// Is the identity with the given UUID an administrator?
+    ///
+    /// This always consults the database, rather than any identity held in
+    /// a login cookie, so that revoking administrator rights takes effect
+    /// immediately.
+    #[tracing::instrument(skip_all, name = "Identity::uuid_is_admin")]
+    pub async fn uuid_is_admin(conn: &mut AsyncPgConnection, uuid: &str) -> QueryResult<bool> {
+        Ok(Self::from_uuid(conn, uuid)
+            .await?
+            .is_some_and(|identity| identity.is_admin))
+    }
+
     /// Retrieve the roles for this identity
     #[tracing::instrument(skip_all, name = "Identity::roles")]
     pub async fn roles(&self, conn: &mut AsyncPgConnection) -> QueryResult<Vec<Role>> {
@@ -128,6 +160,7 @@ pub struct Role {
     pub display_name: String,
     pub description: String,
     pub short_name: String,
+    pub locked: bool,
 }
 
 #[derive(Insertable)]
@@ -151,6 +184,16 @@ impl Role {
         role.filter(uuid.eq(role_uuid)).first(conn).await.optional()
     }
 
+    /// Retrieve whichever of the given roles exist
+    #[tracing::instrument(skip_all, name = "Role::by_uuids")]
+    pub async fn by_uuids(
+        conn: &mut AsyncPgConnection,
+        role_uuids: &[String],
+    ) -> QueryResult<Vec<Role>> {
+        use crate::schema::role::dsl::*;
+        role.filter(uuid.eq_any(role_uuids)).load(conn).await
+    }
+
     /// Retrieve a role by short name
     #[tracing::instrument(skip_all, name = "Role::by_short_name")]
     pub async fn by_short_name(
@@ -200,8 +243,18 @@ impl Role {
 
     /// Are we permitted to edit this role?
     ///
+    /// Nobody may edit a role which an administrator has locked.
     #[tracing::instrument(skip_all, name = "Role::can_modify")]
-    pub async fn can_modify(
+    pub async fn can_modify(&self, conn: &mut AsyncPgConnection, actor: &str) -> QueryResult<bool> {
+        Ok(!self.locked && self.can_view_all(conn, actor).await?)
+    }
+
+    /// Are we permitted to see everything belonging to this role?
+    ///
+    /// Unlike [Role::can_modify] this is unaffected by locking, so owners can
+    /// still see (and export) what they have.
+    #[tracing::instrument(skip_all, name = "Role::can_view_all")]
+    pub async fn can_view_all(
         &self,
         _conn: &mut AsyncPgConnection,
         actor: &str,
@@ -210,6 +263,30 @@ impl Role {
         Ok(self.owner == actor)
     }
 
+    /// Lock or unlock this role
+    #[tracing::instrument(skip_all, name = "Role::set_locked")]
+    pub async fn set_locked(
+        &self,
+        conn: &mut AsyncPgConnection,
+        locked: bool,
+    ) -> QueryResult<Self> {
+        use crate::schema::role::dsl;
+        diesel::update(dsl::role.find(&self.uuid))
+            .set(dsl::locked.eq(locked))
+            .get_result(conn)
+            .await
+    }
+
+    /// Retrieve every locked role
+    #[tracing::instrument(skip_all, name = "Role::all_locked")]
+    pub async fn all_locked(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        use crate::schema::role::dsl::*;
+        role.filter(locked.eq(true))
+            .order_by(short_name.asc())
+            .load(conn)
+            .await
+    }
+
     /// Save this role
     #[tracing::instrument(skip_all, name = "Role::save")]
     pub async fn save(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
@@ -235,7 +312,7 @@ impl Role {
         user: Option<&str>,
     ) -> QueryResult<Vec<Puzzle>> {
         let can_see_unpublished = if let Some(actor) = user {
-            self.can_modify(conn, actor).await?
+            self.can_view_all(conn, actor).await?
         } else {
             false
         };
@@ -252,12 +329,124 @@ impl Role {
             puzzle
                 .filter(owner.eq(&self.uuid))
                 .filter(visibility.eq(Visibility::Published))
+                .filter(hidden.eq(false))
                 .order_by(created_at.desc())
                 .load(conn)
                 .await
         }
     }
 
+    /// Retrieve a page of the puzzles which [Role::visible_puzzles] would,
+    /// restricted to those with every one of the given tags, along with how
+    /// many such puzzles there are in total
+    #[tracing::instrument(skip_all, name = "Role::visible_puzzle_page")]
+    pub async fn visible_puzzle_page(
+        &self,
+        conn: &mut AsyncPgConnection,
+        user: Option<&str>,
+        sort: PuzzleSort,
+        tags: &[String],
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<(Vec<Puzzle>, i64)> {
+        let can_see_unpublished = if let Some(actor) = user {
+            self.can_view_all(conn, actor).await?
+        } else {
+            false
+        };
+        use crate::schema::{puzzle::dsl::*, puzzle_tag};
+        let matching = || {
+            let mut query = puzzle.filter(owner.eq(&self.uuid)).into_boxed();
+            if !can_see_unpublished {
+                query = query
+                    .filter(visibility.eq(Visibility::Published))
+                    .filter(hidden.eq(false));
+            }
+            for tag in tags {
+                query = query.filter(
+                    uuid.eq_any(
+                        puzzle_tag::table
+                            .filter(puzzle_tag::tag.eq(tag))
+                            .select(puzzle_tag::puzzle),
+                    ),
+                );
+            }
+            query
+        };
+        let total = matching().count().get_result(conn).await?;
+        let ordered = match sort {
+            PuzzleSort::Newest => matching().order_by(created_at.desc()),
+            PuzzleSort::Updated => matching().order_by(updated_at.desc()),
+            PuzzleSort::Name => matching().order_by(display_name.asc()),
+        };
+        let puzzles = ordered
+            .then_order_by(uuid.asc())
+            .offset(offset)
+            .limit(limit)
+            .load(conn)
+            .await?;
+        Ok((puzzles, total))
+    }
+
+    /// Find roles whose names contain the given text
+    #[tracing::instrument(skip_all, name = "Role::search")]
+    pub async fn search(
+        conn: &mut AsyncPgConnection,
+        text: &str,
+        limit: i64,
+    ) -> QueryResult<Vec<Role>> {
+        use crate::schema::role::dsl::*;
+        let pattern = utils::contains_pattern(text);
+        role.filter(short_name.ilike(&pattern).or(display_name.ilike(&pattern)))
+            .order_by(short_name.asc())
+            .limit(limit)
+            .load(conn)
+            .await
+    }
+
+    #[tracing::instrument(skip_all, name = "Role::count")]
+    pub async fn count(conn: &mut AsyncPgConnection) -> QueryResult<i64> {
+        use crate::schema::role::dsl::*;
+        role.count().get_result(conn).await
+    }
+
+    /// Retrieve a page of every role, in a stable order, alongside when
+    /// each last had a published puzzle updated, if ever
+    #[tracing::instrument(skip_all, name = "Role::page_with_last_published")]
+    pub async fn page_with_last_published(
+        conn: &mut AsyncPgConnection,
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<Vec<(Role, Option<OffsetDateTime>)>> {
+        use crate::schema::{puzzle::dsl as pdsl, role::dsl as rdsl};
+
+        let roles: Vec<Role> = rdsl::role
+            .order_by(rdsl::uuid.asc())
+            .offset(offset)
+            .limit(limit)
+            .load(conn)
+            .await?;
+        let uuids: Vec<&str> = roles.iter().map(|role| role.uuid.as_str()).collect();
+        let updated: BTreeMap<String, Option<OffsetDateTime>> = pdsl::puzzle
+            .filter(pdsl::owner.eq_any(&uuids))
+            .filter(pdsl::visibility.eq(Visibility::Published))
+            .filter(pdsl::hidden.eq(false))
+            .group_by(pdsl::owner)
+            .select((pdsl::owner, diesel::dsl::max(pdsl::updated_at)))
+            .load::<(String, Option<OffsetDateTime>)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(roles
+            .into_iter()
+            .map(|role| {
+                let updated = updated.get(&role.uuid).copied().flatten();
+                (role, updated)
+            })
+            .collect())
+    }
+
     /// This role's short name is available if either no other role has it,
     /// or the name is unchanged.
     #[tracing::instrument(skip_all, name = "Role::short_name_available")]
@@ -282,6 +471,7 @@ pub struct Puzzle {
     pub visibility: Visibility,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
+    pub hidden: bool,
 }
 
 #[derive(Insertable)]
@@ -310,6 +500,17 @@ impl Puzzle {
             .optional()
     }
 
+    /// Retrieve whichever of the given puzzles exist, regardless of whether
+    /// they can be seen
+    #[tracing::instrument(skip_all, name = "Puzzle::by_uuids")]
+    pub async fn by_uuids(
+        conn: &mut AsyncPgConnection,
+        puzzle_uuids: &[String],
+    ) -> QueryResult<Vec<Self>> {
+        use crate::schema::puzzle::dsl::*;
+        puzzle.filter(uuid.eq_any(puzzle_uuids)).load(conn).await
+    }
+
     #[tracing::instrument(skip_all, name = "Puzzle::by_short_name")]
     pub async fn by_short_name(
         conn: &mut AsyncPgConnection,
@@ -349,12 +550,31 @@ impl Puzzle {
             .await
     }
 
+    /// Insert a puzzle exactly as given, used when restoring archives so
+    /// that the original timestamps are kept
+    #[tracing::instrument(skip_all, name = "Puzzle::insert")]
+    pub async fn insert(conn: &mut AsyncPgConnection, new: &NewPuzzle<'_>) -> QueryResult<Self> {
+        use crate::schema::puzzle;
+        diesel::insert_into(puzzle::table)
+            .values(new)
+            .get_result(conn)
+            .await
+    }
+
     #[tracing::instrument(skip_all, name = "Puzzle::can_be_seen")]
     pub async fn can_be_seen(
         &self,
         conn: &mut AsyncPgConnection,
         user: Option<&str>,
     ) -> QueryResult<bool> {
+        if self.hidden {
+            // Hidden puzzles are only for their owners and administrators
+            return match user {
+                Some(user) => Ok(self.is_owned_by(conn, user).await?
+                    || Identity::uuid_is_admin(conn, user).await?),
+                None => Ok(false),
+            };
+        }
         match self.visibility {
             Visibility::Restricted => {
                 if let Some(user) = user {
@@ -385,9 +605,18 @@ impl Puzzle {
             .await
     }
 
+    /// We're permitted to edit this puzzle *iff* we can modify the owning role
     #[tracing::instrument(skip_all, name = "Puzzle::can_edit")]
     pub async fn can_edit(&self, conn: &mut AsyncPgConnection, user: &str) -> QueryResult<bool> {
-        // We're permitted to edit this puzzle *iff* the given user has access to the owning role
+        match Role::by_uuid(conn, &self.owner).await? {
+            Some(role) => role.can_modify(conn, user).await,
+            None => Ok(false),
+        }
+    }
+
+    #[tracing::instrument(skip_all, name = "Puzzle::is_owned_by")]
+    pub async fn is_owned_by(&self, conn: &mut AsyncPgConnection, user: &str) -> QueryResult<bool> {
+        // The puzzle is ours *iff* the given user has access to the owning role
         let user = match Identity::from_uuid(conn, user).await? {
             Some(id) => id,
             None => return Ok(false),
@@ -421,6 +650,31 @@ impl Puzzle {
             .await
     }
 
+    /// Hide or unhide this puzzle
+    #[tracing::instrument(skip_all, name = "Puzzle::set_hidden")]
+    pub async fn set_hidden(
+        &self,
+        conn: &mut AsyncPgConnection,
+        hidden: bool,
+    ) -> QueryResult<Self> {
+        use crate::schema::puzzle::dsl;
+        diesel::update(dsl::puzzle.find(&self.uuid))
+            .set(dsl::hidden.eq(hidden))
+            .get_result(conn)
+            .await
+    }
+
+    /// Retrieve every hidden puzzle
+    #[tracing::instrument(skip_all, name = "Puzzle::all_hidden")]
+    pub async fn all_hidden(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        use crate::schema::puzzle::dsl::*;
+        puzzle
+            .filter(hidden.eq(true))
+            .order_by(updated_at.desc())
+            .load(conn)
+            .await
+    }
+
     #[tracing::instrument(skip_all, name = "Puzzle::get_tags")]
     pub async fn get_tags(&self, conn: &mut AsyncPgConnection) -> QueryResult<Vec<String>> {
         use crate::schema::puzzle_tag::dsl;
@@ -432,12 +686,68 @@ impl Puzzle {
             .await
     }
 
+    #[tracing::instrument(skip_all, name = "Puzzle::all")]
+    pub async fn all(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        use crate::schema::puzzle::dsl::*;
+
+        puzzle.order_by(created_at.asc()).load(conn).await
+    }
+
+    /// Find published puzzles whose names contain the given text
+    #[tracing::instrument(skip_all, name = "Puzzle::search_published")]
+    pub async fn search_published(
+        conn: &mut AsyncPgConnection,
+        text: &str,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use crate::schema::puzzle::dsl::*;
+        let pattern = utils::contains_pattern(text);
+        puzzle
+            .filter(visibility.eq(Visibility::Published))
+            .filter(hidden.eq(false))
+            .filter(short_name.ilike(&pattern).or(display_name.ilike(&pattern)))
+            .order_by(updated_at.desc())
+            .limit(limit)
+            .load(conn)
+            .await
+    }
+
+    #[tracing::instrument(skip_all, name = "Puzzle::count_published")]
+    pub async fn count_published(conn: &mut AsyncPgConnection) -> QueryResult<i64> {
+        use crate::schema::puzzle::dsl::*;
+        puzzle
+            .filter(visibility.eq(Visibility::Published))
+            .filter(hidden.eq(false))
+            .count()
+            .get_result(conn)
+            .await
+    }
+
+    /// Retrieve a page of every published puzzle, in a stable order
+    #[tracing::instrument(skip_all, name = "Puzzle::published_page")]
+    pub async fn published_page(
+        conn: &mut AsyncPgConnection,
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use crate::schema::puzzle::dsl::*;
+        puzzle
+            .filter(visibility.eq(Visibility::Published))
+            .filter(hidden.eq(false))
+            .order_by(uuid.asc())
+            .offset(offset)
+            .limit(limit)
+            .load(conn)
+            .await
+    }
+
     #[tracing::instrument(skip_all, name = "Puzzle::get_recent_published")]
     pub async fn get_recent_published(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
         use crate::schema::puzzle::dsl as pdsl;
 
         pdsl::puzzle
             .filter(pdsl::visibility.eq(Visibility::Published))
+            .filter(pdsl::hidden.eq(false))
             .order_by(pdsl::updated_at.desc())
             .limit(10)
             .get_results(conn)
@@ -454,6 +764,8 @@ pub struct PuzzleState {
     pub updated_at: OffsetDateTime,
     pub data: String,
     pub uuid: String,
+    pub solution_check: SolutionCheck,
+    pub hide_solution: bool,
 }
 
 #[derive(Insertable)]
@@ -465,6 +777,8 @@ pub struct NewPuzzleState<'a> {
     pub updated_at: OffsetDateTime,
     pub data: &'a str,
     pub uuid: &'a str,
+    pub solution_check: SolutionCheck,
+    pub hide_solution: bool,
 }
 
 impl Puzzle {
@@ -485,6 +799,8 @@ impl Puzzle {
         description: &str,
         visibility: Visibility,
         data: &str,
+        solution_check: SolutionCheck,
+        hide_solution: bool,
     ) -> QueryResult<PuzzleState> {
         use crate::schema::puzzle_state;
         let state_uuid = utils::random_uuid_within("puzzle_state", &self.uuid);
@@ -495,6 +811,8 @@ impl Puzzle {
             data,
             updated_at: OffsetDateTime::now_utc(),
             uuid: &state_uuid,
+            solution_check,
+            hide_solution,
         };
         diesel::insert_into(puzzle_state::table)
             .values(&new)
@@ -528,6 +846,19 @@ impl PuzzleState {
         }
     }
 
+    /// Insert a puzzle state exactly as given, used when restoring archives
+    #[tracing::instrument(skip_all, name = "PuzzleState::insert")]
+    pub async fn insert(
+        conn: &mut AsyncPgConnection,
+        new: &NewPuzzleState<'_>,
+    ) -> QueryResult<Self> {
+        use crate::schema::puzzle_state;
+        diesel::insert_into(puzzle_state::table)
+            .values(new)
+            .get_result(conn)
+            .await
+    }
+
     #[tracing::instrument(skip_all, name = "PuzzleState::by_uuid")]
     pub async fn by_uuid(conn: &mut AsyncPgConnection, uuid: &str) -> QueryResult<Option<Self>> {
         use crate::schema::puzzle_state::dsl;
@@ -545,6 +876,8 @@ impl PuzzleState {
         conn: &mut AsyncPgConnection,
         description: &str,
         data: &str,
+        solution_check: SolutionCheck,
+        hide_solution: bool,
     ) -> QueryResult<()> {
         use crate::schema::puzzle_state::dsl;
 
@@ -553,6 +886,8 @@ impl PuzzleState {
             .set((
                 dsl::description.eq(description),
                 dsl::data.eq(data),
+                dsl::solution_check.eq(solution_check),
+                dsl::hide_solution.eq(hide_solution),
                 dsl::updated_at.eq(OffsetDateTime::now_utc()),
             ))
             .execute(conn)
@@ -633,9 +968,96 @@ impl Tag {
             description,
         };
 
-        diesel::insert_into(dsl::tag)
+        let tag = diesel::insert_into(dsl::tag)
             .values(newtag)
             .get_result(conn)
+            .await?;
+        Self::forget_cached().await;
+        Ok(tag)
+    }
+
+    /// Save this tag's name, colours, and description
+    #[tracing::instrument(skip_all, name = "Tag::save")]
+    pub async fn save(&self, conn: &mut AsyncPgConnection) -> QueryResult<Self> {
+        use crate::schema::tag::dsl;
+
+        let tag = diesel::update(dsl::tag.find(&self.uuid))
+            .set((
+                dsl::name.eq(&self.name),
+                dsl::colour.eq(&self.colour),
+                dsl::black_text.eq(self.black_text),
+                dsl::description.eq(&self.description),
+            ))
+            .get_result(conn)
+            .await?;
+        Self::forget_cached().await;
+        Ok(tag)
+    }
+
+    /// Delete this tag, removing it from every puzzle which has it
+    #[tracing::instrument(skip_all, name = "Tag::delete")]
+    pub async fn delete(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        use crate::schema::{puzzle_tag, tag};
+
+        diesel::delete(puzzle_tag::table)
+            .filter(puzzle_tag::tag.eq(&self.uuid))
+            .execute(conn)
+            .await?;
+        diesel::delete(tag::table.find(&self.uuid))
+            .execute(conn)
+            .await?;
+        Self::forget_cached().await;
+        Ok(())
+    }
+
+    /// Give every puzzle with this tag the other tag instead, and then
+    /// delete this tag
+    #[tracing::instrument(skip_all, name = "Tag::merge_into")]
+    pub async fn merge_into(&self, conn: &mut AsyncPgConnection, other: &Tag) -> QueryResult<()> {
+        use crate::schema::puzzle_tag::dsl;
+
+        let already: Vec<String> = dsl::puzzle_tag
+            .filter(dsl::tag.eq(&other.uuid))
+            .select(dsl::puzzle)
+            .get_results(conn)
+            .await?;
+        let moving: Vec<String> = dsl::puzzle_tag
+            .filter(dsl::tag.eq(&self.uuid))
+            .select(dsl::puzzle)
+            .get_results(conn)
+            .await?;
+        for puzzle in moving.iter().filter(|puzzle| !already.contains(puzzle)) {
+            diesel::insert_into(dsl::puzzle_tag)
+                .values(NewPuzzleTag {
+                    uuid: &utils::uuid("tag", &other.uuid, puzzle),
+                    puzzle,
+                    tag: &other.uuid,
+                })
+                .execute(conn)
+                .await?;
+        }
+
+        self.delete(conn).await
+    }
+
+    /// Tags are cached by UUID and by search pattern, and any change to a
+    /// tag could alter either
+    async fn forget_cached() {
+        TAG_CACHE.lock().await.clear();
+        TAG_CACHE_BY_PATTERN.lock().await.clear();
+    }
+
+    #[tracing::instrument(skip_all, name = "Tag::by_names")]
+    pub async fn by_names(
+        conn: &mut AsyncPgConnection,
+        names: &[String],
+    ) -> QueryResult<Vec<Self>> {
+        use crate::schema::tag::dsl;
+
+        dsl::tag
+            .filter(dsl::name.eq_any(names))
+            .order_by(dsl::name.asc())
+            .get_results(conn)
             .await
     }
 
@@ -703,3 +1125,407 @@ impl Puzzle {
             .map(|_| ())
     }
 }
+
+#[derive(Debug, Queryable)]
+pub struct ApiToken {
+    pub uuid: String,
+    pub owner: String,
+    pub name: String,
+    pub token_hash: String,
+    pub role: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub last_used: Option<OffsetDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::api_token)]
+pub struct NewApiToken<'a> {
+    pub uuid: &'a str,
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub token_hash: &'a str,
+    pub role: Option<&'a str>,
+}
+
+impl ApiToken {
+    /// Create a token, the caller is responsible for hashing the secret
+    #[tracing::instrument(skip_all, name = "ApiToken::create")]
+    pub async fn create(
+        conn: &mut AsyncPgConnection,
+        owner: &str,
+        name: &str,
+        token_hash: &str,
+        role: Option<&str>,
+    ) -> QueryResult<Self> {
+        use crate::schema::api_token;
+        let uuid = utils::random_uuid_within("api_token", owner);
+        let new = NewApiToken {
+            uuid: &uuid,
+            owner,
+            name,
+            token_hash,
+            role,
+        };
+        diesel::insert_into(api_token::table)
+            .values(&new)
+            .get_result(conn)
+            .await
+    }
+
+    /// Retrieve the token whose secret has the given hash
+    #[tracing::instrument(skip_all, name = "ApiToken::by_hash")]
+    pub async fn by_hash(conn: &mut AsyncPgConnection, hash: &str) -> QueryResult<Option<Self>> {
+        use crate::schema::api_token::dsl;
+        dsl::api_token
+            .filter(dsl::token_hash.eq(hash))
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    /// Retrieve all the tokens belonging to the given identity, oldest first
+    #[tracing::instrument(skip_all, name = "ApiToken::by_owner")]
+    pub async fn by_owner(conn: &mut AsyncPgConnection, owner: &str) -> QueryResult<Vec<Self>> {
+        use crate::schema::api_token::dsl;
+        dsl::api_token
+            .filter(dsl::owner.eq(owner))
+            .order_by(dsl::created_at.asc())
+            .get_results(conn)
+            .await
+    }
+
+    /// Record that the token has just been used
+    #[tracing::instrument(skip_all, name = "ApiToken::touch")]
+    pub async fn touch(&self, conn: &mut AsyncPgConnection) -> QueryResult<()> {
+        use crate::schema::api_token::dsl;
+        diesel::update(dsl::api_token.find(&self.uuid))
+            .set(dsl::last_used.eq(OffsetDateTime::now_utc()))
+            .execute(conn)
+            .await
+            .map(|_| ())
+    }
+
+    /// Delete the given token, if it belongs to the given identity
+    ///
+    /// Returns whether a token was deleted
+    #[tracing::instrument(skip_all, name = "ApiToken::delete")]
+    pub async fn delete(
+        conn: &mut AsyncPgConnection,
+        owner: &str,
+        uuid: &str,
+    ) -> QueryResult<bool> {
+        use crate::schema::api_token::dsl;
+        diesel::delete(dsl::api_token)
+            .filter(dsl::uuid.eq(uuid).and(dsl::owner.eq(owner)))
+            .execute(conn)
+            .await
+            .map(|n| n > 0)
+    }
+}
+
+#[derive(Debug, Queryable)]
+pub struct Report {
+    pub uuid: String,
+    pub reporter: String,
+    pub target_kind: ReportTargetKind,
+    pub target: String,
+    pub reason: ReportReason,
+    pub note: String,
+    pub status: ReportStatus,
+    pub created_at: OffsetDateTime,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<OffsetDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::report)]
+pub struct NewReport<'a> {
+    pub uuid: &'a str,
+    pub reporter: &'a str,
+    pub target_kind: ReportTargetKind,
+    pub target: &'a str,
+    pub reason: ReportReason,
+    pub note: &'a str,
+}
+
+impl Report {
+    #[tracing::instrument(skip_all, name = "Report::create")]
+    pub async fn create(
+        conn: &mut AsyncPgConnection,
+        reporter: &str,
+        target_kind: ReportTargetKind,
+        target: &str,
+        reason: ReportReason,
+        note: &str,
+    ) -> QueryResult<Self> {
+        use crate::schema::report;
+        let uuid = utils::random_uuid_within("report", reporter);
+        let new = NewReport {
+            uuid: &uuid,
+            reporter,
+            target_kind,
+            target,
+            reason,
+            note,
+        };
+        diesel::insert_into(report::table)
+            .values(&new)
+            .get_result(conn)
+            .await
+    }
+
+    /// Retrieve the open report, if any, which the reporter has already filed
+    /// about the target for the given reason
+    #[tracing::instrument(skip_all, name = "Report::open_by")]
+    pub async fn open_by(
+        conn: &mut AsyncPgConnection,
+        reporter: &str,
+        target_kind: ReportTargetKind,
+        target: &str,
+        reason: ReportReason,
+    ) -> QueryResult<Option<Self>> {
+        use crate::schema::report::dsl;
+        dsl::report
+            .filter(
+                dsl::reporter
+                    .eq(reporter)
+                    .and(dsl::target_kind.eq(target_kind))
+                    .and(dsl::target.eq(target))
+                    .and(dsl::reason.eq(reason))
+                    .and(dsl::status.eq(ReportStatus::Open)),
+            )
+            .first(conn)
+            .await
+            .optional()
+    }
+
+    #[tracing::instrument(skip_all, name = "Report::set_note")]
+    pub async fn set_note(&self, conn: &mut AsyncPgConnection, note: &str) -> QueryResult<Self> {
+        use crate::schema::report::dsl;
+        diesel::update(dsl::report.find(&self.uuid))
+            .set(dsl::note.eq(note))
+            .get_result(conn)
+            .await
+    }
+
+    /// Retrieve every open report, oldest first
+    #[tracing::instrument(skip_all, name = "Report::all_open")]
+    pub async fn all_open(conn: &mut AsyncPgConnection) -> QueryResult<Vec<Self>> {
+        use crate::schema::report::dsl;
+        dsl::report
+            .filter(dsl::status.eq(ReportStatus::Open))
+            .order_by(dsl::created_at.asc())
+            .get_results(conn)
+            .await
+    }
+
+    /// Close every open report against the target
+    ///
+    /// Returns how many reports were closed
+    #[tracing::instrument(skip_all, name = "Report::close_all")]
+    pub async fn close_all(
+        conn: &mut AsyncPgConnection,
+        target_kind: ReportTargetKind,
+        target: &str,
+        status: ReportStatus,
+        closed_by: &str,
+    ) -> QueryResult<usize> {
+        use crate::schema::report::dsl;
+        diesel::update(dsl::report)
+            .filter(
+                dsl::target_kind
+                    .eq(target_kind)
+                    .and(dsl::target.eq(target))
+                    .and(dsl::status.eq(ReportStatus::Open)),
+            )
+            .set((
+                dsl::status.eq(status),
+                dsl::closed_by.eq(closed_by),
+                dsl::closed_at.eq(OffsetDateTime::now_utc()),
+            ))
+            .execute(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Queryable)]
+pub struct RoleFollow {
+    pub identity: String,
+    pub role: String,
+    pub created_at: OffsetDateTime,
+}
+
+impl RoleFollow {
+    /// Follow a role, which does nothing if it is already followed
+    #[tracing::instrument(skip_all, name = "RoleFollow::follow")]
+    pub async fn follow(
+        conn: &mut AsyncPgConnection,
+        identity: &str,
+        role: &str,
+    ) -> QueryResult<()> {
+        use crate::schema::role_follow::dsl;
+        diesel::insert_into(dsl::role_follow)
+            .values((dsl::identity.eq(identity), dsl::role.eq(role)))
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await
+            .map(|_| ())
+    }
+
+    #[tracing::instrument(skip_all, name = "RoleFollow::unfollow")]
+    pub async fn unfollow(
+        conn: &mut AsyncPgConnection,
+        identity: &str,
+        role: &str,
+    ) -> QueryResult<()> {
+        use crate::schema::role_follow::dsl;
+        diesel::delete(dsl::role_follow.find((identity, role)))
+            .execute(conn)
+            .await
+            .map(|_| ())
+    }
+
+    /// The UUIDs of every role the identity follows
+    #[tracing::instrument(skip_all, name = "RoleFollow::roles_followed_by")]
+    pub async fn roles_followed_by(
+        conn: &mut AsyncPgConnection,
+        identity: &str,
+    ) -> QueryResult<Vec<String>> {
+        use crate::schema::role_follow::dsl;
+        dsl::role_follow
+            .filter(dsl::identity.eq(identity))
+            .order_by(dsl::created_at.asc())
+            .select(dsl::role)
+            .get_results(conn)
+            .await
+    }
+
+    /// The UUIDs of every identity which follows the role
+    #[tracing::instrument(skip_all, name = "RoleFollow::followers_of")]
+    pub async fn followers_of(
+        conn: &mut AsyncPgConnection,
+        role: &str,
+    ) -> QueryResult<Vec<String>> {
+        use crate::schema::role_follow::dsl;
+        dsl::role_follow
+            .filter(dsl::role.eq(role))
+            .select(dsl::identity)
+            .get_results(conn)
+            .await
+    }
+}
+
+#[derive(Debug, Queryable)]
+pub struct Notification {
+    pub uuid: String,
+    pub identity: String,
+    pub kind: NotificationKind,
+    pub role: String,
+    pub puzzle: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub read: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::notification)]
+pub struct NewNotification<'a> {
+    pub uuid: String,
+    pub identity: &'a str,
+    pub kind: NotificationKind,
+    pub role: &'a str,
+    pub puzzle: Option<&'a str>,
+}
+
+impl<'a> NewNotification<'a> {
+    pub fn new(
+        identity: &'a str,
+        kind: NotificationKind,
+        role: &'a str,
+        puzzle: Option<&'a str>,
+    ) -> Self {
+        Self {
+            uuid: utils::random_uuid_within("notification", identity),
+            identity,
+            kind,
+            role,
+            puzzle,
+        }
+    }
+}
+
+impl Notification {
+    /// Store notifications, skipping any which their identities have
+    /// already been sent
+    ///
+    /// Returns how many were stored
+    #[tracing::instrument(skip_all, name = "Notification::create_all")]
+    pub async fn create_all(
+        conn: &mut AsyncPgConnection,
+        new: &[NewNotification<'_>],
+    ) -> QueryResult<usize> {
+        use crate::schema::notification;
+        diesel::insert_into(notification::table)
+            .values(new)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await
+    }
+
+    /// Retrieve a page of the identity's notifications, newest first
+    #[tracing::instrument(skip_all, name = "Notification::for_identity")]
+    pub async fn for_identity(
+        conn: &mut AsyncPgConnection,
+        identity: &str,
+        offset: i64,
+        limit: i64,
+    ) -> QueryResult<Vec<Self>> {
+        use crate::schema::notification::dsl;
+        dsl::notification
+            .filter(dsl::identity.eq(identity))
+            .order_by((dsl::created_at.desc(), dsl::uuid.asc()))
+            .offset(offset)
+            .limit(limit)
+            .get_results(conn)
+            .await
+    }
+
+    #[tracing::instrument(skip_all, name = "Notification::count_for_identity")]
+    pub async fn count_for_identity(
+        conn: &mut AsyncPgConnection,
+        identity: &str,
+        unread_only: bool,
+    ) -> QueryResult<i64> {
+        use crate::schema::notification::dsl;
+        let mut query = dsl::notification
+            .filter(dsl::identity.eq(identity))
+            .into_boxed();
+        if unread_only {
+            query = query.filter(dsl::read.eq(false));
+        }
+        query.count().get_result(conn).await
+    }
+
+    /// Mark the identity's notifications as read, either those given or all
+    /// of them
+    #[tracing::instrument(skip_all, name = "Notification::mark_read")]
+    pub async fn mark_read(
+        conn: &mut AsyncPgConnection,
+        identity: &str,
+        uuids: Option<&[String]>,
+    ) -> QueryResult<usize> {
+        use crate::schema::notification::dsl;
+        let query = diesel::update(dsl::notification)
+            .filter(dsl::identity.eq(identity))
+            .filter(dsl::read.eq(false));
+        match uuids {
+            Some(uuids) => {
+                query
+                    .filter(dsl::uuid.eq_any(uuids))
+                    .set(dsl::read.eq(true))
+                    .execute(conn)
+                    .await
+            }
+            None => query.set(dsl::read.eq(true)).execute(conn).await,
+        }
+    }
+}
@@ -1,12 +1,16 @@
 //! SQL Types for Linkdoku models
 
-use common::objects;
+use common::{internal::report, objects};
 use diesel::{
     backend::Backend, deserialize::FromSql, pg::Pg, query_builder::QueryId, serialize::ToSql,
     sql_types::Text, AsExpression, FromSqlRow, SqlType,
 };
 
-use crate::schema::sql_types::Visibility as VisibilityType;
+use crate::schema::sql_types::{
+    NotificationKind as NotificationKindType, ReportReason as ReportReasonType,
+    ReportStatus as ReportStatusType, ReportTarget as ReportTargetType,
+    SolutionCheck as SolutionCheckType, Visibility as VisibilityType,
+};
 
 #[derive(Debug, FromSqlRow, AsExpression, SqlType)]
 #[diesel(sql_type = VisibilityType)]
@@ -70,3 +74,257 @@ impl QueryId for crate::schema::sql_types::Visibility {
 
     const HAS_STATIC_QUERY_ID: bool = true;
 }
+
+#[derive(Debug, FromSqlRow, AsExpression, SqlType)]
+#[diesel(sql_type = SolutionCheckType)]
+pub enum SolutionCheck {
+    Disabled,
+    Verdict,
+    CountWrong,
+}
+
+impl<DB: Backend> ToSql<SolutionCheckType, DB> for SolutionCheck
+where
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, DB>,
+    ) -> diesel::serialize::Result {
+        match *self {
+            SolutionCheck::Disabled => ("disabled").to_sql(out),
+            SolutionCheck::Verdict => ("verdict").to_sql(out),
+            SolutionCheck::CountWrong => ("count_wrong").to_sql(out),
+        }
+    }
+}
+
+impl FromSql<SolutionCheckType, Pg> for SolutionCheck {
+    fn from_sql(
+        bytes: <diesel::pg::Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"disabled" => Ok(Self::Disabled),
+            b"verdict" => Ok(Self::Verdict),
+            b"count_wrong" => Ok(Self::CountWrong),
+            _ => Err("Unrecognised solution check variant".into()),
+        }
+    }
+}
+
+impl From<SolutionCheck> for objects::SolutionCheck {
+    fn from(val: SolutionCheck) -> Self {
+        match val {
+            SolutionCheck::Disabled => objects::SolutionCheck::Disabled,
+            SolutionCheck::Verdict => objects::SolutionCheck::Verdict,
+            SolutionCheck::CountWrong => objects::SolutionCheck::CountWrong,
+        }
+    }
+}
+
+impl From<objects::SolutionCheck> for SolutionCheck {
+    fn from(value: objects::SolutionCheck) -> Self {
+        match value {
+            objects::SolutionCheck::Disabled => SolutionCheck::Disabled,
+            objects::SolutionCheck::Verdict => SolutionCheck::Verdict,
+            objects::SolutionCheck::CountWrong => SolutionCheck::CountWrong,
+        }
+    }
+}
+
+impl QueryId for crate::schema::sql_types::SolutionCheck {
+    type QueryId = Self;
+
+    const HAS_STATIC_QUERY_ID: bool = true;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromSqlRow, AsExpression, SqlType)]
+#[diesel(sql_type = ReportTargetType)]
+pub enum ReportTargetKind {
+    Puzzle,
+    PuzzleState,
+    Role,
+}
+
+impl<DB: Backend> ToSql<ReportTargetType, DB> for ReportTargetKind
+where
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, DB>,
+    ) -> diesel::serialize::Result {
+        match *self {
+            ReportTargetKind::Puzzle => ("puzzle").to_sql(out),
+            ReportTargetKind::PuzzleState => ("puzzle_state").to_sql(out),
+            ReportTargetKind::Role => ("role").to_sql(out),
+        }
+    }
+}
+
+impl FromSql<ReportTargetType, Pg> for ReportTargetKind {
+    fn from_sql(
+        bytes: <diesel::pg::Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"puzzle" => Ok(Self::Puzzle),
+            b"puzzle_state" => Ok(Self::PuzzleState),
+            b"role" => Ok(Self::Role),
+            _ => Err("Unrecognised report target variant".into()),
+        }
+    }
+}
+
+impl QueryId for crate::schema::sql_types::ReportTarget {
+    type QueryId = Self;
+
+    const HAS_STATIC_QUERY_ID: bool = true;
+}
+
+#[derive(Debug, FromSqlRow, AsExpression, SqlType)]
+#[diesel(sql_type = ReportReasonType)]
+pub enum ReportReason {
+    BrokenLink,
+    Spoiler,
+    Plagiarism,
+    Abuse,
+}
+
+impl<DB: Backend> ToSql<ReportReasonType, DB> for ReportReason
+where
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, DB>,
+    ) -> diesel::serialize::Result {
+        match *self {
+            ReportReason::BrokenLink => ("broken_link").to_sql(out),
+            ReportReason::Spoiler => ("spoiler").to_sql(out),
+            ReportReason::Plagiarism => ("plagiarism").to_sql(out),
+            ReportReason::Abuse => ("abuse").to_sql(out),
+        }
+    }
+}
+
+impl FromSql<ReportReasonType, Pg> for ReportReason {
+    fn from_sql(
+        bytes: <diesel::pg::Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"broken_link" => Ok(Self::BrokenLink),
+            b"spoiler" => Ok(Self::Spoiler),
+            b"plagiarism" => Ok(Self::Plagiarism),
+            b"abuse" => Ok(Self::Abuse),
+            _ => Err("Unrecognised report reason variant".into()),
+        }
+    }
+}
+
+impl From<ReportReason> for report::ReportReason {
+    fn from(val: ReportReason) -> Self {
+        match val {
+            ReportReason::BrokenLink => report::ReportReason::BrokenLink,
+            ReportReason::Spoiler => report::ReportReason::Spoiler,
+            ReportReason::Plagiarism => report::ReportReason::Plagiarism,
+            ReportReason::Abuse => report::ReportReason::Abuse,
+        }
+    }
+}
+
+impl From<report::ReportReason> for ReportReason {
+    fn from(value: report::ReportReason) -> Self {
+        match value {
+            report::ReportReason::BrokenLink => ReportReason::BrokenLink,
+            report::ReportReason::Spoiler => ReportReason::Spoiler,
+            report::ReportReason::Plagiarism => ReportReason::Plagiarism,
+            report::ReportReason::Abuse => ReportReason::Abuse,
+        }
+    }
+}
+
+impl QueryId for crate::schema::sql_types::ReportReason {
+    type QueryId = Self;
+
+    const HAS_STATIC_QUERY_ID: bool = true;
+}
+
+#[derive(Debug, FromSqlRow, AsExpression, SqlType)]
+#[diesel(sql_type = ReportStatusType)]
+pub enum ReportStatus {
+    Open,
+    Resolved,
+    Dismissed,
+}
+
+impl<DB: Backend> ToSql<ReportStatusType, DB> for ReportStatus
+where
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, DB>,
+    ) -> diesel::serialize::Result {
+        match *self {
+            ReportStatus::Open => ("open").to_sql(out),
+            ReportStatus::Resolved => ("resolved").to_sql(out),
+            ReportStatus::Dismissed => ("dismissed").to_sql(out),
+        }
+    }
+}
+
+impl FromSql<ReportStatusType, Pg> for ReportStatus {
+    fn from_sql(
+        bytes: <diesel::pg::Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"open" => Ok(Self::Open),
+            b"resolved" => Ok(Self::Resolved),
+            b"dismissed" => Ok(Self::Dismissed),
+            _ => Err("Unrecognised report status variant".into()),
+        }
+    }
+}
+
+impl QueryId for crate::schema::sql_types::ReportStatus {
+    type QueryId = Self;
+
+    const HAS_STATIC_QUERY_ID: bool = true;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromSqlRow, AsExpression, SqlType)]
+#[diesel(sql_type = NotificationKindType)]
+pub enum NotificationKind {
+    RolePublished,
+}
+
+impl<DB: Backend> ToSql<NotificationKindType, DB> for NotificationKind
+where
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, DB>,
+    ) -> diesel::serialize::Result {
+        match *self {
+            NotificationKind::RolePublished => ("role_published").to_sql(out),
+        }
+    }
+}
+
+impl FromSql<NotificationKindType, Pg> for NotificationKind {
+    fn from_sql(
+        bytes: <diesel::pg::Pg as diesel::backend::Backend>::RawValue<'_>,
+    ) -> diesel::deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"role_published" => Ok(Self::RolePublished),
+            _ => Err("Unrecognised notification kind variant".into()),
+        }
+    }
+}
+
+impl QueryId for crate::schema::sql_types::NotificationKind {
+    type QueryId = Self;
+
+    const HAS_STATIC_QUERY_ID: bool = true;
+}
@@ -1,17 +1,65 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "notification_kind"))]
+    pub struct NotificationKind;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_reason"))]
+    pub struct ReportReason;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_status"))]
+    pub struct ReportStatus;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "report_target"))]
+    pub struct ReportTarget;
+
+    #[derive(diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "solution_check"))]
+    pub struct SolutionCheck;
+
     #[derive(diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "visibility"))]
     pub struct Visibility;
 }
 
+diesel::table! {
+    api_token (uuid) {
+        uuid -> Varchar,
+        owner -> Varchar,
+        name -> Varchar,
+        token_hash -> Varchar,
+        role -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        last_used -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     identity (uuid) {
         uuid -> Varchar,
         oidc_handle -> Varchar,
         display_name -> Varchar,
         gravatar_hash -> Varchar,
+        is_admin -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::NotificationKind;
+
+    notification (uuid) {
+        uuid -> Varchar,
+        identity -> Varchar,
+        kind -> NotificationKind,
+        role -> Varchar,
+        puzzle -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        read -> Bool,
     }
 }
 
@@ -27,12 +75,14 @@ diesel::table! {
         visibility -> Visibility,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        hidden -> Bool,
     }
 }
 
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::Visibility;
+    use super::sql_types::SolutionCheck;
 
     puzzle_state (id) {
         id -> Int4,
@@ -42,6 +92,8 @@ diesel::table! {
         updated_at -> Timestamptz,
         data -> Text,
         uuid -> Varchar,
+        solution_check -> SolutionCheck,
+        hide_solution -> Bool,
     }
 }
 
@@ -53,6 +105,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ReportTarget;
+    use super::sql_types::ReportReason;
+    use super::sql_types::ReportStatus;
+
+    report (uuid) {
+        uuid -> Varchar,
+        reporter -> Varchar,
+        target_kind -> ReportTarget,
+        target -> Varchar,
+        reason -> ReportReason,
+        note -> Text,
+        status -> ReportStatus,
+        created_at -> Timestamptz,
+        closed_by -> Nullable<Varchar>,
+        closed_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     role (uuid) {
         uuid -> Varchar,
@@ -60,6 +132,15 @@ diesel::table! {
         display_name -> Varchar,
         description -> Text,
         short_name -> Varchar,
+        locked -> Bool,
+    }
+}
+
+diesel::table! {
+    role_follow (identity, role) {
+        identity -> Varchar,
+        role -> Varchar,
+        created_at -> Timestamptz,
     }
 }
 
@@ -73,17 +154,28 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(api_token -> identity (owner));
+diesel::joinable!(api_token -> role (role));
+diesel::joinable!(notification -> identity (identity));
+diesel::joinable!(notification -> puzzle (puzzle));
+diesel::joinable!(notification -> role (role));
 diesel::joinable!(puzzle -> role (owner));
 diesel::joinable!(puzzle_state -> puzzle (puzzle));
 diesel::joinable!(puzzle_tag -> puzzle (puzzle));
 diesel::joinable!(puzzle_tag -> tag (tag));
 diesel::joinable!(role -> identity (owner));
+diesel::joinable!(role_follow -> identity (identity));
+diesel::joinable!(role_follow -> role (role));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_token,
     identity,
+    notification,
     puzzle,
     puzzle_state,
     puzzle_tag,
+    report,
     role,
+    role_follow,
     tag,
 );
@@ -1,6 +1,8 @@
 //! Utility functions for use in this database
 //!
 
+use sha2::{Digest, Sha256};
+
 const KIND_MARKER: &str = "KIND\0";
 const ID_MARKER: &str = "\0ID\0";
 const SALT_MARKER: &str = "\0SALT\0";
@@ -37,3 +39,46 @@ pub fn random_uuid_within(kind: &str, uuid: &str) -> String {
     state.consume(rand::random::<[u8; 16]>());
     format!("{:x}", state.compute())
 }
+
+/// A fresh secret for an API token
+pub fn token_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("ldk_{hex}")
+}
+
+/// The hash under which a token secret is stored
+pub fn token_hash(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// An `ILIKE` pattern matching anything which contains `text`
+///
+/// Wildcards and the escape character in `text` are escaped so that they
+/// only ever match themselves.
+pub fn contains_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    pattern.push('%');
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_patterns() {
+        assert_eq!(contains_pattern("killer"), "%killer%");
+        assert_eq!(contains_pattern("100%"), "%100\\%%");
+        assert_eq!(contains_pattern("_"), "%\\_%");
+        assert_eq!(contains_pattern("a\\b"), "%a\\\\b%");
+        assert_eq!(contains_pattern(""), "%%");
+    }
+}
@@ -169,8 +169,14 @@ pub struct OpenGraphMetaProps {
     pub width: Option<usize>,
     pub height: Option<usize>,
     pub mimetype: Option<AttrValue>,
+    /// The canonical URL of the page, if this is not given then the URL
+    /// of the current page is used and no canonical link is emitted
     pub url: Option<AttrValue>,
     pub description: AttrValue,
+    /// Whether to let oEmbed consumers discover the card for this page,
+    /// which only makes sense for puzzle pages
+    #[prop_or_default]
+    pub oembed: bool,
 }
 
 #[function_component(OpenGraphMeta)]
@@ -185,6 +191,12 @@ pub fn opengraph_meta_render(props: &OpenGraphMetaProps) -> Html {
         .clone()
         .unwrap_or_else(|| AttrValue::from("website"));
     let favicon = use_asset_url(&base.asset_str);
+    // Only real images are worth showing large in link previews
+    let twitter_card = if props.image.is_some() {
+        "summary_large_image"
+    } else {
+        "summary"
+    };
     let image = props
         .image
         .clone()
@@ -196,21 +208,50 @@ pub fn opengraph_meta_render(props: &OpenGraphMetaProps) -> Html {
         .clone()
         .unwrap_or(AttrValue::Static("image/svg+xml"));
     let this_uri = use_page_url();
+    let canonical = match props.url.clone() {
+        Some(url) => html! {
+            <Helmet>
+                <link rel="canonical" href={url} />
+            </Helmet>
+        },
+        None => html! {},
+    };
     let url = props
         .url
         .clone()
         .unwrap_or_else(|| AttrValue::from(this_uri));
+    let oembed = if props.oembed {
+        let href = format!(
+            "{}oembed?url={}",
+            base.uri,
+            url::form_urlencoded::byte_serialize(url.as_bytes()).collect::<String>()
+        );
+        html! {
+            <Helmet>
+                <link rel="alternate" type="application/json+oembed" href={href} title={title.clone()} />
+            </Helmet>
+        }
+    } else {
+        html! {}
+    };
     let description = props.description.clone();
     html! {
-        <Helmet>
-            <meta property="og:title" content={title} />
-            <meta property="og:type" content={ogtype} />
-            <meta property="og:image" content={image} />
-            <meta property="og:image:width" content={width} />
-            <meta property="og:image:height" content={height} />
-            <meta property="og:image:type" content={mimetype} />
-            <meta property="og:url" content={url} />
-            <meta property="og:description" content={description} />
-        </Helmet>
+        <>
+            <Helmet>
+                <meta name="description" content={description.clone()} />
+                <meta property="og:site_name" content="Linkdoku" />
+                <meta property="og:title" content={title} />
+                <meta property="og:type" content={ogtype} />
+                <meta property="og:image" content={image} />
+                <meta property="og:image:width" content={width} />
+                <meta property="og:image:height" content={height} />
+                <meta property="og:image:type" content={mimetype} />
+                <meta property="og:url" content={url} />
+                <meta property="og:description" content={description} />
+                <meta name="twitter:card" content={twitter_card} />
+            </Helmet>
+            {canonical}
+            {oembed}
+        </>
     }
 }
@@ -28,6 +28,11 @@ pub enum IconType {
     WarningIcon,
     BrokenIcon,
     CancelIcon,
+    ReportIcon,
+    NotificationIcon,
+    UnreadNotificationIcon,
+    FollowIcon,
+    FollowingIcon,
 }
 
 pub use IconType::*;
@@ -61,6 +66,11 @@ impl IconType {
             WarningIcon => "mdi-alert-circle",
             BrokenIcon => "mdi-heart-broken",
             CancelIcon => "mdi-cancel",
+            ReportIcon => "mdi-flag-outline",
+            NotificationIcon => "mdi-bell-outline",
+            UnreadNotificationIcon => "mdi-bell-ring",
+            FollowIcon => "mdi-account-plus-outline",
+            FollowingIcon => "mdi-account-check",
         }
     }
 }
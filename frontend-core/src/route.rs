@@ -12,6 +12,12 @@ pub enum Route {
     CreatePuzzle,
     #[at("/puzzle/:puzzle")]
     ViewPuzzle { puzzle: String },
+    #[at("/settings")]
+    Settings,
+    #[at("/admin")]
+    Admin,
+    #[at("/notifications")]
+    Notifications,
 
     // These routes are informational and not for general use
     #[at("/-/version-info")]
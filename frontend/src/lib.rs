@@ -4,7 +4,7 @@ use bounce::{
     BounceRoot,
 };
 use components::{
-    layout::VersionChecker,
+    layout::{frontend_version_hash, VersionChecker},
     modals::TermsAndConditions,
     user::{UserMenuNavbarItem, UserProvider},
 };
@@ -57,7 +57,7 @@ pub(crate) fn root_element() -> Html {
         tscsredisplay,
     );
     html! {
-        <LinkdokuAPIProvider>
+        <LinkdokuAPIProvider persist_version={frontend_version_hash()}>
             <UserProvider>
                 <ToastContainer>
                     <Helmet>
@@ -1,4 +1,7 @@
+pub(crate) mod admin;
 pub(crate) mod home;
+pub(crate) mod notifications;
 pub(crate) mod puzzle;
 pub(crate) mod role;
+pub(crate) mod settings;
 pub(crate) mod shortcuts;
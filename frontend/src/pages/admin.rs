@@ -0,0 +1,660 @@
+//! Administration pages for Linkdoku
+//!
+//! Administrators manage tags here, work through reports filed by users,
+//! and moderate puzzles and roles.  The
+//! backend checks every request, this page merely avoids offering things
+//! to those who cannot do them.
+
+use apiprovider::{use_apiprovider, LinkdokuAPI};
+use common::{
+    internal::{
+        admin::{Overview, ReportedItem},
+        report::ReportTarget,
+    },
+    objects, APIResult,
+};
+use components::{
+    layout::MainPageLayout, role::Role, tag::Tag, user::LoginStatus, utils::NiceDate,
+};
+use frontend_core::Route;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_router::prelude::*;
+use yew_toastrack::{use_toaster, Toast, ToastLevel, Toaster};
+
+use crate::util_components::Title;
+
+#[function_component(AdminPage)]
+pub fn pages_admin_render() -> Html {
+    let user_info = use_context::<LoginStatus>().unwrap();
+
+    let body = if user_info.is_admin() {
+        html! {
+            <>
+                <TagAdmin />
+                <Moderation />
+            </>
+        }
+    } else {
+        html! {
+            <p>{"You must be logged in as an administrator to see this page."}</p>
+        }
+    };
+
+    html! {
+        <MainPageLayout>
+            <Title value="Administration" />
+            <h1 class="title">{"Administration"}</h1>
+            {body}
+        </MainPageLayout>
+    }
+}
+
+fn report_failure(toaster: &Toaster, what: &str, e: impl std::fmt::Display) {
+    toaster.toast(
+        Toast::new(format!("Unable to {what}: {e}"))
+            .with_level(ToastLevel::Danger)
+            .with_lifetime(5000),
+    );
+}
+
+/// Puzzles and roles may be given by UUID or by pasting their permalinks
+fn last_segment(text: &str) -> String {
+    text.trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn input_value(node: &NodeRef) -> String {
+    node.cast::<HtmlInputElement>()
+        .map(|input| input.value())
+        .unwrap_or_default()
+}
+
+// ---------------- Tags ----------------
+
+#[function_component(TagAdmin)]
+fn tag_admin_render() -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let tags = use_state_eq(|| None::<Vec<objects::Tag>>);
+
+    use_effect_with_deps(
+        {
+            let api = api.clone();
+            let tags = tags.setter();
+            let toaster = toaster.clone();
+            move |_: &()| {
+                spawn_local(async move {
+                    match api.find_tags("").await {
+                        Ok(response) => tags.set(Some(response.tags)),
+                        Err(e) => report_failure(&toaster, "list tags", e),
+                    }
+                });
+                || ()
+            }
+        },
+        (),
+    );
+
+    let onchange = Callback::from({
+        let tags = tags.setter();
+        move |new_tags| tags.set(Some(new_tags))
+    });
+
+    let rows = match &*tags {
+        None => html! {
+            <tr><td colspan="6">{"Loading…"}</td></tr>
+        },
+        Some(list) => list
+            .iter()
+            .map(|tag| {
+                html! {
+                    <TagRow key={tag.uuid.clone()} tag={tag.clone()} onchange={onchange.clone()} />
+                }
+            })
+            .collect::<Html>(),
+    };
+
+    let options = tags
+        .as_ref()
+        .map(|list| {
+            list.iter()
+                .map(|tag| html! { <option value={tag.uuid.clone()}>{tag.name.clone()}</option> })
+                .collect::<Html>()
+        })
+        .unwrap_or_default();
+
+    html! {
+        <>
+            <h2 class="subtitle">{"Tags"}</h2>
+            <table class="table is-fullwidth">
+                <thead>
+                    <tr>
+                        <th>{"Tag"}</th>
+                        <th>{"Name"}</th>
+                        <th>{"Colour"}</th>
+                        <th>{"Black text"}</th>
+                        <th>{"Description"}</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows}
+                </tbody>
+            </table>
+            <NewTag onchange={onchange.clone()} />
+            <MergeTags options={options} onchange={onchange} />
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct TagRowProps {
+    tag: objects::Tag,
+    onchange: Callback<Vec<objects::Tag>>,
+}
+
+#[function_component(TagRow)]
+fn tag_row_render(props: &TagRowProps) -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let name_ref = use_node_ref();
+    let colour_ref = use_node_ref();
+    let black_text_ref = use_node_ref();
+    let description_ref = use_node_ref();
+
+    let on_save = Callback::from({
+        let api = api.clone();
+        let toaster = toaster.clone();
+        let onchange = props.onchange.clone();
+        let uuid = props.tag.uuid.clone();
+        let name_ref = name_ref.clone();
+        let colour_ref = colour_ref.clone();
+        let black_text_ref = black_text_ref.clone();
+        let description_ref = description_ref.clone();
+        move |_| {
+            let tag = objects::Tag {
+                uuid: uuid.clone(),
+                name: input_value(&name_ref),
+                colour: input_value(&colour_ref),
+                black_text: black_text_ref
+                    .cast::<HtmlInputElement>()
+                    .map(|input| input.checked())
+                    .unwrap_or_default(),
+                description: input_value(&description_ref),
+            };
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let onchange = onchange.clone();
+            spawn_local(async move {
+                match api.admin_update_tag(tag).await {
+                    Ok(response) => onchange.emit(response.tags),
+                    Err(e) => report_failure(&toaster, "update tag", e),
+                }
+            });
+        }
+    });
+
+    let on_delete = Callback::from({
+        let onchange = props.onchange.clone();
+        let uuid = props.tag.uuid.clone();
+        move |_| {
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let onchange = onchange.clone();
+            let uuid = uuid.clone();
+            spawn_local(async move {
+                match api.admin_delete_tag(uuid).await {
+                    Ok(response) => onchange.emit(response.tags),
+                    Err(e) => report_failure(&toaster, "delete tag", e),
+                }
+            });
+        }
+    });
+
+    let tag = &props.tag;
+    html! {
+        <tr>
+            <td><Tag tag={tag.uuid.clone()} /></td>
+            <td><input ref={name_ref} class="input is-small" type="text" value={tag.name.clone()} /></td>
+            <td><input ref={colour_ref} type="color" value={tag.colour.clone()} /></td>
+            <td><input ref={black_text_ref} type="checkbox" checked={tag.black_text} /></td>
+            <td><input ref={description_ref} class="input is-small" type="text" value={tag.description.clone()} /></td>
+            <td>
+                <div class="buttons">
+                    <button class="button is-small is-primary" onclick={on_save}>{"Save"}</button>
+                    <button class="button is-small is-danger" onclick={on_delete}>{"Delete"}</button>
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct TagChangeProps {
+    onchange: Callback<Vec<objects::Tag>>,
+}
+
+#[function_component(NewTag)]
+fn new_tag_render(props: &TagChangeProps) -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let name_ref = use_node_ref();
+    let colour_ref = use_node_ref();
+    let black_text_ref = use_node_ref();
+    let description_ref = use_node_ref();
+
+    let on_create = Callback::from({
+        let onchange = props.onchange.clone();
+        let name_ref = name_ref.clone();
+        let colour_ref = colour_ref.clone();
+        let black_text_ref = black_text_ref.clone();
+        let description_ref = description_ref.clone();
+        move |_| {
+            let name = input_value(&name_ref);
+            let colour = input_value(&colour_ref);
+            let black_text = black_text_ref
+                .cast::<HtmlInputElement>()
+                .map(|input| input.checked())
+                .unwrap_or_default();
+            let description = input_value(&description_ref);
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let onchange = onchange.clone();
+            let name_ref = name_ref.clone();
+            spawn_local(async move {
+                match api
+                    .admin_create_tag(name, colour, black_text, description)
+                    .await
+                {
+                    Ok(response) => {
+                        if let Some(input) = name_ref.cast::<HtmlInputElement>() {
+                            input.set_value("");
+                        }
+                        onchange.emit(response.tags);
+                    }
+                    Err(e) => report_failure(&toaster, "create tag", e),
+                }
+            });
+        }
+    });
+
+    html! {
+        <div class="box">
+            <h3 class="subtitle is-6">{"New tag"}</h3>
+            <div class="field is-grouped">
+                <div class="control is-expanded">
+                    <input ref={name_ref} class="input" type="text" placeholder="prefix:Name" />
+                </div>
+                <div class="control">
+                    <input ref={colour_ref} type="color" value="#3e8ed0" />
+                </div>
+                <div class="control">
+                    <label class="checkbox">
+                        <input ref={black_text_ref} type="checkbox" checked={true} />
+                        {" Black text"}
+                    </label>
+                </div>
+                <div class="control is-expanded">
+                    <input ref={description_ref} class="input" type="text" placeholder="Description" />
+                </div>
+                <div class="control">
+                    <button class="button is-primary" onclick={on_create}>{"Create tag"}</button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct MergeTagsProps {
+    options: Html,
+    onchange: Callback<Vec<objects::Tag>>,
+}
+
+#[function_component(MergeTags)]
+fn merge_tags_render(props: &MergeTagsProps) -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let from_ref = use_node_ref();
+    let into_ref = use_node_ref();
+
+    let on_merge = Callback::from({
+        let onchange = props.onchange.clone();
+        let from_ref = from_ref.clone();
+        let into_ref = into_ref.clone();
+        move |_| {
+            let (Some(from), Some(into)) = (
+                from_ref.cast::<HtmlSelectElement>(),
+                into_ref.cast::<HtmlSelectElement>(),
+            ) else {
+                return;
+            };
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let onchange = onchange.clone();
+            spawn_local(async move {
+                match api.admin_merge_tags(from.value(), into.value()).await {
+                    Ok(response) => onchange.emit(response.tags),
+                    Err(e) => report_failure(&toaster, "merge tags", e),
+                }
+            });
+        }
+    });
+
+    html! {
+        <div class="box">
+            <h3 class="subtitle is-6">{"Merge tags"}</h3>
+            <div class="field is-grouped">
+                <div class="control">
+                    <div class="select">
+                        <select ref={from_ref}>{props.options.clone()}</select>
+                    </div>
+                </div>
+                <div class="control">
+                    <p class="pt-2">{"is replaced by"}</p>
+                </div>
+                <div class="control">
+                    <div class="select">
+                        <select ref={into_ref}>{props.options.clone()}</select>
+                    </div>
+                </div>
+                <div class="control">
+                    <button class="button is-warning" onclick={on_merge}>{"Merge"}</button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+// ---------------- Moderation ----------------
+
+/// Run a moderation call, and show the overview it returns
+fn moderate<F, Fut>(
+    api: &LinkdokuAPI,
+    toaster: &Toaster,
+    overview: &UseStateSetter<Option<Overview>>,
+    what: &'static str,
+    call: F,
+) where
+    F: FnOnce(LinkdokuAPI) -> Fut,
+    Fut: std::future::Future<Output = APIResult<Overview>> + 'static,
+{
+    let fut = call(api.clone());
+    let toaster = toaster.clone();
+    let overview = overview.clone();
+    spawn_local(async move {
+        match fut.await {
+            Ok(response) => overview.set(Some(response)),
+            Err(e) => report_failure(&toaster, what, e),
+        }
+    });
+}
+
+#[derive(Properties, PartialEq)]
+struct ReportedItemProps {
+    item: ReportedItem,
+    overview: UseStateSetter<Option<Overview>>,
+}
+
+/// Everything open against one target, which is closed all at once
+#[function_component(ReportedItemRow)]
+fn reported_item_render(props: &ReportedItemProps) -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+
+    let close = |what: &'static str, resolved: bool| {
+        let api = api.clone();
+        let toaster = toaster.clone();
+        let overview = props.overview.clone();
+        let target = props.item.target.clone();
+        Callback::from(move |_| {
+            let target = target.clone();
+            moderate(&api, &toaster, &overview, what, |api| async move {
+                if resolved {
+                    api.admin_resolve_reports(target).await
+                } else {
+                    api.admin_dismiss_reports(target).await
+                }
+            });
+        })
+    };
+    let on_resolve = close("resolve reports", true);
+    let on_dismiss = close("dismiss reports", false);
+
+    let item = &props.item;
+    let kind = match item.target {
+        ReportTarget::Puzzle(_) => "Puzzle",
+        ReportTarget::PuzzleState(_) => "Puzzle state",
+        ReportTarget::Role(_) => "Role",
+    };
+    let name = item
+        .display_name
+        .clone()
+        .unwrap_or_else(|| "(deleted)".to_string());
+    let subject = match (&item.puzzle, &item.role) {
+        (Some(puzzle), _) => html! {
+            <Link<Route> to={Route::ViewPuzzle { puzzle: puzzle.clone() }}>{name}</Link<Route>>
+        },
+        (None, Some(role)) => html! {
+            <Link<Route> to={Route::ViewRole { role: role.clone() }}>{name}</Link<Route>>
+        },
+        (None, None) => html! { {name} },
+    };
+
+    let reports = item.reports.iter().map(|report| {
+        html! {
+            <li>
+                <strong>{report.reason.describe()}</strong>
+                {format!(" from {}, ", report.reporter)}
+                <NiceDate date={report.created_at.clone()} />
+                if !report.note.is_empty() {
+                    <p class="has-text-grey">{report.note.clone()}</p>
+                }
+            </li>
+        }
+    });
+
+    html! {
+        <tr>
+            <td>{kind}</td>
+            <td>{subject}</td>
+            <td><ul>{for reports}</ul></td>
+            <td>
+                <div class="buttons">
+                    <button class="button is-small is-success" onclick={on_resolve}>{"Resolve"}</button>
+                    <button class="button is-small" onclick={on_dismiss}>{"Dismiss"}</button>
+                </div>
+            </td>
+        </tr>
+    }
+}
+
+#[function_component(Moderation)]
+fn moderation_render() -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let overview = use_state_eq(|| None::<Overview>);
+    let puzzle_ref = use_node_ref();
+    let role_ref = use_node_ref();
+
+    use_effect_with_deps(
+        {
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let overview = overview.setter();
+            move |_: &()| {
+                moderate(
+                    &api,
+                    &toaster,
+                    &overview,
+                    "load moderation overview",
+                    |api| async move { api.admin_overview().await },
+                );
+                || ()
+            }
+        },
+        (),
+    );
+
+    let puzzle_action = |what: &'static str, hidden: Option<bool>| {
+        let api = api.clone();
+        let toaster = toaster.clone();
+        let overview = overview.setter();
+        let puzzle_ref = puzzle_ref.clone();
+        Callback::from(move |_| {
+            let uuid = last_segment(&input_value(&puzzle_ref));
+            moderate(&api, &toaster, &overview, what, |api| async move {
+                match hidden {
+                    Some(hidden) => api.admin_hide_puzzle(uuid, hidden).await,
+                    None => api.admin_unpublish_puzzle(uuid).await,
+                }
+            });
+        })
+    };
+    let on_hide = puzzle_action("hide puzzle", Some(true));
+    let on_unpublish = puzzle_action("unpublish puzzle", None);
+
+    let on_lock = Callback::from({
+        let api = api.clone();
+        let toaster = toaster.clone();
+        let overview = overview.setter();
+        let role_ref = role_ref.clone();
+        move |_| {
+            let uuid = last_segment(&input_value(&role_ref));
+            moderate(&api, &toaster, &overview, "lock role", |api| async move {
+                api.admin_lock_role(uuid, true).await
+            });
+        }
+    });
+
+    let (reports, hidden, locked) = match &*overview {
+        None => (
+            html! { <tr><td colspan="4">{"Loading…"}</td></tr> },
+            html! { <tr><td colspan="3">{"Loading…"}</td></tr> },
+            html! { <tr><td colspan="2">{"Loading…"}</td></tr> },
+        ),
+        Some(current) => {
+            let reports = if current.reports.is_empty() {
+                html! { <tr><td colspan="4">{"There are no open reports"}</td></tr> }
+            } else {
+                current
+                    .reports
+                    .iter()
+                    .map(|item| {
+                        html! {
+                            <ReportedItemRow item={item.clone()} overview={overview.setter()} />
+                        }
+                    })
+                    .collect::<Html>()
+            };
+            let hidden = if current.hidden_puzzles.is_empty() {
+                html! { <tr><td colspan="3">{"No puzzles are hidden"}</td></tr> }
+            } else {
+                current
+                    .hidden_puzzles
+                    .iter()
+                    .map(|puzzle| {
+                        let on_unhide = Callback::from({
+                            let api = api.clone();
+                            let toaster = toaster.clone();
+                            let overview = overview.setter();
+                            let uuid = puzzle.uuid.clone();
+                            move |_| {
+                                let uuid = uuid.clone();
+                                moderate(&api, &toaster, &overview, "unhide puzzle", |api| async move {
+                                    api.admin_hide_puzzle(uuid, false).await
+                                });
+                            }
+                        });
+                        html! {
+                            <tr>
+                                <td>
+                                    <Link<Route> to={Route::ViewPuzzle { puzzle: puzzle.uuid.clone() }}>
+                                        {puzzle.display_name.clone()}
+                                    </Link<Route>>
+                                </td>
+                                <td><Role uuid={puzzle.owner.clone()} /></td>
+                                <td>
+                                    <button class="button is-small" onclick={on_unhide}>{"Unhide"}</button>
+                                </td>
+                            </tr>
+                        }
+                    })
+                    .collect::<Html>()
+            };
+            let locked = if current.locked_roles.is_empty() {
+                html! { <tr><td colspan="2">{"No roles are locked"}</td></tr> }
+            } else {
+                current
+                    .locked_roles
+                    .iter()
+                    .map(|role| {
+                        let on_unlock = Callback::from({
+                            let api = api.clone();
+                            let toaster = toaster.clone();
+                            let overview = overview.setter();
+                            let uuid = role.uuid.clone();
+                            move |_| {
+                                let uuid = uuid.clone();
+                                moderate(&api, &toaster, &overview, "unlock role", |api| async move {
+                                    api.admin_lock_role(uuid, false).await
+                                });
+                            }
+                        });
+                        html! {
+                            <tr>
+                                <td><Role uuid={role.uuid.clone()} /></td>
+                                <td>
+                                    <button class="button is-small" onclick={on_unlock}>{"Unlock"}</button>
+                                </td>
+                            </tr>
+                        }
+                    })
+                    .collect::<Html>()
+            };
+            (reports, hidden, locked)
+        }
+    };
+
+    html! {
+        <>
+            <h2 class="subtitle">{"Moderation"}</h2>
+            <h3 class="subtitle is-6">{"Reports"}</h3>
+            <table class="table is-fullwidth">
+                <tbody>{reports}</tbody>
+            </table>
+            <h3 class="subtitle is-6">{"Hidden puzzles"}</h3>
+            <table class="table is-fullwidth">
+                <tbody>{hidden}</tbody>
+            </table>
+            <div class="field has-addons">
+                <div class="control is-expanded">
+                    <input ref={puzzle_ref} class="input" type="text" placeholder="Puzzle UUID or permalink" />
+                </div>
+                <div class="control">
+                    <button class="button is-danger" onclick={on_hide}>{"Hide"}</button>
+                </div>
+                <div class="control">
+                    <button class="button is-warning" onclick={on_unpublish}>{"Unpublish"}</button>
+                </div>
+            </div>
+            <h3 class="subtitle is-6">{"Locked roles"}</h3>
+            <table class="table is-fullwidth">
+                <tbody>{locked}</tbody>
+            </table>
+            <div class="field has-addons">
+                <div class="control is-expanded">
+                    <input ref={role_ref} class="input" type="text" placeholder="Role UUID or permalink" />
+                </div>
+                <div class="control">
+                    <button class="button is-danger" onclick={on_lock}>{"Lock"}</button>
+                </div>
+            </div>
+        </>
+    }
+}
@@ -0,0 +1,211 @@
+//! The notification inbox
+//!
+
+use apiprovider::use_apiprovider;
+use bounce::use_atom_setter;
+use common::internal::notification::{self, Notification, NotificationEvent, PAGE_SIZE};
+use components::{
+    layout::MainPageLayout, notification::UnreadNotifications, user::LoginStatus, utils::NiceDate,
+};
+use frontend_core::{component::icon::*, Route};
+use yew::{platform::spawn_local, prelude::*};
+use yew_router::prelude::*;
+use yew_toastrack::{use_toaster, Toast, ToastLevel};
+
+use crate::util_components::Title;
+
+#[function_component(NotificationsPage)]
+pub fn pages_notifications_render() -> Html {
+    let user_info = use_context::<LoginStatus>().unwrap();
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let set_unread = use_atom_setter::<UnreadNotifications>();
+    let page = use_state_eq(|| 0usize);
+    let listing = use_state_eq(|| None::<notification::list::Response>);
+
+    use_effect_with_deps(
+        {
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let listing = listing.setter();
+            let set_unread = set_unread.clone();
+            move |(logged_in, page): &(bool, usize)| {
+                if *logged_in {
+                    let page = *page;
+                    spawn_local(async move {
+                        match api.notifications(page).await {
+                            Ok(found) => {
+                                set_unread(UnreadNotifications(found.unread));
+                                listing.set(Some(found));
+                            }
+                            Err(e) => toaster.toast(
+                                Toast::new(format!("Unable to list notifications: {e}"))
+                                    .with_level(ToastLevel::Warning)
+                                    .with_lifetime(5000),
+                            ),
+                        }
+                    });
+                }
+                || ()
+            }
+        },
+        (user_info.is_logged_in(), *page),
+    );
+
+    if !user_info.is_logged_in() {
+        return html! {
+            <MainPageLayout>
+                <Title value="Notifications" />
+                <p>{"You must be logged in to see your notifications."}</p>
+            </MainPageLayout>
+        };
+    }
+
+    // Marking things read updates the current page in place, rather than
+    // fetching it all over again
+    let mark_read = Callback::from({
+        let listing = listing.clone();
+        move |uuids: Option<Vec<String>>| {
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let listing = listing.clone();
+            let set_unread = set_unread.clone();
+            spawn_local(async move {
+                match api.mark_notifications_read(uuids.clone()).await {
+                    Ok(found) => {
+                        set_unread(UnreadNotifications(found.unread));
+                        if let Some(current) = listing.as_ref() {
+                            let mut updated = current.clone();
+                            for item in updated.notifications.iter_mut() {
+                                let marked = match &uuids {
+                                    Some(uuids) => uuids.contains(&item.uuid),
+                                    None => true,
+                                };
+                                if marked {
+                                    item.read = true;
+                                }
+                            }
+                            updated.unread = found.unread;
+                            listing.set(Some(updated));
+                        }
+                    }
+                    Err(e) => toaster.toast(
+                        Toast::new(format!("Unable to mark notifications as read: {e}"))
+                            .with_level(ToastLevel::Danger)
+                            .with_lifetime(5000),
+                    ),
+                }
+            });
+        }
+    });
+
+    let content = match &*listing {
+        None => html! {
+            <p>{"Loading…"}</p>
+        },
+        Some(found) if found.notifications.is_empty() => html! {
+            <p>
+                {"You have no notifications.  Follow a role to be told whenever it publishes a puzzle."}
+            </p>
+        },
+        Some(found) => {
+            let items = found.notifications.iter().map(|item| {
+                html! {
+                    <NotificationItem item={item.clone()} mark_read={mark_read.clone()} />
+                }
+            });
+            let pages = found.total.div_ceil(PAGE_SIZE);
+            let previous = Callback::from({
+                let page = page.clone();
+                move |_| page.set(page.saturating_sub(1))
+            });
+            let next = Callback::from({
+                let page = page.clone();
+                move |_| page.set(*page + 1)
+            });
+            let mark_all = mark_read.reform(|_| None);
+            html! {
+                <>
+                    <div class="buttons">
+                        <button class="button is-small" onclick={mark_all} disabled={found.unread == 0}>
+                            {"Mark all as read"}
+                        </button>
+                    </div>
+                    <div class="panel">
+                        {for items}
+                    </div>
+                    if pages > 1 {
+                        <nav class="pagination" role="navigation" aria-label="pagination">
+                            <button class="pagination-previous" onclick={previous} disabled={*page == 0}>{"Newer"}</button>
+                            <button class="pagination-next" onclick={next} disabled={*page + 1 >= pages}>{"Older"}</button>
+                            <ul class="pagination-list">
+                                <li><span class="pagination-ellipsis">{format!("Page {} of {pages}", *page + 1)}</span></li>
+                            </ul>
+                        </nav>
+                    }
+                </>
+            }
+        }
+    };
+
+    html! {
+        <MainPageLayout>
+            <Title value="Notifications" />
+            <h1 class="title">{"Notifications"}</h1>
+            {content}
+        </MainPageLayout>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct NotificationItemProps {
+    item: Notification,
+    mark_read: Callback<Option<Vec<String>>>,
+}
+
+#[function_component(NotificationItem)]
+fn notification_item_render(props: &NotificationItemProps) -> Html {
+    let nav = use_navigator().unwrap();
+    let item = &props.item;
+
+    let (message, target) = match &item.event {
+        NotificationEvent::RolePublished {
+            role,
+            role_name,
+            puzzle,
+            puzzle_name,
+        } => match puzzle_name {
+            Some(puzzle_name) => (
+                format!("{role_name} published {puzzle_name}"),
+                Route::ViewPuzzle {
+                    puzzle: puzzle.clone(),
+                },
+            ),
+            None => (
+                format!("{role_name} published a puzzle which is no longer available"),
+                Route::ViewRole { role: role.clone() },
+            ),
+        },
+    };
+
+    // Following a notification also marks it as read
+    let onclick = Callback::from({
+        let mark_read = props.mark_read.clone();
+        let uuid = item.uuid.clone();
+        let read = item.read;
+        move |_| {
+            if !read {
+                mark_read.emit(Some(vec![uuid.clone()]));
+            }
+            nav.push(&target);
+        }
+    });
+
+    html! {
+        <a class={classes!("panel-block", (!item.read).then_some("has-text-weight-bold"))} onclick={onclick}>
+            <Icon class={"panel-icon"} icon={if item.read { NotificationIcon } else { UnreadNotificationIcon }} />
+            <span class="is-flex-grow-1">{message}</span>
+            <span class="has-text-grey is-size-7"><NiceDate date={item.created_at.clone()} /></span>
+        </a>
+    }
+}
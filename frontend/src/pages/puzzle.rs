@@ -1,15 +1,23 @@
 use apiprovider::{use_apiprovider, use_cached_value, use_puzzle_lookup};
 use common::{
     clean_short_name,
-    objects::{self, PuzzleData, PuzzleState, Visibility},
+    internal::report::ReportTarget,
+    objects::{self, PuzzleData, PuzzleState, SolutionCheck, Visibility},
     public::puzzle,
 };
-use components::{layout::MainPageLayout, role::Role, tag::TagSet, user::LoginStatus};
+use components::{
+    layout::MainPageLayout, report::ReportButton, role::Role, tag::TagSet, user::LoginStatus,
+};
 use frontend_core::{
     component::{core::OpenGraphMeta, icon::*, utility::*},
     use_route_url, Route, ShortcutRoute,
 };
-use puzzleutils::{fpuzzles, xform::transform_markdown};
+use puzzleutils::{
+    fpuzzles,
+    solver::{self, Solutions, SolverError},
+    variants,
+    xform::transform_markdown,
+};
 use serde_json::Value;
 use stylist::yew::{styled_component, use_style};
 use tracing::info;
@@ -17,7 +25,9 @@ use tutorials::{tutorial, use_tutorial_node, TutorialAnchor, TutorialController,
 use web_sys::{HtmlInputElement, Url};
 use yew::{platform::spawn_local, prelude::*, virtual_dom::VChild};
 use yew_bulma_tabs::*;
-use yew_markdown::{editor::MarkdownEditor, render::MarkdownRender, xform::Transformer};
+use yew_markdown::{
+    editor::MarkdownEditor, plain::excerpt, render::MarkdownRender, xform::Transformer,
+};
 use yew_paginator::Paginator;
 use yew_router::prelude::*;
 use yew_toastrack::{use_toaster, Toast, ToastLevel};
@@ -181,7 +191,7 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
         html! {
             <TutorialAnchor noderef={shortcut_node}>
                 <Tooltip content={"Copy shortcut to puzzle"} alignment={TooltipAlignment::Bottom}>
-                    <CopyButton content={short_url.clone()} icon={PuzzleNiceLinkIcon} size={IconSize::Medium}/>
+                    <CopyButton content={short_url} icon={PuzzleNiceLinkIcon} size={IconSize::Medium}/>
                 </Tooltip>
             </TutorialAnchor>
         }
@@ -220,20 +230,49 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
         move |req| transform_markdown(&state, req)
     });
 
-    let image = match &display_state.data {
-        PuzzleData::FPuzzles(data) => Some(fpuzzles::grid_url_png(data)),
+    // Link previews are fetched anonymously, so the card only ever describes
+    // what anyone could see, whoever happens to be looking at the page
+    let card_state = if puzzle.visibility == Visibility::Restricted {
+        None
+    } else {
+        let latest = |visibility| {
+            puzzle
+                .states
+                .iter()
+                .rev()
+                .find(|state| state.visibility == visibility)
+        };
+        latest(Visibility::Published).or_else(|| latest(Visibility::Public))
+    };
+
+    let image = match card_state.map(|state| &state.data) {
+        Some(PuzzleData::FPuzzles(data)) => Some(fpuzzles::grid_url_png(data)),
         _ => None,
     };
 
+    let canonical_url = use_route_url(&Route::ViewPuzzle {
+        puzzle: puzzle.uuid.clone(),
+    });
+
+    let description = match card_state.map(|state| excerpt(&state.description, 200)) {
+        Some(text) if !text.is_empty() => {
+            format!("A puzzle by {}. {text}", role.display_name)
+        }
+        _ => format!("A puzzle by {}", role.display_name),
+    };
+
+    // Without a grid image the defaults describe the site's own icon
+    let has_image = image.is_some();
     let ogtags = html! {
         <OpenGraphMeta
             title={puzzle.display_name.clone()}
             image={image}
-            width={512}
-            height={512}
-            mimetype={"image/png"}
-            url={short_url}
-            description={format!("{} by {}", puzzle.display_name, role.display_name)}
+            width={has_image.then_some(512)}
+            height={has_image.then_some(512)}
+            mimetype={has_image.then_some(AttrValue::Static("image/png"))}
+            url={canonical_url}
+            description={description}
+            oembed={true}
         />
     };
 
@@ -382,6 +421,8 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
         updated_at: "".to_string(),
         data: PuzzleData::Nothing,
         visibility: Visibility::Restricted,
+        solution_check: SolutionCheck::default(),
+        hide_solution: false,
     });
 
     let state_editor = {
@@ -448,11 +489,39 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
             }
         });
 
+        let add_tag = Callback::from({
+            let api = use_apiprovider();
+            let puzzle_uuid = puzzle.uuid.clone();
+            let toaster = toaster.clone();
+            let puzzle_data = puzzle_data.clone();
+            move |tag: AttrValue| {
+                let api = api.clone();
+                let puzzle_uuid = puzzle_uuid.clone();
+                let toaster = toaster.clone();
+                let puzzle_data = puzzle_data.clone();
+                spawn_local(async move {
+                    match api
+                        .edit_puzzle_tags(&puzzle_uuid, &[tag.to_string()], &[])
+                        .await
+                    {
+                        Ok(puzz) => puzzle_data.refresh(&puzzle_uuid, puzz),
+                        Err(e) => {
+                            toaster.toast(
+                                Toast::new(format!("Unable to add tag to puzzle: {e}"))
+                                    .with_level(ToastLevel::Warning)
+                                    .with_lifetime(2500),
+                            );
+                        }
+                    }
+                });
+            }
+        });
+
         let has_desc = !state_under_edit.description.is_empty();
 
         html! {
             <>
-                <PuzzleStateEditor state_change={onchange} state={(*state_under_edit).clone()} />
+                <PuzzleStateEditor state_change={onchange} state={(*state_under_edit).clone()} tags={puzzle.tags.clone()} add_tag={add_tag} />
                 <div class="field is-grouped">
                     <div class="control">
                         <button class="button is-default" disabled={has_desc && !*button_enabled}onclick={do_save_state}>
@@ -550,7 +619,13 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
             </>
         }
     } else {
-        html! {}
+        html! {
+            <ReportButton
+                target={ReportTarget::Puzzle(puzzle.uuid.clone())}
+                noun={"puzzle"}
+                state={Some(AttrValue::from(display_state.uuid.clone()))}
+            />
+        }
     };
 
     let state_buttons = {
@@ -935,6 +1010,10 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
                     <TagSet tags={puzzle.tags.clone()} />
                     <hr width={"40%"} />
                     <MarkdownRender markdown={display_state.description.clone()} transformer={transformer}/>
+                    if matches!(display_state.data, PuzzleData::FPuzzles(_)) && display_state.solution_check != SolutionCheck::Disabled {
+                        <hr width={"40%"} />
+                        <SolutionChecker state={display_state.uuid.clone()} />
+                    }
                     <hr width={"40%"} />
                     <Paginator count={puzzle.states.len()} current={(*display_index)+1} aria_label={"Puzzle State"} element={"puzzle state"} onchange={set_index} />
                 </>
@@ -992,6 +1071,80 @@ fn view_puzzle_inner(props: &PuzzlePageProps) -> HtmlResult {
     })
 }
 
+#[derive(Properties, PartialEq)]
+struct SolutionCheckerProps {
+    state: String,
+}
+
+#[function_component(SolutionChecker)]
+fn solution_checker_render(props: &SolutionCheckerProps) -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let input_ref = use_node_ref();
+    let outcome = use_state_eq(|| None);
+
+    let onclick = Callback::from({
+        let input_ref = input_ref.clone();
+        let state = props.state.clone();
+        let outcome = outcome.setter();
+        move |_| {
+            let input: HtmlInputElement = input_ref.cast().unwrap();
+            // Digits are cells, anything else which isn't whitespace is an empty cell
+            let grid = input
+                .value()
+                .chars()
+                .filter(|ch| !ch.is_whitespace())
+                .map(|ch| ch.to_digit(10).filter(|&d| d != 0).map(|d| d as u8))
+                .collect();
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let state = state.clone();
+            let outcome = outcome.clone();
+            spawn_local(async move {
+                match api.check_solution(state, grid).await {
+                    Ok(res) => outcome.set(Some(res)),
+                    Err(e) => {
+                        outcome.set(None);
+                        toaster.toast(
+                            Toast::new(format!("Unable to check solution: {e}"))
+                                .with_level(ToastLevel::Warning)
+                                .with_lifetime(5000),
+                        );
+                    }
+                }
+            });
+        }
+    });
+
+    let outcome = match &*outcome {
+        None => html! {},
+        Some(puzzle::check_solution::Response::Correct) => {
+            html! { <p class="help is-success">{"Correct, well done!"}</p> }
+        }
+        Some(puzzle::check_solution::Response::Incorrect) => {
+            html! { <p class="help is-danger">{"Sorry, that is not right"}</p> }
+        }
+        Some(puzzle::check_solution::Response::WrongCells(n)) => {
+            html! { <p class="help is-danger">{format!("Sorry, {n} cell(s) are wrong")}</p> }
+        }
+    };
+
+    html! {
+        <div class="field">
+            <label class="label">{"Check your answer"}</label>
+            <div class="field has-addons">
+                <div class="control is-expanded">
+                    <input ref={input_ref} class="input" type="text" placeholder="Your grid, row by row, e.g. 123456789…" />
+                </div>
+                <div class="control">
+                    <button class="button is-info" onclick={onclick}>{"Check"}</button>
+                </div>
+            </div>
+            {outcome}
+        </div>
+    }
+}
+
 // Editors
 
 const DEFAULT_FPUZZLES_DESCRIPTION: &str = r"
@@ -1014,7 +1167,7 @@ tutorial!(
     CreatePuzzleTutorial,
     role: "You can select which of your roles this puzzle belongs to here",
     puzzle_data: "You can choose which kind of puzzle data goes here",
-    fpuzzles_url: "Enter a URL to an fpuzzles puzzle (or puzzle string) here.  You can also put a tinyurl in here if you already have one.",
+    fpuzzles_url: "Enter a URL to an fpuzzles puzzle (or puzzle string) here.  You can also put a shortened link (eg. tinyurl or bit.ly) or a SudokuPad short link in here if you already have one.",
     fpuzzles_data: "If you enter valid data, the decoded puzzle will show here",
     description: "Write the description of your puzzle here",
     short_name: "The short name of your puzzle will be used in shortcut links",
@@ -1044,7 +1197,10 @@ pub fn create_puzzle_page_render() -> Html {
                 visibility: Visibility::Restricted,
                 updated_at: "".to_string(),
                 data: PuzzleData::FPuzzles(Value::Null),
+                solution_check: SolutionCheck::default(),
+                hide_solution: false,
             },
+            tags: vec![],
         }
         .into()
     });
@@ -1119,6 +1275,18 @@ pub fn create_puzzle_page_render() -> Html {
         let fpd_ref = use_tutorial_node!(tutorial.fpuzzles_data);
         let disp_ref = use_tutorial_node!(tutorial.description);
 
+        let add_tag = Callback::from({
+            let nav = nav.clone();
+            let state = state.clone();
+            move |tag: AttrValue| {
+                let mut state = (*state).clone();
+                if !state.tags.iter().any(|t| t.as_str() == tag.as_str()) {
+                    state.tags.push(tag.to_string());
+                }
+                nav.replace_with_state(&Route::CreatePuzzle, state);
+            }
+        });
+
         fields.push(html! {
             <PuzzleStateEditor
                 state_change={onchange}
@@ -1127,10 +1295,29 @@ pub fn create_puzzle_page_render() -> Html {
                 fpuzzles_data_ref={fp_ref}
                 fpuzzles_data_show_ref={fpd_ref}
                 description_ref={disp_ref}
+                tags={state.tags.clone()}
+                add_tag={add_tag}
             />
         })
     }
 
+    // Tags chosen from the suggestions
+    if !state.tags.is_empty() {
+        let on_delete_tag = Callback::from({
+            let nav = nav.clone();
+            let state = state.clone();
+            move |tag: AttrValue| {
+                let mut state = (*state).clone();
+                state.tags.retain(|t| t.as_str() != tag.as_str());
+                nav.replace_with_state(&Route::CreatePuzzle, state);
+            }
+        });
+
+        fields.push(html! {
+            <TagSet tags={state.tags.clone()} label={"Tags for puzzle"} ondelete={on_delete_tag} />
+        });
+    }
+
     // Short name
     {
         let input_ref = use_node_ref();
@@ -1246,8 +1433,8 @@ pub fn create_puzzle_page_render() -> Html {
                             &state.owner,
                             &state.short_name,
                             &state.display_name,
-                            &state.initial_state.description,
-                            &state.initial_state.data,
+                            &state.initial_state,
+                            &state.tags,
                         )
                         .await
                     {
@@ -1316,6 +1503,9 @@ struct PuzzleStateEditorProps {
     fpuzzles_data_ref: Option<NodeRef>,
     fpuzzles_data_show_ref: Option<NodeRef>,
     description_ref: Option<NodeRef>,
+    #[prop_or_default]
+    tags: Vec<String>,
+    add_tag: Option<Callback<AttrValue>>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -1390,6 +1580,9 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
     // FPuzzles data
     {
         let input_ref = use_node_ref();
+        // Links are only expanded once pasted or committed, rather than on
+        // every keystroke while one is being typed in
+        let pasted = use_mut_ref(|| false);
 
         let handle_change = Callback::from({
             let input_ref = input_ref.clone();
@@ -1398,7 +1591,7 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
             let memory_setter = fpuzzles_memory.setter();
             let apiprovider = use_apiprovider();
             let toaster = use_toaster();
-            move |()| {
+            move |expand: bool| {
                 let input: HtmlInputElement = input_ref.cast().unwrap();
                 let value = input.value();
                 memory_setter.set(value.clone());
@@ -1410,32 +1603,46 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
                         new_state.description = DEFAULT_FPUZZLES_DESCRIPTION.to_string();
                     }
                     setter.emit(new_state);
-                } else {
-                    // Maybe the user pasted a tinyurl or somesuch, kick off a background task to try
-                    // to resolve it, eg. https://tinyurl.com/e3xu5xb4
+                } else if expand {
+                    // Maybe the user pasted a shortened link, kick off a background task to try
+                    // to expand it, eg. https://tinyurl.com/e3xu5xb4
                     let apiprovider = apiprovider.clone();
                     let toaster = toaster.clone();
                     if let Ok(url) = Url::new(&value) {
-                        if let Some(slug) = match url.hostname().to_ascii_lowercase().as_str() {
-                            "tinyurl.com" => Some(url.pathname()),
-                            "fpuzzles.com" => url.search_params().get("id"),
+                        let link = match url.hostname().to_ascii_lowercase().as_str() {
+                            // f-puzzles short links are tinyurl slugs
+                            "fpuzzles.com" | "f-puzzles.com" => url
+                                .search_params()
+                                .get("id")
+                                .map(|slug| format!("https://tinyurl.com/{slug}")),
+                            _ if matches!(url.protocol().as_str(), "http:" | "https:")
+                                && url.pathname().len() > 1 =>
+                            {
+                                Some(value)
+                            }
                             _ => None,
-                        } {
-                            let slug = if let Some(rest) = slug.strip_prefix('/') {
-                                rest.to_string()
-                            } else {
-                                slug
-                            };
+                        };
+                        if let Some(link) = link {
                             spawn_local(async move {
-                                match apiprovider.try_expand_tinyurl(slug).await {
+                                match apiprovider.expand_url(link).await {
                                     Err(e) => {
                                         toaster.toast(
-                                            Toast::new(format!("Failed to expand slug: {e}"))
+                                            Toast::new(format!("Failed to expand link: {e}"))
                                                 .with_level(ToastLevel::Warning)
                                                 .with_lifetime(2500),
                                         );
                                     }
                                     Ok(res) => {
+                                        if !res.unmapped.is_empty() {
+                                            toaster.toast(
+                                                Toast::new(format!(
+                                                    "Some of the puzzle could not be kept: {}",
+                                                    res.unmapped.join(", ")
+                                                ))
+                                                .with_level(ToastLevel::Warning)
+                                                .with_lifetime(5000),
+                                            );
+                                        }
                                         input.set_value(&res.replacement);
                                     }
                                 }
@@ -1448,10 +1655,15 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
 
         let onchanged = Callback::from({
             let handle_change = handle_change.clone();
-            move |_| handle_change.emit(())
+            move |_| handle_change.emit(true)
+        });
+
+        let onpasted = Callback::from({
+            let pasted = pasted.clone();
+            move |_| *pasted.borrow_mut() = true
         });
 
-        let oninput = Callback::from(move |_| handle_change.emit(()));
+        let oninput = Callback::from(move |_| handle_change.emit(pasted.replace(false)));
 
         let content_to_render = if let PuzzleData::FPuzzles(value) = &props.state.data {
             if !matches!(value, Value::Null) {
@@ -1462,10 +1674,22 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
         } else {
             None
         };
+        let onembed = Callback::from({
+            let setter = props.state_change.clone();
+            let state = props.state.clone();
+            let memory_setter = fpuzzles_memory.setter();
+            move |value: Value| {
+                memory_setter.set(fpuzzles::encode(&value));
+                let mut new_state = state.clone();
+                new_state.data = PuzzleData::FPuzzles(value);
+                setter.emit(new_state);
+            }
+        });
+
         let content_rendered = if let Some(value) = content_to_render {
             html! {
                 <div class="tile is-child notification is-success">
-                    <FPuzzlesRenderer data={value.clone()} />
+                    <FPuzzlesRenderer data={value.clone()} onembed={onembed} />
                 </div>
             }
         } else {
@@ -1483,7 +1707,7 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
                         <label class="label">{"Link to puzzle, or puzzle string"}</label>
                     </TutorialAnchor>
                     <div class="control has-icons-left">
-                        <input ref={input_ref} class="input" type="text" placeholder="http://f-puzzles.com/?load=......" onchange={onchanged} oninput={oninput} value={fpuzzles_memory.to_string()}/>
+                        <input ref={input_ref} class="input" type="text" placeholder="http://f-puzzles.com/?load=......" onchange={onchanged} oninput={oninput} onpaste={onpasted} value={fpuzzles_memory.to_string()}/>
                         <Icon size={IconSize::Small} icon={SimpleLinkIcon} class="icon is-left" />
                     </div>
                 </div>
@@ -1565,6 +1789,115 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
         });
     }
 
+    // Solution checking
+    {
+        let choices = [
+            (SolutionCheck::Disabled, "Disabled"),
+            (SolutionCheck::Verdict, "Correct or not"),
+            (SolutionCheck::CountWrong, "Count wrong cells"),
+        ]
+        .into_iter()
+        .map(|(choice, label)| {
+            let onclick = Callback::from({
+                let state_change = props.state_change.clone();
+                let state = props.state.clone();
+                move |_| {
+                    let mut new_state = state.clone();
+                    new_state.solution_check = choice;
+                    state_change.emit(new_state);
+                }
+            });
+            let class = if props.state.solution_check == choice {
+                "button is-info is-selected"
+            } else {
+                "button"
+            };
+            html! {
+                <button class={class} onclick={onclick}>{label}</button>
+            }
+        });
+
+        let toggle_hidden = Callback::from({
+            let state_change = props.state_change.clone();
+            let state = props.state.clone();
+            move |_| {
+                let mut new_state = state.clone();
+                new_state.hide_solution = !new_state.hide_solution;
+                state_change.emit(new_state);
+            }
+        });
+
+        fields.push(html! {
+            <div class="field">
+                <div class="control">
+                    <label class="checkbox">
+                        <input type="checkbox" checked={props.state.hide_solution} onclick={toggle_hidden} />
+                        {" Hide any embedded solution from solvers"}
+                    </label>
+                </div>
+            </div>
+        });
+
+        fields.push(html! {
+            <div class="field">
+                <label class="label">{"Solution checking"}</label>
+                <div class="control">
+                    <div class="buttons has-addons">
+                        {for choices}
+                    </div>
+                </div>
+                <p class="help">{"How much solvers are told when checking their answer against an embedded solution"}</p>
+            </div>
+        });
+    }
+
+    // Suggested tags
+    {
+        let names = match &props.state.data {
+            PuzzleData::FPuzzles(value) if !value.is_null() => variants::suggest_tags(value),
+            _ => vec![],
+        };
+        let suggested = use_state_eq(Vec::<String>::new);
+        {
+            let suggested = suggested.setter();
+            let api = use_apiprovider();
+            use_effect_with_deps(
+                move |names: &Vec<&'static str>| {
+                    let names = names.clone();
+                    spawn_local(async move {
+                        if names.is_empty() {
+                            suggested.set(vec![]);
+                            return;
+                        }
+                        match api.tags_by_name(&names).await {
+                            Ok(resp) => {
+                                suggested.set(resp.tags.into_iter().map(|tag| tag.uuid).collect())
+                            }
+                            Err(e) => info!("Unable to look up suggested tags: {e}"),
+                        }
+                    });
+                },
+                names,
+            );
+        }
+
+        let to_suggest: Vec<_> = suggested
+            .iter()
+            .filter(|tag| !props.tags.contains(tag))
+            .cloned()
+            .collect();
+
+        if let Some(add_tag) = props.add_tag.clone() {
+            if !to_suggest.is_empty() {
+                fields.push(html! {
+                    <div class="field">
+                        <TagSet tags={to_suggest} label={"Suggested tags (click to add)"} onclick={add_tag} />
+                    </div>
+                });
+            }
+        }
+    }
+
     html! {
         <>
             {for fields.into_iter()}
@@ -1575,6 +1908,8 @@ fn puzzle_state_editor_render(props: &PuzzleStateEditorProps) -> Html {
 #[derive(Properties, PartialEq)]
 struct FPuzzlesDataRender {
     data: Value,
+    #[prop_or_default]
+    onembed: Option<Callback<Value>>,
 }
 
 #[styled_component(FPuzzlesRenderer)]
@@ -1614,6 +1949,63 @@ fn fpuzzles_renderer(props: &FPuzzlesDataRender) -> Html {
         "No grid?".into()
     };
 
+    // Solving can take a while, so we only do it when asked, and we remember
+    // which data the result belongs to so that edits invalidate it.
+    let checked = use_state_eq(|| None::<(Value, Result<Solutions, SolverError>)>);
+    let solutions = (*checked)
+        .as_ref()
+        .filter(|(data, _)| data == &props.data)
+        .map(|(_, solutions)| solutions);
+
+    let (unique_state, unique_text) = match solutions {
+        None => (Warn, "Not checked yet".to_string()),
+        Some(Result::Ok(Solutions::Unique(_))) => (Ok, "Verified".to_string()),
+        Some(Result::Ok(Solutions::Multiple)) => (Bad, "Multiple solutions".to_string()),
+        Some(Result::Ok(Solutions::None)) => (Bad, "No solution".to_string()),
+        Some(Err(e)) => (Warn, format!("Not checked: {e}")),
+    };
+
+    let check_button = if solutions.is_none() {
+        let onclick = Callback::from({
+            let checked = checked.clone();
+            let data = props.data.clone();
+            move |_| {
+                let solutions = solver::solve(&data);
+                checked.set(Some((data.clone(), solutions)));
+            }
+        });
+        html! {
+            <div class="field">
+                <button class="button is-small is-info" onclick={onclick}>{"Check for a unique solution"}</button>
+            </div>
+        }
+    } else {
+        html! {}
+    };
+
+    let embed_button = match (solutions, &props.onembed) {
+        (Some(Result::Ok(Solutions::Unique(solution))), Some(onembed))
+            if !metadata.has_solution =>
+        {
+            let onclick = Callback::from({
+                let onembed = onembed.clone();
+                let data = props.data.clone();
+                let solution = solution.clone();
+                move |_| {
+                    let mut data = data.clone();
+                    solver::embed_solution(&mut data, &solution);
+                    onembed.emit(data);
+                }
+            });
+            html! {
+                <div class="field">
+                    <button class="button is-small is-info" onclick={onclick}>{"Embed found solution"}</button>
+                </div>
+            }
+        }
+        _ => html! {},
+    };
+
     html! {
         <div class={"tile is-ancestor"}>
             <div class={"tile"}>
@@ -1624,6 +2016,9 @@ fn fpuzzles_renderer(props: &FPuzzlesDataRender) -> Html {
                         {show_field("Author", metadata.author.as_ref().map(|_| Ok).unwrap_or(Bad), metadata.author.unwrap_or_else(||"No embedded author".to_string()))}
                         {show_field("Ruleset", metadata.rules.as_ref().map(|_| Ok).unwrap_or(Bad), metadata.rules.map(|_| "Provided").unwrap_or("Not provided").to_string())}
                         {show_field("Solution", if metadata.has_solution { Ok } else { Warn }, (if metadata.has_solution { "Provided" } else { "Not provided" }).to_string())}
+                        {show_field("Unique solution verified", unique_state, unique_text)}
+                        {check_button}
+                        {embed_button}
                     </div>
                 </div>
             </div>
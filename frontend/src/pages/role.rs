@@ -2,20 +2,31 @@
 //!
 //! Currently there are two main pages here, the RolePage and the RoleEditPage
 
+use std::rc::Rc;
+
 use apiprovider::{use_apiprovider, use_cached_value, use_cached_value_by_name};
-use common::objects;
-use components::{layout::MainPageLayout, puzzle::PuzzleList, user::LoginStatus};
+use common::{
+    internal::report::ReportTarget,
+    objects,
+    public::role::puzzles::{self, PuzzleSort, MAX_TAGS, PAGE_SIZE},
+    APIResult,
+};
+use components::{
+    layout::MainPageLayout, notification::FollowButton, puzzle::PuzzleListEntry,
+    report::ReportButton, tag::TagSet, user::LoginStatus,
+};
 use frontend_core::{
-    component::{icon::*, utility::*},
+    component::{core::OpenGraphMeta, icon::*, utility::*},
     use_route_url, Route, ShortcutRoute,
 };
 use tutorials::{
     tutorial, use_tutorial_node, TutorialAnchor, TutorialAnchorPosition::TutorialTop,
     TutorialController, TutorialData,
 };
-use web_sys::HtmlInputElement;
-use yew::{platform::spawn_local, prelude::*};
-use yew_markdown::{editor::MarkdownEditor, render::MarkdownRender};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*, suspense::*};
+use yew_markdown::{editor::MarkdownEditor, plain::excerpt, render::MarkdownRender};
+use yew_paginator::Paginator;
 use yew_router::prelude::*;
 use yew_toastrack::{use_toaster, Toast, ToastLevel};
 
@@ -89,23 +100,26 @@ fn pages_role_render_inner(props: &RolePageProps) -> HtmlResult {
             </TutorialAnchor>
         }
     } else {
-        html! {}
+        html! {
+            <>
+                <FollowButton role={raw_role.uuid.clone()} />
+                <ReportButton target={ReportTarget::Role(raw_role.uuid.clone())} noun={"role"} />
+            </>
+        }
     };
 
     let perma_link_node = use_tutorial_node!(tutorial.permalink);
 
-    let perma_link = {
-        let permalink = Route::ViewRole {
-            role: raw_role.uuid.clone(),
-        };
-        let permalink = use_route_url(&permalink);
-        html! {
-            <TutorialAnchor noderef={perma_link_node}>
-                <Tooltip content={"Copy permalink to role"} alignment={TooltipAlignment::Bottom}>
-                    <CopyButton content={permalink} size={IconSize::Medium}/>
-                </Tooltip>
-            </TutorialAnchor>
-        }
+    let permalink = use_route_url(&Route::ViewRole {
+        role: raw_role.uuid.clone(),
+    });
+
+    let perma_link = html! {
+        <TutorialAnchor noderef={perma_link_node}>
+            <Tooltip content={"Copy permalink to role"} alignment={TooltipAlignment::Bottom}>
+                <CopyButton content={permalink.clone()} size={IconSize::Medium}/>
+            </Tooltip>
+        </TutorialAnchor>
     };
 
     let shortcut_link_node = use_tutorial_node!(tutorial.shortcutlink);
@@ -124,6 +138,19 @@ fn pages_role_render_inner(props: &RolePageProps) -> HtmlResult {
         }
     };
 
+    let description = match excerpt(&raw_role.description, 200) {
+        text if text.is_empty() => format!("Puzzles by {}", raw_role.display_name),
+        text => text,
+    };
+    let ogtags = html! {
+        <OpenGraphMeta
+            title={raw_role.display_name.clone()}
+            ogtype={"profile"}
+            url={permalink}
+            description={description}
+        />
+    };
+
     let name_node = use_tutorial_node!(tutorial.name);
     let description_node = use_tutorial_node!(tutorial.description);
     let puzzle_list_node = use_tutorial_node!(tutorial.puzzle_list);
@@ -131,6 +158,7 @@ fn pages_role_render_inner(props: &RolePageProps) -> HtmlResult {
     Ok(html! {
         <>
             <TutorialController tutorial={TutorialData::from(tutorial)} />
+            {ogtags}
             <Title value={format!("{} - Role", raw_role.display_name)} />
             <h1 class={"title"}>
                 <TutorialAnchor noderef={name_node}>
@@ -145,12 +173,215 @@ fn pages_role_render_inner(props: &RolePageProps) -> HtmlResult {
             </TutorialAnchor>
             <hr width={"40%"} />
             <TutorialAnchor noderef={puzzle_list_node} position={TutorialTop} class="is-block">
-                <PuzzleList role={raw_role.uuid.clone()} />
+                <RolePuzzleList role={raw_role.uuid.clone()} />
             </TutorialAnchor>
         </>
     })
 }
 
+#[derive(Properties, PartialEq)]
+struct RolePuzzleListProps {
+    role: AttrValue,
+}
+
+/// Pages through a role's puzzles, which can be sorted and filtered by tag
+#[function_component(RolePuzzleList)]
+fn role_puzzle_list_render(props: &RolePuzzleListProps) -> Html {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let page = use_state_eq(|| 0usize);
+    let sort = use_state_eq(PuzzleSort::default);
+    let tags = use_state_eq(Vec::<String>::new);
+    let tag_filter = use_state_eq(String::new);
+    let found_tags = use_state_eq(Vec::<String>::new);
+    let sort_ref = use_node_ref();
+    let tag_filter_ref = use_node_ref();
+
+    let on_sort = Callback::from({
+        let sort = sort.setter();
+        let page = page.setter();
+        let node = sort_ref.clone();
+        move |_| {
+            let select: HtmlSelectElement = node.cast().unwrap();
+            sort.set(match select.value().as_str() {
+                "updated" => PuzzleSort::Updated,
+                "name" => PuzzleSort::Name,
+                _ => PuzzleSort::Newest,
+            });
+            page.set(0);
+        }
+    });
+
+    let on_filter_input = Callback::from({
+        let filter_setter = tag_filter.setter();
+        let found_setter = found_tags.setter();
+        let node = tag_filter_ref.clone();
+        move |_| {
+            let input: HtmlInputElement = node.cast().unwrap();
+            let value = input.value();
+            filter_setter.set(value.clone());
+            if value.is_empty() {
+                found_setter.set(vec![]);
+                return;
+            }
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let found_setter = found_setter.clone();
+            spawn_local(async move {
+                match api.find_tags(value).await {
+                    Ok(found) => {
+                        found_setter.set(found.tags.into_iter().map(|tag| tag.uuid).collect())
+                    }
+                    Err(e) => toaster.toast(
+                        Toast::new(format!("Unable to find tags: {e}"))
+                            .with_level(ToastLevel::Warning)
+                            .with_lifetime(2500),
+                    ),
+                }
+            });
+        }
+    });
+
+    let on_add_tag = Callback::from({
+        let tags = tags.clone();
+        let page = page.setter();
+        let filter_setter = tag_filter.setter();
+        let found_setter = found_tags.setter();
+        move |tag: AttrValue| {
+            if tags.len() < MAX_TAGS && !tags.iter().any(|t| t.as_str() == tag.as_str()) {
+                let mut newtags = (*tags).clone();
+                newtags.push(tag.to_string());
+                tags.set(newtags);
+                page.set(0);
+            }
+            filter_setter.set(String::new());
+            found_setter.set(vec![]);
+        }
+    });
+
+    let on_remove_tag = Callback::from({
+        let tags = tags.clone();
+        let page = page.setter();
+        move |tag: AttrValue| {
+            tags.set(
+                tags.iter()
+                    .filter(|t| t.as_str() != tag.as_str())
+                    .cloned()
+                    .collect(),
+            );
+            page.set(0);
+        }
+    });
+
+    let on_page = Callback::from({
+        let page = page.setter();
+        move |n: usize| page.set(n - 1)
+    });
+
+    html! {
+        <>
+            <div class="panel">
+                <p class="panel-heading">{"Puzzles"}</p>
+                <div class="panel-block">
+                    <div class="field is-grouped is-grouped-multiline">
+                        <div class="control">
+                            <div class="select is-small">
+                                <select ref={sort_ref} onchange={on_sort}>
+                                    <option value="newest" selected={*sort == PuzzleSort::Newest}>{"Newest first"}</option>
+                                    <option value="updated" selected={*sort == PuzzleSort::Updated}>{"Recently updated first"}</option>
+                                    <option value="name" selected={*sort == PuzzleSort::Name}>{"By name"}</option>
+                                </select>
+                            </div>
+                        </div>
+                        <div class="control">
+                            <input ref={tag_filter_ref} class="input is-small" value={(*tag_filter).clone()} placeholder="Filter by tag" oninput={on_filter_input}/>
+                        </div>
+                    </div>
+                </div>
+                if !tags.is_empty() {
+                    <div class="panel-block">
+                        <TagSet tags={(*tags).clone()} ondelete={on_remove_tag} />
+                    </div>
+                }
+                if !found_tags.is_empty() {
+                    <div class="panel-block">
+                        <TagSet tags={(*found_tags).clone()} onclick={on_add_tag} />
+                    </div>
+                }
+                <Suspense fallback={html! {}}>
+                    <RolePuzzlePage role={props.role.clone()} page={*page} sort={*sort} tags={(*tags).clone()} onpage={on_page} />
+                </Suspense>
+            </div>
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct RolePuzzlePageProps {
+    role: AttrValue,
+    page: usize,
+    sort: PuzzleSort,
+    tags: Vec<String>,
+    onpage: Callback<usize>,
+}
+
+/// One page of a role's puzzles, fetched while rendering so that server
+/// rendered role pages list them too
+#[function_component(RolePuzzlePage)]
+fn role_puzzle_page_render(props: &RolePuzzlePageProps) -> HtmlResult {
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+
+    let listing: UseFutureHandle<APIResult<puzzles::Response>> = use_future_with_deps(
+        |deps: Rc<(AttrValue, usize, PuzzleSort, Vec<String>)>| async move {
+            let (role, page, sort, tags) = &*deps;
+            api.published_puzzle_list(role.as_str(), *page, *sort, tags)
+                .await
+        },
+        (
+            props.role.clone(),
+            props.page,
+            props.sort,
+            props.tags.clone(),
+        ),
+    )?;
+
+    let found = match listing.as_ref() {
+        Ok(found) => found,
+        Err(e) => {
+            toaster.toast(
+                Toast::new(format!("Failure fetching puzzle list: {e}"))
+                    .with_level(ToastLevel::Warning)
+                    .with_lifetime(5000),
+            );
+            return Ok(html! {});
+        }
+    };
+
+    if found.puzzles.is_empty() {
+        return Ok(html! {
+            <div class="panel-block">{"There are no puzzles to show"}</div>
+        });
+    }
+
+    let entries = found.puzzles.iter().map(|meta| {
+        html! {
+            <Link<Route> to={Route::ViewPuzzle { puzzle: meta.uuid.clone() }} classes="panel-block">
+                <PuzzleListEntry puzzle={meta.uuid.clone()} show_role={false}/>
+            </Link<Route>>
+        }
+    });
+
+    Ok(html! {
+        <>
+            {for entries}
+            <div class="panel-block">
+                <Paginator count={found.total.div_ceil(PAGE_SIZE)} current={props.page + 1} aria_label={"Puzzle list"} element={"page"} onchange={props.onpage.clone()} />
+            </div>
+        </>
+    })
+}
+
 #[function_component(RoleEditPage)]
 pub fn pages_role_edit(props: &RolePageProps) -> Html {
     let fallback = html! {};
@@ -0,0 +1,231 @@
+//! Settings pages for Linkdoku
+//!
+//! For now this is where personal API tokens are managed
+
+use apiprovider::use_apiprovider;
+use common::internal::token::{TokenInfo, TokenScope};
+use components::{layout::MainPageLayout, role::Role, user::LoginStatus};
+use frontend_core::component::utility::CopyButton;
+use web_sys::HtmlInputElement;
+use yew::{platform::spawn_local, prelude::*};
+use yew_toastrack::{use_toaster, Toast, ToastLevel};
+
+use crate::util_components::Title;
+
+#[function_component(SettingsPage)]
+pub fn pages_settings_render() -> Html {
+    html! {
+        <MainPageLayout>
+            <Title value="Settings" />
+            <h1 class="title">{"Settings"}</h1>
+            <ApiTokens />
+        </MainPageLayout>
+    }
+}
+
+#[function_component(ApiTokens)]
+fn api_tokens_render() -> Html {
+    let user_info = use_context::<LoginStatus>().unwrap();
+    let api = use_apiprovider();
+    let toaster = use_toaster();
+    let tokens = use_state_eq(|| None::<Vec<TokenInfo>>);
+    let new_secret = use_state_eq(|| None::<String>);
+    let publish = use_state_eq(|| false);
+    let name_ref = use_node_ref();
+
+    use_effect_with_deps(
+        {
+            let api = api.clone();
+            let tokens = tokens.setter();
+            let toaster = toaster.clone();
+            move |logged_in: &bool| {
+                if *logged_in {
+                    spawn_local(async move {
+                        match api.list_tokens().await {
+                            Ok(response) => tokens.set(Some(response.tokens)),
+                            Err(e) => toaster.toast(
+                                Toast::new(format!("Unable to list API tokens: {e}"))
+                                    .with_level(ToastLevel::Warning)
+                                    .with_lifetime(5000),
+                            ),
+                        }
+                    });
+                }
+                || ()
+            }
+        },
+        user_info.is_logged_in(),
+    );
+
+    let current_role = match user_info.current_role() {
+        Some(role) => role.to_string(),
+        None => {
+            return html! {
+                <p>{"You must be logged in to manage API tokens."}</p>
+            }
+        }
+    };
+
+    let on_create = Callback::from({
+        let api = api.clone();
+        let toaster = toaster.clone();
+        let tokens = tokens.setter();
+        let new_secret = new_secret.setter();
+        let publish = publish.clone();
+        let name_ref = name_ref.clone();
+        let current_role = current_role.clone();
+        move |_| {
+            let input: HtmlInputElement = name_ref.cast().unwrap();
+            let name = input.value();
+            let scope = if *publish {
+                TokenScope::Publish(current_role.clone())
+            } else {
+                TokenScope::ReadOnly
+            };
+            let api = api.clone();
+            let toaster = toaster.clone();
+            let tokens = tokens.clone();
+            let new_secret = new_secret.clone();
+            spawn_local(async move {
+                match api.create_token(name, scope).await {
+                    Ok(response) => {
+                        input.set_value("");
+                        new_secret.set(Some(response.secret));
+                        if let Ok(response) = api.list_tokens().await {
+                            tokens.set(Some(response.tokens));
+                        }
+                    }
+                    Err(e) => toaster.toast(
+                        Toast::new(format!("Unable to create API token: {e}"))
+                            .with_level(ToastLevel::Danger)
+                            .with_lifetime(5000),
+                    ),
+                }
+            });
+        }
+    });
+
+    let choose_read_only = Callback::from({
+        let setter = publish.setter();
+        move |_| setter.set(false)
+    });
+    let choose_publish = Callback::from({
+        let setter = publish.setter();
+        move |_| setter.set(true)
+    });
+
+    let secret = match &*new_secret {
+        Some(secret) => html! {
+            <div class="notification is-warning">
+                <p>{"Copy your new token now, it will not be shown again."}</p>
+                <p>
+                    <code>{secret.clone()}</code>
+                    <CopyButton content={secret.clone()} />
+                </p>
+            </div>
+        },
+        None => html! {},
+    };
+
+    let rows = match &*tokens {
+        None => html! {
+            <tr><td colspan="5">{"Loading…"}</td></tr>
+        },
+        Some(list) if list.is_empty() => html! {
+            <tr><td colspan="5">{"You have no API tokens"}</td></tr>
+        },
+        Some(list) => list
+            .iter()
+            .map(|token| {
+                let onclick = Callback::from({
+                    let api = api.clone();
+                    let toaster = toaster.clone();
+                    let tokens = tokens.setter();
+                    let uuid = token.uuid.clone();
+                    move |_| {
+                        let api = api.clone();
+                        let toaster = toaster.clone();
+                        let tokens = tokens.clone();
+                        let uuid = uuid.clone();
+                        spawn_local(async move {
+                            match api.revoke_token(uuid).await {
+                                Ok(response) => tokens.set(Some(response.tokens)),
+                                Err(e) => toaster.toast(
+                                    Toast::new(format!("Unable to revoke API token: {e}"))
+                                        .with_level(ToastLevel::Danger)
+                                        .with_lifetime(5000),
+                                ),
+                            }
+                        });
+                    }
+                });
+                let scope = match &token.scope {
+                    TokenScope::ReadOnly => html! { {"Read only"} },
+                    TokenScope::Publish(role) => html! {
+                        <>{"Publish as "}<Role uuid={role.clone()} /></>
+                    },
+                };
+                html! {
+                    <tr>
+                        <td>{token.name.clone()}</td>
+                        <td>{scope}</td>
+                        <td>{token.created_at.clone()}</td>
+                        <td>{token.last_used.clone().unwrap_or_else(|| "Never".into())}</td>
+                        <td>
+                            <button class="button is-small is-danger" onclick={onclick}>{"Revoke"}</button>
+                        </td>
+                    </tr>
+                }
+            })
+            .collect::<Html>(),
+    };
+
+    html! {
+        <>
+            <h2 class="subtitle">{"API tokens"}</h2>
+            <p class="block">
+                {"Scripts can act as you by sending one of these tokens in an "}
+                <code>{"Authorization: Bearer"}</code>
+                {" header.  Revoke any token you no longer need."}
+            </p>
+            {secret}
+            <table class="table is-fullwidth">
+                <thead>
+                    <tr>
+                        <th>{"Name"}</th>
+                        <th>{"Permits"}</th>
+                        <th>{"Created"}</th>
+                        <th>{"Last used"}</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows}
+                </tbody>
+            </table>
+            <div class="field">
+                <label class="label">{"New token name"}</label>
+                <div class="control">
+                    <input ref={name_ref} class="input" type="text" placeholder="e.g. CI publishing" />
+                </div>
+            </div>
+            <div class="field">
+                <div class="control">
+                    <label class="radio">
+                        <input type="radio" name="scope" checked={!*publish} onclick={choose_read_only} />
+                        {" Read only"}
+                    </label>
+                    <label class="radio">
+                        <input type="radio" name="scope" checked={*publish} onclick={choose_publish} />
+                        {" Publish as "}<Role uuid={current_role} />
+                    </label>
+                </div>
+            </div>
+            <div class="field">
+                <div class="control">
+                    <button class="button is-primary" onclick={on_create}>{"Create token"}</button>
+                </div>
+            </div>
+        </>
+    }
+}
@@ -13,9 +13,12 @@ use yew_toastrack::{use_toaster, Toast, ToastLevel};
 
 use crate::{
     pages::{
+        admin::AdminPage,
         home::HomePage,
+        notifications::NotificationsPage,
         puzzle::{CreatePuzzlePage, PuzzlePage},
         role::{RoleEditPage, RolePage},
+        settings::SettingsPage,
         shortcuts::ShortcutHandler,
     },
     util_components::Title,
@@ -59,6 +62,24 @@ pub fn core_frontend_route_switch(route: Route) -> Html {
             }
         }
 
+        Route::Settings => {
+            html! {
+                <SettingsPage />
+            }
+        }
+
+        Route::Admin => {
+            html! {
+                <AdminPage />
+            }
+        }
+
+        Route::Notifications => {
+            html! {
+                <NotificationsPage />
+            }
+        }
+
         // Uncommon routes
         Route::VersionInformation => {
             html! {
@@ -137,6 +158,7 @@ fn login_flow() -> Html {
                                     gravatar_hash: response.userinfo.gravatar_hash,
                                     roles: response.userinfo.roles,
                                     default_role: response.userinfo.default_role,
+                                    is_admin: response.userinfo.is_admin,
                                 });
                                 toaster
                                     .toast(Toast::new("You are now logged in").with_lifetime(2000));
@@ -0,0 +1,11 @@
+//! Neither the decompressor nor the parser may panic on arbitrary input
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use puzzleutils::ctc;
+
+fuzz_target!(|input: &str| {
+    let _ = ctc::parse(input);
+    let _ = ctc::parse_str(input);
+});
@@ -0,0 +1,16 @@
+//! Anything we manage to parse must survive an encode/parse cycle unchanged
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use puzzleutils::ctc;
+
+fuzz_target!(|input: &str| {
+    if let Ok(value) = ctc::parse(input) {
+        let encoded = ctc::encode(&value);
+        let reparsed = ctc::parse(&encoded).expect("encoded value failed to parse");
+        assert_eq!(value, reparsed);
+        // Encoding is canonical, so a second cycle must be byte-identical
+        assert_eq!(encoded, ctc::encode(&reparsed));
+    }
+});
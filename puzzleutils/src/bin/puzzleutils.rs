@@ -0,0 +1,256 @@
+//! Command line tool for working with puzzle links and encodings
+//!
+//! Exit codes are 0 on success, 1 if the input could not be decoded or is
+//! invalid, 2 for usage errors, and 3 if `convert --strict` would have had
+//! to drop part of the puzzle.
+
+use std::{
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand};
+use puzzleutils::{
+    convert::convert,
+    formats::{self, App, DecodeError, Decoded, Format},
+    variants,
+};
+use serde_json::{json, Value};
+
+const EXIT_INVALID: u8 = 1;
+const EXIT_LOSSY: u8 = 3;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Produce machine-readable JSON output
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args, Debug)]
+struct Input {
+    /// Puzzle link or data, read from stdin if omitted or `-`
+    input: Option<String>,
+
+    /// Only try to decode the input as this format (fpuzzles, ctc, digits)
+    #[arg(short, long)]
+    from: Option<Format>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show what a puzzle link or encoded puzzle contains
+    Inspect {
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Translate a puzzle into another format
+    Convert {
+        #[command(flatten)]
+        input: Input,
+
+        /// Format to convert to (fpuzzles, ctc, digits)
+        #[arg(short, long)]
+        to: Format,
+
+        /// Fail if anything in the puzzle cannot be converted
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Build a link which opens the puzzle in an app
+    Url {
+        #[command(flatten)]
+        input: Input,
+
+        /// App to link to (fpuzzles, sudokupad, beta)
+        #[arg(short, long, default_value = "sudokupad")]
+        app: App,
+    },
+    /// Check that a puzzle link or encoding is well formed
+    Validate {
+        #[command(flatten)]
+        input: Input,
+    },
+}
+
+/// Something went wrong, and how to report it
+struct Failure {
+    code: u8,
+    message: String,
+    details: Value,
+}
+
+impl Failure {
+    fn new(code: u8, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: Value::Null,
+        }
+    }
+}
+
+impl From<DecodeError> for Failure {
+    fn from(e: DecodeError) -> Self {
+        let position = match &e {
+            DecodeError::Ctc(e) => e.position(),
+            _ => None,
+        };
+        Failure {
+            code: EXIT_INVALID,
+            message: e.to_string(),
+            details: json!({ "position": position }),
+        }
+    }
+}
+
+type Outcome = Result<(String, Value), Failure>;
+
+impl Input {
+    fn read(&self) -> Result<String, Failure> {
+        match self.input.as_deref() {
+            Some(input) if input != "-" => Ok(input.to_string()),
+            _ => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input).map_err(|e| {
+                    Failure::new(EXIT_INVALID, format!("Unable to read stdin: {e}"))
+                })?;
+                Ok(input)
+            }
+        }
+    }
+
+    fn decode(&self) -> Result<Decoded, Failure> {
+        Ok(formats::decode(&self.read()?, self.from)?)
+    }
+}
+
+fn inspect(input: &Input) -> Outcome {
+    let decoded = input.decode()?;
+    let metadata = decoded.metadata()?;
+    let tags = match decoded.format {
+        Format::Digits => vec![],
+        _ => variants::suggest_tags(&decoded.value),
+    };
+
+    let mut text = format!("Format: {}\n", decoded.format);
+    if let Some((rows, cols)) = metadata.rows_cols {
+        text.push_str(&format!("Grid: {rows}x{cols}\n"));
+    }
+    for (label, value) in [
+        ("Title", &metadata.title),
+        ("Author", &metadata.author),
+        ("Rules", &metadata.rules),
+    ] {
+        if let Some(value) = value {
+            text.push_str(&format!("{label}: {value}\n"));
+        }
+    }
+    text.push_str(&format!(
+        "Solution: {}\n",
+        if metadata.has_solution { "yes" } else { "no" }
+    ));
+    if !tags.is_empty() {
+        text.push_str(&format!("Suggested tags: {}\n", tags.join(", ")));
+    }
+
+    let json = json!({
+        "format": decoded.format,
+        "metadata": metadata,
+        "suggested_tags": tags,
+    });
+    Ok((text, json))
+}
+
+fn convert_cmd(input: &Input, to: Format, strict: bool) -> Outcome {
+    let decoded = input.decode()?;
+    let conversion =
+        convert(&decoded, to).map_err(|e| Failure::new(EXIT_INVALID, e.to_string()))?;
+    if strict && !conversion.unmapped.is_empty() {
+        return Err(Failure {
+            code: EXIT_LOSSY,
+            message: format!(
+                "Unable to convert without losing: {}",
+                conversion.unmapped.join(", ")
+            ),
+            details: json!({ "unmapped": conversion.unmapped }),
+        });
+    }
+    for what in &conversion.unmapped {
+        eprintln!("warning: not converted: {what}");
+    }
+    let output = formats::encode(to, &conversion.value);
+    let json = json!({
+        "from": decoded.format,
+        "to": to,
+        "output": output,
+        "unmapped": conversion.unmapped,
+    });
+    Ok((format!("{output}\n"), json))
+}
+
+fn url(input: &Input, app: App) -> Outcome {
+    let decoded = input.decode()?;
+    let conversion = convert(&decoded, Format::FPuzzles)
+        .map_err(|e| Failure::new(EXIT_INVALID, e.to_string()))?;
+    for what in &conversion.unmapped {
+        eprintln!("warning: not included in link: {what}");
+    }
+    let url = formats::app_url(app, &conversion.value);
+    let json = json!({ "url": url, "unmapped": conversion.unmapped });
+    Ok((format!("{url}\n"), json))
+}
+
+fn validate(input: &Input) -> Outcome {
+    let decoded = input.decode()?;
+    let problems = decoded.validate();
+    if !problems.is_empty() {
+        return Err(Failure {
+            code: EXIT_INVALID,
+            message: format!("Invalid {} data: {}", decoded.format, problems.join("; ")),
+            details: json!({ "format": decoded.format, "problems": problems }),
+        });
+    }
+    Ok((
+        format!("Valid {} data\n", decoded.format),
+        json!({ "format": decoded.format, "valid": true }),
+    ))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let outcome = match &cli.command {
+        Command::Inspect { input } => inspect(input),
+        Command::Convert { input, to, strict } => convert_cmd(input, *to, *strict),
+        Command::Url { input, app } => url(input, *app),
+        Command::Validate { input } => validate(input),
+    };
+
+    match outcome {
+        Ok((text, json)) => {
+            if cli.json {
+                println!("{json}");
+            } else {
+                print!("{text}");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            if cli.json {
+                let mut out = json!({ "error": failure.message });
+                if let Value::Object(details) = failure.details {
+                    out.as_object_mut().unwrap().extend(details);
+                }
+                println!("{out}");
+            } else {
+                eprintln!("error: {}", failure.message);
+            }
+            ExitCode::from(failure.code)
+        }
+    }
+}
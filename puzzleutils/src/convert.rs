@@ -0,0 +1,177 @@
+//! Converting puzzles between formats
+//!
+//! f-puzzles JSON is the hub: everything is converted to it, and then from
+//! it to the requested format.  Anything which cannot be carried across is
+//! reported in [`Conversion::unmapped`] rather than silently dropped.
+
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::{
+    box_shape,
+    formats::{Decoded, Format},
+};
+
+mod ctc;
+
+/// The largest grid f-puzzles can represent
+const MAX_SIZE: usize = 16;
+
+#[derive(Debug)]
+pub struct Conversion {
+    pub value: Value,
+    /// Descriptions of anything which could not be converted
+    pub unmapped: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ConvertError {
+    BadGrid(String),
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::BadGrid(why) => write!(f, "Unable to convert grid: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+pub type ConvertResult<T> = Result<T, ConvertError>;
+
+/// Convert a decoded puzzle to the given format
+pub fn convert(decoded: &Decoded, to: Format) -> ConvertResult<Conversion> {
+    let mut unmapped = vec![];
+    let value = if decoded.format == to {
+        decoded.value.clone()
+    } else {
+        let fpuzzles = match decoded.format {
+            Format::FPuzzles => decoded.value.clone(),
+            Format::Ctc => ctc::ctc_to_fpuzzles(&decoded.value, &mut unmapped)?,
+            Format::Digits => digits_to_fpuzzles(decoded.value.as_str().unwrap_or_default())?,
+        };
+        match to {
+            Format::FPuzzles => fpuzzles,
+            Format::Ctc => ctc::fpuzzles_to_ctc(&fpuzzles, &mut unmapped)?,
+            Format::Digits => fpuzzles_to_digits(&fpuzzles, &mut unmapped)?,
+        }
+    };
+    Ok(Conversion { value, unmapped })
+}
+
+/// Keys of f-puzzles data which carry no puzzle content of their own
+const FPUZZLES_STRUCTURE: &[&str] = &["size", "grid"];
+
+/// Metadata keys shared by f-puzzles and CtC, the latter keeping them in
+/// cages as "key: value" text
+const METADATA: &[(&str, &str)] = &[
+    ("title", "title: "),
+    ("author", "author: "),
+    ("ruleset", "rules: "),
+];
+
+fn fpuzzles_size(value: &Value) -> ConvertResult<usize> {
+    value
+        .get("size")
+        .and_then(Value::as_u64)
+        .map(|s| s as usize)
+        .filter(|s| (1..=MAX_SIZE).contains(s))
+        .ok_or_else(|| ConvertError::BadGrid("missing or bad size".into()))
+}
+
+fn fpuzzles_cell(value: &Value, row: usize, col: usize) -> Option<&Value> {
+    value.get("grid")?.get(row)?.get(col)
+}
+
+fn fpuzzles_given(cell: Option<&Value>) -> Option<u64> {
+    let cell = cell?;
+    if cell.get("given").and_then(Value::as_bool) != Some(true) {
+        return None;
+    }
+    cell.get("value").and_then(Value::as_u64)
+}
+
+/// Report anything present in the f-puzzles data which was not handled
+fn report_unhandled(value: &Value, handled: &[&str], unmapped: &mut Vec<String>) {
+    for (key, entry) in value.as_object().into_iter().flatten() {
+        if FPUZZLES_STRUCTURE.contains(&key.as_str()) || handled.contains(&key.as_str()) {
+            continue;
+        }
+        match entry {
+            Value::Array(arr) if arr.is_empty() => {}
+            Value::Array(arr) => unmapped.push(format!("{} {key} constraint(s)", arr.len())),
+            Value::Bool(false) | Value::Null => {}
+            Value::Bool(true) => unmapped.push(format!("{key} constraint")),
+            _ => unmapped.push(format!("{key} setting")),
+        }
+    }
+}
+
+fn digits_to_fpuzzles(digits: &str) -> ConvertResult<Value> {
+    let size = (1..=9)
+        .find(|n| n * n == digits.len())
+        .ok_or_else(|| ConvertError::BadGrid("digits do not make a square grid".into()))?;
+    let digits: Vec<u32> = digits
+        .chars()
+        .map(|ch| ch.to_digit(10).unwrap_or(0))
+        .collect();
+    let grid: Vec<Vec<Value>> = digits
+        .chunks(size)
+        .map(|row| {
+            row.iter()
+                .map(|&d| {
+                    if d == 0 {
+                        json!({})
+                    } else {
+                        json!({"value": d, "given": true})
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Ok(json!({"size": size, "grid": grid}))
+}
+
+fn fpuzzles_to_digits(value: &Value, unmapped: &mut Vec<String>) -> ConvertResult<Value> {
+    let size = fpuzzles_size(value)?;
+    if size > 9 {
+        return Err(ConvertError::BadGrid(format!(
+            "{size}x{size} grids need more than one character per cell"
+        )));
+    }
+    let mut digits = String::new();
+    let mut irregular = false;
+    for row in 0..size {
+        for col in 0..size {
+            let cell = fpuzzles_cell(value, row, col);
+            irregular |= cell.and_then(|c| c.get("region")).is_some();
+            match fpuzzles_given(cell) {
+                Some(d) => digits.push_str(&d.to_string()),
+                None => digits.push('0'),
+            }
+        }
+    }
+    if irregular {
+        unmapped.push("irregular regions".into());
+    }
+    report_unhandled(value, &[], unmapped);
+    Ok(Value::String(digits))
+}
+
+/// Region index of every cell, in row-major order
+fn fpuzzles_regions(value: &Value, size: usize) -> Vec<usize> {
+    let (box_height, box_width) = box_shape(size);
+    (0..size * size)
+        .map(|idx| {
+            let (row, col) = (idx / size, idx % size);
+            fpuzzles_cell(value, row, col)
+                .and_then(|c| c.get("region"))
+                .and_then(Value::as_u64)
+                .map(|r| r as usize)
+                .unwrap_or((row / box_height) * box_height + col / box_width)
+        })
+        .collect()
+}
@@ -0,0 +1,1233 @@
+//! f-puzzles to CtC conversion, and back
+//!
+//! SudokuPad has no notion of constraints, only cages, lines, arrows,
+//! underlays and overlays.  Each f-puzzles constraint is drawn in a fixed
+//! style, and those styles are recognised again on the way back so that
+//! f-puzzles data survives a trip through CtC unchanged.  Other CtC drawings
+//! become f-puzzles cosmetics where they line up with the grid.
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    fpuzzles_cell, fpuzzles_given, fpuzzles_regions, fpuzzles_size, report_unhandled, ConvertError,
+    ConvertResult, MAX_SIZE, METADATA,
+};
+use crate::{box_shape, fpuzzles};
+
+/// Size of a cell in CtC drawing units, line widths are given in these
+const CELL_SIZE: f64 = 64.0;
+
+const BLACK: &str = "#000000";
+const WHITE: &str = "#FFFFFF";
+
+// The styles constraints are drawn in
+const THERMO_COLOUR: &str = "#CFCFCF";
+const THERMO_WIDTH: f64 = 21.0;
+const THERMO_BULB: f64 = 0.85;
+const PALINDROME_COLOUR: &str = "#C0C0C0";
+const PALINDROME_WIDTH: f64 = 15.0;
+const BETWEEN_COLOUR: &str = "#C0C0C0";
+const BETWEEN_WIDTH: f64 = 3.0;
+const BETWEEN_END: f64 = 0.8;
+const ARROW_COLOUR: &str = "#888888";
+const ARROW_WIDTH: f64 = 3.0;
+const ARROW_HEAD: f64 = 0.3;
+const ARROW_BULB_MARGIN: f64 = 0.15;
+const DIAGONAL_COLOUR: &str = "#34BBE6";
+const DIAGONAL_WIDTH: f64 = 3.0;
+const DOT_SIZE: f64 = 0.3;
+const QUADRUPLE_SIZE: f64 = 0.6;
+const PARITY_COLOUR: &str = "#CFCFCF";
+const PARITY_SIZE: f64 = 0.8;
+const EXTRA_REGION_COLOUR: &str = "#CFCFCF";
+
+/// Global f-puzzles constraints have nothing to draw, so they are carried
+/// in a metadata entry of their own
+const FLAGS: &[&str] = &["antiknight", "antiking", "nonconsecutive", "disjointgroups"];
+const FLAGS_PREFIX: &str = "fpuzzles: ";
+
+/// f-puzzles keys we convert, beyond the grid and metadata
+const HANDLED: &[&str] = &[
+    "solution",
+    "thermometer",
+    "palindrome",
+    "betweenline",
+    "line",
+    "arrow",
+    "killercage",
+    "extraregion",
+    "difference",
+    "ratio",
+    "xv",
+    "quadruple",
+    "odd",
+    "even",
+    "circle",
+    "rectangle",
+    "text",
+    "diagonal+",
+    "diagonal-",
+    "antiknight",
+    "antiking",
+    "nonconsecutive",
+    "disjointgroups",
+];
+
+/// Properties of f-puzzles cells we convert
+const CELL_KEYS: &[&str] = &["value", "given", "region", "c"];
+
+/// Properties of CtC underlays and overlays we understand.  Border sizes
+/// are purely cosmetic and f-puzzles has nowhere to put them.
+const SHAPE_KEYS: &[&str] = &[
+    "ct",
+    "w",
+    "h",
+    "r",
+    "c1",
+    "c2",
+    "fc",
+    "te",
+    "fs",
+    "borderSize",
+];
+
+type Cell = (usize, usize);
+
+/// Counts of things we could not convert, in the order first seen
+#[derive(Default)]
+struct Tally(Vec<(String, usize)>);
+
+impl Tally {
+    fn add(&mut self, what: impl Into<String>) {
+        let what = what.into();
+        match self.0.iter_mut().find(|(w, _)| *w == what) {
+            Some((_, n)) => *n += 1,
+            None => self.0.push((what, 1)),
+        }
+    }
+
+    fn report(self, unmapped: &mut Vec<String>) {
+        unmapped.extend(self.0.into_iter().map(|(what, n)| format!("{n} {what}")));
+    }
+}
+
+// Geometry helpers.  CtC points are [row, column] with cell (r, c)
+// spanning r..r+1 and c..c+1.
+
+fn num(x: f64) -> Value {
+    if x.fract() == 0.0 && x.abs() < 1e15 {
+        Value::from(x as i64)
+    } else {
+        json!(x)
+    }
+}
+
+fn point((r, c): (f64, f64)) -> Value {
+    json!([num(r), num(c)])
+}
+
+fn centre((r, c): Cell) -> (f64, f64) {
+    (r as f64 + 0.5, c as f64 + 0.5)
+}
+
+fn mean_centre(cells: &[Cell]) -> (f64, f64) {
+    let n = cells.len() as f64;
+    let (r, c) = cells
+        .iter()
+        .map(|&cell| centre(cell))
+        .fold((0.0, 0.0), |(ar, ac), (r, c)| (ar + r, ac + c));
+    (r / n, c / n)
+}
+
+fn approx(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-6
+}
+
+fn read_point(value: &Value) -> Option<(f64, f64)> {
+    let rc = value.as_array()?;
+    Some((rc.first()?.as_f64()?, rc.get(1)?.as_f64()?))
+}
+
+/// The cells whose centres average to the given point: one cell for a
+/// cell centre, two for the middle of an edge, four for a corner.
+fn cells_at((r, c): (f64, f64), size: usize) -> Option<Vec<Cell>> {
+    let axis = |x: f64| -> Option<Vec<f64>> {
+        if approx(x - x.floor(), 0.5) {
+            Some(vec![x.floor()])
+        } else if approx(x, x.round()) {
+            Some(vec![x.round() - 1.0, x.round()])
+        } else {
+            None
+        }
+    };
+    let in_grid = |x: f64| x >= 0.0 && (x as usize) < size;
+    let mut ret = vec![];
+    for row in axis(r)? {
+        for col in axis(c)? {
+            if !in_grid(row) || !in_grid(col) {
+                return None;
+            }
+            ret.push((row as usize, col as usize));
+        }
+    }
+    Some(ret)
+}
+
+fn centre_cell(p: (f64, f64), size: usize) -> Option<Cell> {
+    match cells_at(p, size)?.as_slice() {
+        [cell] => Some(*cell),
+        _ => None,
+    }
+}
+
+fn cell_name((r, c): Cell) -> Value {
+    Value::String(format!("R{}C{}", r + 1, c + 1))
+}
+
+fn cell_names(cells: &[Cell]) -> Value {
+    Value::Array(cells.iter().map(|&cell| cell_name(cell)).collect())
+}
+
+fn parse_cell(value: &Value, size: usize) -> Option<Cell> {
+    let upper = value.as_str()?.to_ascii_uppercase();
+    let (row, col) = upper.strip_prefix('R')?.split_once('C')?;
+    let (row, col): (usize, usize) = (row.parse().ok()?, col.parse().ok()?);
+    (1..=size)
+        .contains(&row)
+        .then_some(())
+        .filter(|_| (1..=size).contains(&col))
+        .map(|_| (row - 1, col - 1))
+}
+
+fn parse_cells(value: Option<&Value>, size: usize) -> Option<Vec<Cell>> {
+    let cells = value?
+        .as_array()?
+        .iter()
+        .map(|cell| parse_cell(cell, size))
+        .collect::<Option<Vec<_>>>()?;
+    (!cells.is_empty()).then_some(cells)
+}
+
+fn parse_lines(value: Option<&Value>, size: usize) -> Option<Vec<Vec<Cell>>> {
+    value?
+        .as_array()?
+        .iter()
+        .map(|line| parse_cells(Some(line), size))
+        .collect()
+}
+
+fn items<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn str_of<'a>(item: &'a Value, key: &str) -> Option<&'a str> {
+    item.get(key).and_then(Value::as_str)
+}
+
+fn f64_of(item: &Value, key: &str) -> Option<f64> {
+    item.get(key).and_then(Value::as_f64)
+}
+
+fn is_adjacent_pair(cells: &[Cell]) -> bool {
+    match cells {
+        [(r1, c1), (r2, c2)] => r1.abs_diff(*r2) + c1.abs_diff(*c2) == 1,
+        _ => false,
+    }
+}
+
+/// Do the cells sit exactly where `cells_at` will find them again?
+fn recoverable(cells: &[Cell], size: usize) -> bool {
+    let mut sorted = cells.to_vec();
+    sorted.sort_unstable();
+    cells_at(mean_centre(cells), size) == Some(sorted)
+}
+
+fn line_item(cells: &[Cell], colour: &str, width: f64) -> Value {
+    json!({
+        "wp": cells.iter().map(|&cell| point(centre(cell))).collect::<Vec<_>>(),
+        "c": colour,
+        "th": num(width),
+    })
+}
+
+fn circle_item(cell: Cell, size: f64, outline: &str, fill: &str) -> Value {
+    json!({
+        "ct": point(centre(cell)),
+        "w": num(size),
+        "h": num(size),
+        "r": 1,
+        "c1": outline,
+        "c2": fill,
+    })
+}
+
+#[derive(Default)]
+struct Drawing {
+    cages: Vec<Value>,
+    lines: Vec<Value>,
+    arrows: Vec<Value>,
+    underlays: Vec<Value>,
+    overlays: Vec<Value>,
+}
+
+pub(super) fn fpuzzles_to_ctc(value: &Value, unmapped: &mut Vec<String>) -> ConvertResult<Value> {
+    let size = fpuzzles_size(value)?;
+    let mut d = Drawing::default();
+    let mut tally = Tally::default();
+
+    // Cells, givens, and cell colours
+    let mut cells = vec![];
+    for row in 0..size {
+        let mut cells_row = vec![];
+        for col in 0..size {
+            let cell = fpuzzles_cell(value, row, col);
+            cells_row.push(match fpuzzles_given(cell) {
+                Some(d) => json!({ "v": d }),
+                None => json!({}),
+            });
+            let Some(cell) = cell.and_then(Value::as_object) else {
+                continue;
+            };
+            if let Some(colour) = cell.get("c").and_then(Value::as_str) {
+                d.underlays.push(json!({
+                    "ct": point(centre((row, col))),
+                    "w": 1,
+                    "h": 1,
+                    "c2": colour,
+                }));
+            }
+            if cell.contains_key("value") && cell.get("given") != Some(&Value::Bool(true)) {
+                tally.add("cell(s) with entered digits");
+            }
+            for key in cell.keys() {
+                if !CELL_KEYS.contains(&key.as_str()) {
+                    tally.add(format!("cell(s) with {key}"));
+                }
+            }
+        }
+        cells.push(cells_row);
+    }
+
+    let regions = fpuzzles_regions(value, size);
+    let regions: Vec<Vec<Value>> = (0..size)
+        .map(|region| {
+            regions
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| **r == region)
+                .map(|(idx, _)| json!([idx / size, idx % size]))
+                .collect()
+        })
+        .collect();
+
+    // Metadata
+    for (key, prefix) in METADATA {
+        if let Some(text) = value.get(*key).and_then(Value::as_str) {
+            d.cages.push(json!({ "v": format!("{prefix}{text}") }));
+        }
+    }
+    if let Some(solution) = fpuzzles::solution(value) {
+        let digits: String = solution.iter().map(|d| d.to_string()).collect();
+        d.cages.push(json!({ "v": format!("solution: {digits}") }));
+    }
+    let flags: Vec<&str> = FLAGS
+        .iter()
+        .copied()
+        .filter(|flag| value.get(*flag).and_then(Value::as_bool) == Some(true))
+        .collect();
+    if !flags.is_empty() {
+        d.cages
+            .push(json!({ "v": format!("{FLAGS_PREFIX}{}", flags.join(", ")) }));
+    }
+
+    // Lines
+    for item in items(value, "thermometer") {
+        match parse_lines(item.get("lines"), size).filter(|lines| !lines.is_empty()) {
+            Some(lines) => {
+                d.underlays.push(circle_item(
+                    lines[0][0],
+                    THERMO_BULB,
+                    THERMO_COLOUR,
+                    THERMO_COLOUR,
+                ));
+                for line in lines {
+                    d.lines.push(line_item(&line, THERMO_COLOUR, THERMO_WIDTH));
+                }
+            }
+            None => tally.add("thermometer(s) with unusable cells"),
+        }
+    }
+    for item in items(value, "palindrome") {
+        match parse_lines(item.get("lines"), size) {
+            Some(lines) => d.lines.extend(
+                lines
+                    .iter()
+                    .map(|line| line_item(line, PALINDROME_COLOUR, PALINDROME_WIDTH)),
+            ),
+            None => tally.add("palindrome(s) with unusable cells"),
+        }
+    }
+    for item in items(value, "betweenline") {
+        match parse_lines(item.get("lines"), size) {
+            Some(lines) => {
+                for line in lines {
+                    d.lines
+                        .push(line_item(&line, BETWEEN_COLOUR, BETWEEN_WIDTH));
+                    for end in [line[0], line[line.len() - 1]] {
+                        d.overlays
+                            .push(circle_item(end, BETWEEN_END, BETWEEN_COLOUR, WHITE));
+                    }
+                }
+            }
+            None => tally.add("between line(s) with unusable cells"),
+        }
+    }
+    for item in items(value, "line") {
+        let lines = parse_lines(item.get("lines"), size);
+        let colour = str_of(item, "outlineC");
+        let width = f64_of(item, "width");
+        match (lines, colour, width) {
+            (Some(lines), Some(colour), Some(width)) => d.lines.extend(
+                lines
+                    .iter()
+                    .map(|line| line_item(line, colour, width * CELL_SIZE)),
+            ),
+            _ => tally.add("line(s) with unusable cells or style"),
+        }
+    }
+    for (key, from, to) in [
+        ("diagonal-", (0.0, 0.0), (size as f64, size as f64)),
+        ("diagonal+", (size as f64, 0.0), (0.0, size as f64)),
+    ] {
+        if value.get(key).and_then(Value::as_bool) == Some(true) {
+            d.lines.push(json!({
+                "wp": [point(from), point(to)],
+                "c": DIAGONAL_COLOUR,
+                "th": num(DIAGONAL_WIDTH),
+            }));
+        }
+    }
+
+    // Arrows
+    for item in items(value, "arrow") {
+        let bulb = parse_cells(item.get("cells"), size);
+        let lines = parse_lines(item.get("lines"), size);
+        let (Some(mut bulb), Some(lines)) = (bulb, lines) else {
+            tally.add("arrow(s) with unusable cells");
+            continue;
+        };
+        // f-puzzles lists bulb cells in the order they were clicked
+        bulb.sort_unstable();
+        bulb.dedup();
+        let (r0, r1) = (
+            bulb.iter().map(|c| c.0).min().unwrap(),
+            bulb.iter().map(|c| c.0).max().unwrap(),
+        );
+        let (c0, c1) = (
+            bulb.iter().map(|c| c.1).min().unwrap(),
+            bulb.iter().map(|c| c.1).max().unwrap(),
+        );
+        let filled: Vec<Cell> = (r0..=r1)
+            .flat_map(|r| (c0..=c1).map(move |c| (r, c)))
+            .collect();
+        if filled != bulb {
+            tally.add("arrow(s) with bulbs which are not rectangles");
+            continue;
+        }
+        let (rows, cols) = ((r1 - r0 + 1) as f64, (c1 - c0 + 1) as f64);
+        d.overlays.push(json!({
+            "ct": point((r0 as f64 + rows / 2.0, c0 as f64 + cols / 2.0)),
+            "w": num(cols - ARROW_BULB_MARGIN),
+            "h": num(rows - ARROW_BULB_MARGIN),
+            "r": 1,
+            "c1": ARROW_COLOUR,
+            "c2": WHITE,
+        }));
+        for line in lines {
+            let mut arrow = line_item(&line, ARROW_COLOUR, ARROW_WIDTH);
+            arrow["hl"] = num(ARROW_HEAD);
+            d.arrows.push(arrow);
+        }
+    }
+
+    // Cages
+    let cage_cells = |cells: &[Cell]| -> Value {
+        Value::Array(cells.iter().map(|&(r, c)| json!([r, c])).collect())
+    };
+    for item in items(value, "killercage") {
+        match parse_cells(item.get("cells"), size) {
+            Some(cells) => {
+                let mut cage = json!({ "ce": cage_cells(&cells) });
+                if let Some(v) = item.get("value").filter(|v| !v.is_null()) {
+                    cage["v"] = v.clone();
+                }
+                d.cages.push(cage);
+            }
+            None => tally.add("killer cage(s) with unusable cells"),
+        }
+    }
+    for item in items(value, "extraregion") {
+        match parse_cells(item.get("cells"), size) {
+            Some(cells) => d.cages.push(json!({
+                "ce": cage_cells(&cells),
+                "c": EXTRA_REGION_COLOUR,
+                "unique": true,
+            })),
+            None => tally.add("extra region(s) with unusable cells"),
+        }
+    }
+
+    // Dots and other marks between cells
+    for (key, fill) in [("difference", WHITE), ("ratio", BLACK)] {
+        for item in items(value, key) {
+            match parse_cells(item.get("cells"), size).filter(|c| is_adjacent_pair(c)) {
+                Some(cells) => {
+                    let mut dot = json!({
+                        "ct": point(mean_centre(&cells)),
+                        "w": num(DOT_SIZE),
+                        "h": num(DOT_SIZE),
+                        "r": 1,
+                        "c1": BLACK,
+                        "c2": fill,
+                    });
+                    if let Some(v) = item.get("value").filter(|v| !v.is_null()) {
+                        dot["te"] = v.clone();
+                    }
+                    d.overlays.push(dot);
+                }
+                None => tally.add(format!("{key} dot(s) with unusable cells")),
+            }
+        }
+    }
+    for item in items(value, "xv") {
+        match (
+            parse_cells(item.get("cells"), size).filter(|c| is_adjacent_pair(c)),
+            item.get("value"),
+        ) {
+            (Some(cells), Some(v)) => d.overlays.push(json!({
+                "ct": point(mean_centre(&cells)),
+                "te": v,
+            })),
+            _ => tally.add("XV mark(s) with unusable cells"),
+        }
+    }
+    for item in items(value, "quadruple") {
+        let cells = parse_cells(item.get("cells"), size)
+            .filter(|cells| cells.len() == 4 && recoverable(cells, size));
+        let values = item.get("values").and_then(Value::as_array);
+        match (cells, values) {
+            (Some(cells), Some(values)) => {
+                let text: Vec<String> = values
+                    .iter()
+                    .map(|v| match v {
+                        Value::String(s) => s.clone(),
+                        v => v.to_string(),
+                    })
+                    .collect();
+                d.overlays.push(json!({
+                    "ct": point(mean_centre(&cells)),
+                    "w": num(QUADRUPLE_SIZE),
+                    "h": num(QUADRUPLE_SIZE),
+                    "r": 1,
+                    "c1": BLACK,
+                    "c2": WHITE,
+                    "te": text.join(" "),
+                }));
+            }
+            _ => tally.add("quadruple(s) with unusable cells"),
+        }
+    }
+
+    // Cell shading
+    for (key, round) in [("odd", true), ("even", false)] {
+        for item in items(value, key) {
+            match item.get("cell").and_then(|c| parse_cell(c, size)) {
+                Some(cell) => {
+                    let mut mark = json!({
+                        "ct": point(centre(cell)),
+                        "w": num(PARITY_SIZE),
+                        "h": num(PARITY_SIZE),
+                        "c2": PARITY_COLOUR,
+                    });
+                    if round {
+                        mark["r"] = 1.into();
+                    }
+                    d.underlays.push(mark);
+                }
+                None => tally.add(format!("{key} cell(s) with unusable cells")),
+            }
+        }
+    }
+
+    // Cosmetics
+    for (key, round) in [("circle", true), ("rectangle", false)] {
+        for item in items(value, key) {
+            let Some(cells) =
+                parse_cells(item.get("cells"), size).filter(|cells| recoverable(cells, size))
+            else {
+                tally.add(format!("{key}(s) over unusual cells"));
+                continue;
+            };
+            if f64_of(item, "angle").unwrap_or(0.0) != 0.0 {
+                tally.add(format!("{key} rotation(s)"));
+            }
+            let mut shape = Map::new();
+            shape.insert("ct".into(), point(mean_centre(&cells)));
+            for (from, to) in [
+                ("width", "w"),
+                ("height", "h"),
+                ("outlineC", "c1"),
+                ("baseC", "c2"),
+                ("fontC", "fc"),
+                ("value", "te"),
+            ] {
+                if let Some(v) = item.get(from) {
+                    shape.insert(to.into(), v.clone());
+                }
+            }
+            if round {
+                shape.insert("r".into(), 1.into());
+            }
+            d.overlays.push(Value::Object(shape));
+        }
+    }
+    for item in items(value, "text") {
+        let Some(cells) =
+            parse_cells(item.get("cells"), size).filter(|cells| recoverable(cells, size))
+        else {
+            tally.add("text(s) over unusual cells");
+            continue;
+        };
+        if f64_of(item, "angle").unwrap_or(0.0) != 0.0 {
+            tally.add("text rotation(s)");
+        }
+        let mut text = Map::new();
+        text.insert("ct".into(), point(mean_centre(&cells)));
+        for (from, to) in [("value", "te"), ("fontC", "fc"), ("size", "fs")] {
+            if let Some(v) = item.get(from) {
+                text.insert(to.into(), v.clone());
+            }
+        }
+        d.overlays.push(Value::Object(text));
+    }
+
+    let handled: Vec<&str> = METADATA
+        .iter()
+        .map(|(key, _)| *key)
+        .chain(HANDLED.iter().copied())
+        .collect();
+    report_unhandled(value, &handled, unmapped);
+    tally.report(unmapped);
+
+    let mut ret = Map::new();
+    ret.insert("id".into(), "fpuzzles".into());
+    ret.insert("cs".into(), num(CELL_SIZE));
+    ret.insert("ce".into(), json!(cells));
+    ret.insert("re".into(), json!(regions));
+    ret.insert("ca".into(), Value::Array(d.cages));
+    for (key, entries) in [
+        ("l", d.lines),
+        ("a", d.arrows),
+        ("u", d.underlays),
+        ("o", d.overlays),
+    ] {
+        if !entries.is_empty() {
+            ret.insert(key.into(), Value::Array(entries));
+        }
+    }
+    Ok(Value::Object(ret))
+}
+
+fn ctc_cell(value: &Value) -> Option<Cell> {
+    let (row, col) = read_point(value)?;
+    (row >= 0.0 && col >= 0.0).then_some((row as usize, col as usize))
+}
+
+/// The f-puzzles data being assembled from CtC
+struct FPuzzles {
+    size: usize,
+    grid: Vec<Vec<Map<String, Value>>>,
+    top: Map<String, Value>,
+    constraints: Vec<(&'static str, Vec<Value>)>,
+}
+
+impl FPuzzles {
+    fn push(&mut self, key: &'static str, item: Value) {
+        match self.constraints.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, items)) => items.push(item),
+            None => self.constraints.push((key, vec![item])),
+        }
+    }
+
+    fn items_mut(&mut self, key: &'static str) -> &mut Vec<Value> {
+        if !self.constraints.iter().any(|(k, _)| *k == key) {
+            self.constraints.push((key, vec![]));
+        }
+        &mut self
+            .constraints
+            .iter_mut()
+            .find(|(k, _)| *k == key)
+            .unwrap()
+            .1
+    }
+}
+
+fn style_is(item: &Value, key: &str, want: &str) -> bool {
+    str_of(item, key)
+        .map(|s| s.eq_ignore_ascii_case(want))
+        .unwrap_or(false)
+}
+
+fn size_is(item: &Value, want: f64) -> bool {
+    matches!((f64_of(item, "w"), f64_of(item, "h")), (Some(w), Some(h)) if approx(w, want) && approx(h, want))
+}
+
+fn is_round(item: &Value) -> bool {
+    f64_of(item, "r").map(|r| r > 0.0).unwrap_or(false)
+}
+
+fn has_only(item: &Value, keys: &[&str]) -> bool {
+    item.as_object()
+        .map(|m| m.keys().all(|k| keys.contains(&k.as_str())))
+        .unwrap_or(false)
+}
+
+/// Cells of a rectangular arrow bulb drawn by `fpuzzles_to_ctc`
+fn bulb_cells(item: &Value, size: usize) -> Option<Vec<Cell>> {
+    let (r, c) = read_point(item.get("ct")?)?;
+    let rows = (f64_of(item, "h")? + ARROW_BULB_MARGIN).round();
+    let cols = (f64_of(item, "w")? + ARROW_BULB_MARGIN).round();
+    let (r0, c0) = (r - rows / 2.0, c - cols / 2.0);
+    if rows < 1.0 || cols < 1.0 || !approx(r0, r0.round()) || !approx(c0, c0.round()) {
+        return None;
+    }
+    let (r0, c0) = (r0.round(), c0.round());
+    if r0 < 0.0 || c0 < 0.0 || r0 + rows > size as f64 || c0 + cols > size as f64 {
+        return None;
+    }
+    let (r0, c0, rows, cols) = (r0 as usize, c0 as usize, rows as usize, cols as usize);
+    Some(
+        (r0..r0 + rows)
+            .flat_map(|r| (c0..c0 + cols).map(move |c| (r, c)))
+            .collect(),
+    )
+}
+
+fn shape_to_cosmetic(item: &Value, cells: &[Cell]) -> (&'static str, Value) {
+    let mut ret = Map::new();
+    ret.insert("cells".into(), cell_names(cells));
+    // Text has no shape of its own
+    if item.get("w").is_none() && item.get("h").is_none() && item.get("te").is_some() {
+        for (from, to) in [("te", "value"), ("fc", "fontC"), ("fs", "size")] {
+            if let Some(v) = item.get(from) {
+                ret.insert(to.into(), v.clone());
+            }
+        }
+        return ("text", Value::Object(ret));
+    }
+    for (from, to, default) in [
+        ("c2", "baseC", json!("#FFFFFF00")),
+        ("c1", "outlineC", json!("#00000000")),
+        ("fc", "fontC", json!(BLACK)),
+        ("w", "width", json!(1)),
+        ("h", "height", json!(1)),
+        ("te", "value", json!("")),
+    ] {
+        ret.insert(to.into(), item.get(from).cloned().unwrap_or(default));
+    }
+    let key = if is_round(item) {
+        "circle"
+    } else {
+        "rectangle"
+    };
+    (key, Value::Object(ret))
+}
+
+pub(super) fn ctc_to_fpuzzles(value: &Value, unmapped: &mut Vec<String>) -> ConvertResult<Value> {
+    let rows = value
+        .get("ce")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ConvertError::BadGrid("no cells".into()))?;
+    let size = rows.len();
+    if !(1..=MAX_SIZE).contains(&size)
+        || rows
+            .iter()
+            .any(|row| row.as_array().map(Vec::len) != Some(size))
+    {
+        return Err(ConvertError::BadGrid(
+            "f-puzzles needs a square grid of at most 16x16".into(),
+        ));
+    }
+    let mut tally = Tally::default();
+
+    let grid: Vec<Vec<Map<String, Value>>> = rows
+        .iter()
+        .map(|row| {
+            row.as_array()
+                .into_iter()
+                .flatten()
+                .map(|cell| {
+                    let mut ret = Map::new();
+                    let given = cell.get("v").and_then(|v| {
+                        v.as_u64()
+                            .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+                    });
+                    if let Some(d) = given {
+                        ret.insert("value".into(), d.into());
+                        ret.insert("given".into(), true.into());
+                    }
+                    for key in cell.as_object().into_iter().flatten().map(|(k, _)| k) {
+                        if key != "v" {
+                            tally.add(format!("cell(s) with {key}"));
+                        }
+                    }
+                    ret
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut fp = FPuzzles {
+        size,
+        grid,
+        top: Map::new(),
+        constraints: vec![],
+    };
+    fp.top.insert("size".into(), size.into());
+
+    // Only record regions if they differ from the standard boxes
+    if let Some(regions) = value.get("re").and_then(Value::as_array) {
+        let (box_height, box_width) = box_shape(size);
+        let mut assigned = vec![None; size * size];
+        for (idx, region) in regions.iter().enumerate() {
+            for (row, col) in region.as_array().into_iter().flatten().filter_map(ctc_cell) {
+                if row < size && col < size {
+                    assigned[row * size + col] = Some(idx);
+                }
+            }
+        }
+        // Region numbering may differ, so compare which cells share regions
+        let standard = |idx: usize| {
+            let (row, col) = (idx / size, idx % size);
+            (row / box_height) * box_height + col / box_width
+        };
+        let same_shape = (0..size * size).all(|a| {
+            (0..size * size).all(|b| (assigned[a] == assigned[b]) == (standard(a) == standard(b)))
+        });
+        if !same_shape {
+            for (idx, region) in assigned.iter().enumerate() {
+                if let Some(region) = region {
+                    fp.grid[idx / size][idx % size].insert("region".into(), (*region).into());
+                }
+            }
+        }
+    }
+
+    convert_cages(value, &mut fp, &mut tally);
+    convert_lines(value, &mut fp, &mut tally);
+    convert_shapes(value, &mut fp, &mut tally);
+
+    for (key, entry) in value.as_object().into_iter().flatten() {
+        if ["id", "cs", "ce", "re", "ca", "l", "a", "u", "o"].contains(&key.as_str()) {
+            continue;
+        }
+        match entry {
+            Value::Array(arr) if arr.is_empty() => {}
+            Value::Array(arr) => unmapped.push(format!("{} {key} item(s)", arr.len())),
+            _ => unmapped.push(format!("{key} setting")),
+        }
+    }
+    tally.report(unmapped);
+
+    let FPuzzles {
+        grid,
+        mut top,
+        constraints,
+        ..
+    } = fp;
+    top.insert("grid".into(), json!(grid));
+    for (key, items) in constraints {
+        if !items.is_empty() {
+            top.insert(key.into(), Value::Array(items));
+        }
+    }
+    Ok(Value::Object(top))
+}
+
+fn convert_cages(value: &Value, fp: &mut FPuzzles, tally: &mut Tally) {
+    let size = fp.size;
+    for cage in items(value, "ca") {
+        let text = str_of(cage, "v");
+        let cells: Option<Vec<Cell>> = cage.get("ce").and_then(Value::as_array).map(|cells| {
+            cells
+                .iter()
+                .filter_map(ctc_cell)
+                .filter(|&(r, c)| r < size && c < size)
+                .collect()
+        });
+
+        if cells.as_ref().map(Vec::is_empty).unwrap_or(true) {
+            // Cages without cells carry metadata
+            let text = text.unwrap_or_default();
+            if let Some((key, rest)) = METADATA
+                .iter()
+                .find_map(|(key, prefix)| text.strip_prefix(prefix).map(|rest| (key, rest)))
+            {
+                fp.top.insert(key.to_string(), rest.into());
+            } else if let Some(digits) = text.strip_prefix("solution: ") {
+                let solution: Vec<Value> = digits
+                    .chars()
+                    .map(|ch| ch.to_digit(10).map(Value::from).unwrap_or(Value::Null))
+                    .collect();
+                fp.top.insert("solution".into(), Value::Array(solution));
+            } else if let Some(flags) = text.strip_prefix(FLAGS_PREFIX) {
+                for flag in flags.split(',').map(str::trim) {
+                    match FLAGS.iter().find(|f| **f == flag) {
+                        Some(flag) => {
+                            fp.top.insert(flag.to_string(), true.into());
+                        }
+                        None => tally.add(format!("unknown f-puzzles flag(s) {flag}")),
+                    }
+                }
+            } else if let Some((key, _)) = text.split_once(": ") {
+                tally.add(format!("{key} metadata"));
+            } else {
+                tally.add("cage(s) without cells");
+            }
+            continue;
+        }
+        let cells = cells.unwrap();
+
+        if cage.get("hidden").and_then(Value::as_bool) == Some(true) {
+            tally.add("hidden cage(s)");
+        } else if style_is(cage, "c", EXTRA_REGION_COLOUR)
+            && cage.get("unique").and_then(Value::as_bool) == Some(true)
+            && text.is_none()
+        {
+            fp.push("extraregion", json!({ "cells": cell_names(&cells) }));
+        } else {
+            let mut item = json!({ "cells": cell_names(&cells) });
+            if let Some(v) = cage.get("v") {
+                item["value"] = v.clone();
+            }
+            fp.push("killercage", item);
+        }
+    }
+}
+
+fn line_cells(item: &Value, size: usize) -> Option<Vec<Cell>> {
+    item.get("wp")?
+        .as_array()?
+        .iter()
+        .map(|p| read_point(p).and_then(|p| centre_cell(p, size)))
+        .collect::<Option<Vec<_>>>()
+        .filter(|cells| !cells.is_empty())
+}
+
+fn convert_lines(value: &Value, fp: &mut FPuzzles, tally: &mut Tally) {
+    let size = fp.size as f64;
+    for line in items(value, "l") {
+        let width = f64_of(line, "th");
+        let styled = |colour: &str, want: f64| {
+            style_is(line, "c", colour) && width.map(|w| approx(w, want)).unwrap_or(false)
+        };
+
+        if styled(DIAGONAL_COLOUR, DIAGONAL_WIDTH) {
+            let points: Vec<_> = items(line, "wp").filter_map(read_point).collect();
+            let is = |from: (f64, f64), to: (f64, f64)| {
+                points.len() == 2
+                    && approx(points[0].0, from.0)
+                    && approx(points[0].1, from.1)
+                    && approx(points[1].0, to.0)
+                    && approx(points[1].1, to.1)
+            };
+            if is((0.0, 0.0), (size, size)) {
+                fp.top.insert("diagonal-".into(), true.into());
+                continue;
+            }
+            if is((size, 0.0), (0.0, size)) {
+                fp.top.insert("diagonal+".into(), true.into());
+                continue;
+            }
+        }
+
+        let Some(cells) = line_cells(line, fp.size) else {
+            tally.add("line(s) not through cell centres");
+            continue;
+        };
+        let lines = json!([cell_names(&cells)]);
+
+        if styled(THERMO_COLOUR, THERMO_WIDTH) {
+            // Thermometers sharing a bulb are one constraint
+            let bulb = cell_name(cells[0]);
+            let thermos = fp.items_mut("thermometer");
+            match thermos.iter_mut().find(|t| t["lines"][0][0] == bulb) {
+                Some(thermo) => thermo["lines"]
+                    .as_array_mut()
+                    .unwrap()
+                    .push(cell_names(&cells)),
+                None => thermos.push(json!({ "lines": lines })),
+            }
+        } else if styled(PALINDROME_COLOUR, PALINDROME_WIDTH) {
+            fp.push("palindrome", json!({ "lines": lines }));
+        } else if styled(BETWEEN_COLOUR, BETWEEN_WIDTH) {
+            fp.push("betweenline", json!({ "lines": lines }));
+        } else {
+            let mut item = json!({
+                "lines": lines,
+                "outlineC": str_of(line, "c").unwrap_or(BLACK),
+            });
+            if let Some(width) = width {
+                item["width"] = json!(width / CELL_SIZE);
+            }
+            fp.push("line", item);
+        }
+    }
+}
+
+fn convert_shapes(value: &Value, fp: &mut FPuzzles, tally: &mut Tally) {
+    let size = fp.size;
+
+    // Arrow bulbs come first so that arrows can find them
+    let mut bulbs: Vec<(Vec<Cell>, Vec<Value>)> = vec![];
+    let mut shapes = vec![];
+    for shape in items(value, "o").chain(items(value, "u")) {
+        let cells = shape
+            .get("ct")
+            .and_then(read_point)
+            .and_then(|p| cells_at(p, size));
+        let Some(cells) = cells.filter(|_| has_only(shape, SHAPE_KEYS)) else {
+            tally.add("shape(s) not on the grid");
+            continue;
+        };
+        if is_round(shape) && style_is(shape, "c1", ARROW_COLOUR) && style_is(shape, "c2", WHITE) {
+            if let Some(bulb) = bulb_cells(shape, size) {
+                bulbs.push((bulb, vec![]));
+                continue;
+            }
+        }
+        shapes.push((shape, cells));
+    }
+
+    for arrow in items(value, "a") {
+        let Some(cells) = line_cells(arrow, size) else {
+            tally.add("arrow(s) not through cell centres");
+            continue;
+        };
+        match bulbs.iter_mut().find(|(bulb, _)| bulb.contains(&cells[0])) {
+            Some((_, lines)) => lines.push(cell_names(&cells)),
+            None => tally.add("arrow(s) without a bulb"),
+        }
+    }
+    for (bulb, lines) in bulbs {
+        fp.push(
+            "arrow",
+            json!({ "cells": cell_names(&bulb), "lines": lines }),
+        );
+    }
+
+    for (shape, cells) in shapes {
+        let round = is_round(shape);
+        let single = match cells.as_slice() {
+            [cell] => Some(*cell),
+            _ => None,
+        };
+        let text = shape.get("te");
+
+        if let (Some(cell), true) = (single, size_is(shape, 1.0)) {
+            if has_only(shape, &["ct", "w", "h", "c2"]) {
+                if let Some(colour) = shape.get("c2") {
+                    fp.grid[cell.0][cell.1].insert("c".into(), colour.clone());
+                    continue;
+                }
+            }
+        }
+        if single.is_some()
+            && round
+            && size_is(shape, THERMO_BULB)
+            && style_is(shape, "c1", THERMO_COLOUR)
+            && style_is(shape, "c2", THERMO_COLOUR)
+        {
+            // Thermometer bulbs are implied by their lines
+            continue;
+        }
+        if let (Some(cell), true) = (single, size_is(shape, PARITY_SIZE)) {
+            if style_is(shape, "c2", PARITY_COLOUR) && shape.get("c1").is_none() {
+                let key = if round { "odd" } else { "even" };
+                fp.push(key, json!({ "cell": cell_name(cell) }));
+                continue;
+            }
+        }
+        if single.is_some()
+            && round
+            && size_is(shape, BETWEEN_END)
+            && style_is(shape, "c1", BETWEEN_COLOUR)
+            && style_is(shape, "c2", WHITE)
+        {
+            // Between line ends are implied by their lines
+            continue;
+        }
+        if is_adjacent_pair(&cells)
+            && round
+            && size_is(shape, DOT_SIZE)
+            && style_is(shape, "c1", BLACK)
+        {
+            let key = if style_is(shape, "c2", WHITE) {
+                Some("difference")
+            } else if style_is(shape, "c2", BLACK) {
+                Some("ratio")
+            } else {
+                None
+            };
+            if let Some(key) = key {
+                let mut item = json!({ "cells": cell_names(&cells) });
+                if let Some(v) = text {
+                    item["value"] = v.clone();
+                }
+                fp.push(key, item);
+                continue;
+            }
+        }
+        if is_adjacent_pair(&cells) && has_only(shape, &["ct", "te"]) {
+            if let Some(v) = text.filter(|v| v.as_str() == Some("X") || v.as_str() == Some("V")) {
+                fp.push("xv", json!({ "cells": cell_names(&cells), "value": v }));
+                continue;
+            }
+        }
+        if cells.len() == 4
+            && round
+            && size_is(shape, QUADRUPLE_SIZE)
+            && style_is(shape, "c1", BLACK)
+            && style_is(shape, "c2", WHITE)
+        {
+            let values: Vec<Value> = text
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(|v| {
+                    v.parse::<u64>()
+                        .map(Value::from)
+                        .unwrap_or_else(|_| v.into())
+                })
+                .collect();
+            fp.push(
+                "quadruple",
+                json!({ "cells": cell_names(&cells), "values": values }),
+            );
+            continue;
+        }
+
+        let (key, item) = shape_to_cosmetic(shape, &cells);
+        fp.push(key, item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(size: usize, constraints: Value) -> Value {
+        let mut ret = json!({
+            "size": size,
+            "grid": vec![vec![json!({}); size]; size],
+        });
+        for (key, value) in constraints.as_object().unwrap() {
+            ret[key] = value.clone();
+        }
+        ret
+    }
+
+    fn round_trip(value: &Value) -> Value {
+        let mut unmapped = vec![];
+        let ctc = fpuzzles_to_ctc(value, &mut unmapped).unwrap();
+        assert!(unmapped.is_empty(), "f-puzzles to CtC: {unmapped:?}");
+        let ret = ctc_to_fpuzzles(&ctc, &mut unmapped).unwrap();
+        assert!(unmapped.is_empty(), "CtC to f-puzzles: {unmapped:?}");
+        ret
+    }
+
+    #[test]
+    fn constraints_round_trip() {
+        let cases = [
+            json!({ "title": "Tiny", "author": "Someone", "ruleset": "Normal rules" }),
+            json!({ "solution": (0..36).map(|n| n % 6 + 1).collect::<Vec<_>>() }),
+            json!({ "thermometer": [{ "lines": [["R1C1", "R2C2", "R3C3"], ["R1C1", "R1C2"]] }] }),
+            json!({ "palindrome": [{ "lines": [["R2C1", "R2C2", "R3C2"]] }] }),
+            json!({ "betweenline": [{ "lines": [["R4C1", "R4C2", "R4C3", "R4C4"]] }] }),
+            json!({ "line": [{ "lines": [["R5C1", "R5C2"]], "outlineC": "#FF0000", "width": 0.25 }] }),
+            json!({ "arrow": [{ "cells": ["R1C6"], "lines": [["R1C6", "R2C5", "R3C4"]] }] }),
+            json!({ "arrow": [{ "cells": ["R3C3", "R3C4"], "lines": [["R3C4", "R4C5", "R5C6"]] }] }),
+            json!({ "killercage": [{ "cells": ["R1C1", "R1C2"], "value": "7" }, { "cells": ["R6C6"] }] }),
+            json!({ "extraregion": [{ "cells": ["R1C1", "R2C2", "R3C3", "R4C4", "R5C5", "R6C6"] }] }),
+            json!({ "difference": [{ "cells": ["R1C1", "R1C2"] }] }),
+            json!({ "ratio": [{ "cells": ["R2C2", "R3C2"] }] }),
+            json!({ "xv": [{ "cells": ["R4C4", "R4C5"], "value": "X" }] }),
+            json!({ "quadruple": [{ "cells": ["R1C1", "R1C2", "R2C1", "R2C2"], "values": [1, 2] }] }),
+            json!({ "odd": [{ "cell": "R2C3" }], "even": [{ "cell": "R5C4" }] }),
+            json!({ "diagonal+": true, "diagonal-": true }),
+            json!({ "antiknight": true, "antiking": true, "nonconsecutive": true, "disjointgroups": true }),
+            json!({ "circle": [{
+                "cells": ["R3C3"],
+                "baseC": "#FFFFFF",
+                "outlineC": "#000000",
+                "fontC": "#000000",
+                "width": 0.5,
+                "height": 0.5,
+                "value": "",
+            }] }),
+            json!({ "rectangle": [{
+                "cells": ["R2C2", "R2C3"],
+                "baseC": "#FF00FF",
+                "outlineC": "#000000",
+                "fontC": "#000000",
+                "width": 1.5,
+                "height": 0.5,
+                "value": "A",
+            }] }),
+            json!({ "text": [{ "cells": ["R6C1"], "value": "Hello", "fontC": "#000000", "size": 0.5 }] }),
+        ];
+        for constraints in cases {
+            let value = puzzle(6, constraints.clone());
+            assert_eq!(
+                round_trip(&value),
+                value,
+                "{constraints} did not round-trip"
+            );
+        }
+    }
+
+    #[test]
+    fn arrow_bulb_order_does_not_matter() {
+        let value = puzzle(
+            6,
+            json!({ "arrow": [{
+                "cells": ["R4C3", "R3C4", "R3C3", "R4C4", "R3C3"],
+                "lines": [["R4C4", "R5C5", "R6C6"]],
+            }] }),
+        );
+        let back = round_trip(&value);
+        assert_eq!(
+            back["arrow"],
+            json!([{
+                "cells": ["R3C3", "R3C4", "R4C3", "R4C4"],
+                "lines": [["R4C4", "R5C5", "R6C6"]],
+            }])
+        );
+    }
+
+    #[test]
+    fn irregular_regions_round_trip() {
+        let mut value = puzzle(4, json!({}));
+        let regions = [0, 0, 0, 1, 0, 2, 1, 1, 2, 2, 3, 1, 2, 3, 3, 3];
+        for (idx, region) in regions.iter().enumerate() {
+            value["grid"][idx / 4][idx % 4]["region"] = json!(region);
+        }
+        value["grid"][0][0]["value"] = json!(1);
+        value["grid"][0][0]["given"] = json!(true);
+        assert_eq!(round_trip(&value), value);
+    }
+}
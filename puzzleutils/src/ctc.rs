@@ -11,6 +11,7 @@ mod parse;
 pub use encode::*;
 pub use parse::*;
 use serde_json::Value;
+use url::Url;
 
 use crate::GridMetadata;
 
@@ -36,16 +37,66 @@ fn find_kvsub(value: &Value, what: &str) -> Option<String> {
         })
 }
 
-pub fn metadata(value: &Value) -> GridMetadata {
+fn find_arg(url: &Url, key_to_find: &'static str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == key_to_find)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Acquire CtC data from a SudokuPad link or a bare CtC puzzle string
+pub fn extract<S: AsRef<str>>(input: S) -> ParseResult<Value> {
+    let input = input.as_ref().trim();
+    if let Ok(url) = Url::parse(input) {
+        if let Some(host) = url.host_str() {
+            let host = host.to_ascii_lowercase();
+            if host.ends_with("sudokupad.app") || host.ends_with("app.crackingthecryptic.com") {
+                if let Some(data) = find_arg(&url, "puzzleid")
+                    .and_then(|s| s.strip_prefix("ctc").map(String::from))
+                    .or_else(|| {
+                        url.query()
+                            .and_then(|s| s.strip_prefix("ctc").map(String::from))
+                    })
+                    .or_else(|| url.path().strip_prefix("/ctc").map(String::from))
+                {
+                    // As with f-puzzles links, plusses can get turned into spaces
+                    return parse_str(&data.replace(' ', "+"));
+                }
+            }
+        }
+    }
+    parse_str(input)
+}
+
+/// Extract the grid size and metadata from CtC data
+///
+/// Unlike f-puzzles, CtC data has no separate size, so if the cells are
+/// missing or empty then we cannot say anything about the grid at all.
+pub fn metadata(value: &Value) -> ParseResult<GridMetadata> {
     let cells = find_entry(value, "ce")
         .and_then(Value::as_array)
-        .and_then(|rows| rows[0].as_array().map(|cols| (rows.len(), cols.len())));
+        .and_then(|rows| {
+            rows.first()
+                .and_then(Value::as_array)
+                .map(|cols| (rows.len(), cols.len()))
+        })
+        .filter(|&(_, cols)| cols > 0)
+        .ok_or(ParseError::NoGrid)?;
 
-    GridMetadata {
+    Ok(GridMetadata {
         title: find_kvsub(value, "title: "),
         author: find_kvsub(value, "author: "),
         rules: find_kvsub(value, "rules: "),
-        rows_cols: cells,
+        rows_cols: Some(cells),
         has_solution: find_kvsub(value, "solution: ").is_some(),
-    }
+    })
+}
+
+/// Extract the embedded solution, if there is one, as a flat row-major
+/// list of digits.  Cells without a known digit are given as zero.
+pub fn solution(value: &Value) -> Option<Vec<u8>> {
+    find_kvsub(value, "solution: ").map(|s| {
+        s.chars()
+            .map(|ch| ch.to_digit(10).map(|d| d as u8).unwrap_or(0))
+            .collect()
+    })
 }
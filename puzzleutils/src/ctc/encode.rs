@@ -1,19 +1,27 @@
 //! encoding CtC puzzles
 //!
+//! This is the inverse of [`parse`][super::parse], such that
+//! `parse(&encode(value))` gives back `value` for any JSON value.
 
 use serde_json::{Map, Value};
 
+/// Encode and compress a value as a CtC puzzle string
 pub fn encode_str(puzzle: &Value) -> String {
+    lz_str::compress_to_base64(encode(puzzle).as_str())
+}
+
+/// Encode a value in the (uncompressed) CtC encoding
+pub fn encode(value: &Value) -> String {
     let mut enc = String::new();
-    encode_value(&mut enc, puzzle);
-    lz_str::compress_to_base64(enc.as_str())
+    encode_value(&mut enc, value);
+    enc
 }
 
 fn encode_value(enc: &mut String, value: &Value) {
     match value {
-        Value::Null => {}
+        Value::Null => enc.push_str("null"),
         Value::Bool(b) => enc.push(if *b { 't' } else { 'f' }),
-        Value::Number(n) => encode_rawstring(enc, &format!("{}", n)),
+        Value::Number(n) => enc.push_str(&n.to_string()),
         Value::String(s) => encode_string(enc, s),
         Value::Array(arr) => encode_array(enc, arr),
         Value::Object(m) => encode_map(enc, m),
@@ -24,22 +32,30 @@ fn encode_string(enc: &mut String, s: &str) {
     match s {
         "#000000" => enc.push_str("#0"),
         "#FFFFFF" => enc.push_str("#F"),
-        _ => {
-            enc.push('\'');
-            encode_rawstring(enc, s);
-            enc.push('\'');
-        }
+        _ => encode_quoted(enc, s),
     }
 }
 
-fn encode_rawstring(enc: &mut String, s: &str) {
+fn encode_quoted(enc: &mut String, s: &str) {
+    enc.push('\'');
     for ch in s.chars() {
         match ch {
             '\'' => enc.push_str("\\'"),
+            '\\' => enc.push_str("\\\\"),
             '\n' => enc.push_str("\\n"),
+            '\r' => enc.push_str("\\r"),
+            '\t' => enc.push_str("\\t"),
             _ => enc.push(ch),
         }
     }
+    enc.push('\'');
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
 }
 
 fn encode_map(enc: &mut String, map: &Map<String, Value>) {
@@ -48,7 +64,11 @@ fn encode_map(enc: &mut String, map: &Map<String, Value>) {
         if i != 0 {
             enc.push(',');
         }
-        enc.push_str(k);
+        if is_bare_key(k) {
+            enc.push_str(k);
+        } else {
+            encode_quoted(enc, k);
+        }
         enc.push(':');
         encode_value(enc, v);
     }
@@ -56,12 +76,14 @@ fn encode_map(enc: &mut String, map: &Map<String, Value>) {
 }
 
 fn encode_array(enc: &mut String, arr: &[Value]) {
+    // An array of a single empty object would otherwise look like an empty array
+    let elide_empty = arr.len() > 1;
     enc.push('[');
     for (i, val) in arr.iter().enumerate() {
         if i != 0 {
             enc.push(',')
         }
-        if val.as_object().map(|m| m.is_empty()) != Some(true) {
+        if !(elide_empty && val.as_object().map(Map::is_empty).unwrap_or(false)) {
             encode_value(enc, val);
         }
     }
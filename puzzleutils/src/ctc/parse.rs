@@ -1,164 +1,327 @@
-use std::{iter::Peekable, str::Chars, string::FromUtf16Error};
+//! parsing CtC puzzles
+//!
+//! The CtC encoding is JSON with a few space saving tweaks:
+//!
+//! * Strings are quoted with `'` rather than `"`
+//! * Object keys made only of `[A-Za-z0-9_]` are left unquoted
+//! * `true` and `false` are written as `t` and `f`
+//! * `"#000000"` and `"#FFFFFF"` are written as `#0` and `#F`
+//! * Empty objects inside arrays are left out, so `[{},{}]` is `[,]`
+//!
+//! Positions in errors are character offsets into the decompressed text.
 
-use serde_json::{Map, Value};
+use std::{fmt, string::FromUtf16Error};
+
+use serde_json::{Map, Number, Value};
 
 #[derive(Debug)]
 pub enum ParseError {
     LZStrEncodingError,
     BadUnicode(FromUtf16Error),
-    UnexpectEndOfInput,
-    UnexpectedCharacter(char),
-    JsonError(serde_json::Error),
+    UnexpectedEndOfInput { position: usize },
+    UnexpectedCharacter { ch: char, position: usize },
+    BadNumber { text: String, position: usize },
+    BadEscape { ch: char, position: usize },
+    TrailingInput { position: usize },
+    NoGrid,
+}
+
+impl ParseError {
+    /// Where in the decompressed text the problem was found, if the
+    /// problem was with the text rather than the compression
+    pub fn position(&self) -> Option<usize> {
+        use ParseError::*;
+        match self {
+            LZStrEncodingError | BadUnicode(_) | NoGrid => None,
+            UnexpectedEndOfInput { position }
+            | UnexpectedCharacter { position, .. }
+            | BadNumber { position, .. }
+            | BadEscape { position, .. }
+            | TrailingInput { position } => Some(*position),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ParseError::*;
+        match self {
+            LZStrEncodingError => write!(f, "Unable to decompress puzzle data"),
+            BadUnicode(e) => write!(f, "Decompressed puzzle data is not valid: {e}"),
+            UnexpectedEndOfInput { position } => {
+                write!(f, "Unexpected end of input at {position}")
+            }
+            UnexpectedCharacter { ch, position } => {
+                write!(f, "Unexpected character {ch:?} at {position}")
+            }
+            BadNumber { text, position } => write!(f, "Bad number {text:?} at {position}"),
+            BadEscape { ch, position } => write!(f, "Bad escape \\{ch} at {position}"),
+            TrailingInput { position } => write!(f, "Unexpected data after puzzle at {position}"),
+            NoGrid => write!(f, "Puzzle data has no grid of cells"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::BadUnicode(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 pub type ParseResult<T> = std::result::Result<T, ParseError>;
 
+/// Decompress and parse a CtC puzzle string
 pub fn parse_str(input: &str) -> ParseResult<Value> {
     let decoded = lz_str::decompress_from_base64(input).ok_or(ParseError::LZStrEncodingError)?;
     let decoded = String::from_utf16(&decoded).map_err(ParseError::BadUnicode)?;
-    consume_object(&mut decoded.chars().peekable())
+    parse(&decoded)
 }
 
-fn assert_next(seq: &mut Peekable<Chars>, ch: char) -> ParseResult<()> {
-    match seq.next() {
-        None => Err(ParseError::UnexpectEndOfInput),
-        Some(n) => {
-            if n == ch {
-                Ok(())
-            } else {
-                Err(ParseError::UnexpectedCharacter(n))
-            }
-        }
+/// Parse the (already decompressed) CtC encoding of a value
+pub fn parse(input: &str) -> ParseResult<Value> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    let value = parser.acquire_value()?;
+    if parser.pos != parser.chars.len() {
+        return Err(ParseError::TrailingInput {
+            position: parser.pos,
+        });
     }
+    Ok(value)
 }
 
-fn acquire_value(seq: &mut Peekable<Chars>) -> ParseResult<Value> {
-    match seq.peek() {
-        None => Err(ParseError::UnexpectEndOfInput),
-        Some('{') => consume_object(seq),
-        Some('[') => consume_array(seq),
-        Some('\'') => consume_string(seq),
-        Some('#') => consume_hexcode(seq),
-        Some('t') => {
-            seq.next();
-            Ok(Value::Bool(true))
-        }
-        Some('f') => {
-            seq.next();
-            Ok(Value::Bool(false))
-        }
-        Some(n) if "0123456789".contains(*n) => consume_number(seq),
-        Some(ch) => Err(ParseError::UnexpectedCharacter(*ch)),
-    }
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
 }
 
-fn acquire_key(seq: &mut Peekable<Chars>) -> ParseResult<String> {
-    let mut ret = String::new();
-    loop {
-        match seq.next() {
-            None => return Err(ParseError::UnexpectEndOfInput),
-            Some(':') => break,
-            Some(ch) => ret.push(ch),
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> ParseResult<char> {
+        let ch = self
+            .peek()
+            .ok_or(ParseError::UnexpectedEndOfInput { position: self.pos })?;
+        self.pos += 1;
+        Ok(ch)
+    }
+
+    fn unexpected(&self, ch: char) -> ParseError {
+        // Only ever called just after consuming `ch`
+        ParseError::UnexpectedCharacter {
+            ch,
+            position: self.pos - 1,
         }
     }
-    Ok(ret)
-}
 
-fn consume_object(seq: &mut Peekable<Chars>) -> ParseResult<Value> {
-    assert_next(seq, '{')?;
-    let mut ret = serde_json::Map::new();
-
-    loop {
-        match seq.peek() {
-            None => return Err(ParseError::UnexpectEndOfInput),
-            Some('}') => break,
-            _ => {
-                // key:Value
-                let key = acquire_key(seq)?;
-                let value = acquire_value(seq)?;
-                if matches!(seq.peek(), Some(',')) {
-                    seq.next();
-                }
-                ret.insert(key, value);
-            }
+    fn assert_next(&mut self, want: char) -> ParseResult<()> {
+        match self.next()? {
+            ch if ch == want => Ok(()),
+            ch => Err(self.unexpected(ch)),
         }
     }
-    assert_next(seq, '}')?;
 
-    Ok(Value::Object(ret))
-}
+    fn assert_word(&mut self, word: &str) -> ParseResult<()> {
+        word.chars().try_for_each(|ch| self.assert_next(ch))
+    }
 
-fn consume_array(seq: &mut Peekable<Chars>) -> ParseResult<Value> {
-    assert_next(seq, '[')?;
-    let mut ret = vec![];
-    loop {
-        match seq.peek() {
-            None => return Err(ParseError::UnexpectEndOfInput),
-            Some(']') => break,
-            Some(',') => {
-                seq.next();
-                ret.push(Value::Object(Map::new()));
-                if matches!(seq.peek(), Some(']')) {
-                    ret.push(Value::Object(Map::new()));
+    fn acquire_value(&mut self) -> ParseResult<Value> {
+        match self.peek() {
+            None => Err(ParseError::UnexpectedEndOfInput { position: self.pos }),
+            Some('{') => self.consume_object(),
+            Some('[') => self.consume_array(),
+            Some(q @ ('\'' | '"')) => self.consume_string(q).map(Value::String),
+            Some('#') => self.consume_hexcode(),
+            Some('t') => {
+                self.pos += 1;
+                if self.chars[self.pos..].starts_with(&['r', 'u', 'e']) {
+                    self.pos += 3;
                 }
+                Ok(Value::Bool(true))
             }
-            _ => {
-                ret.push(acquire_value(seq)?);
-                if matches!(seq.peek(), Some(',')) {
-                    seq.next();
+            Some('f') => {
+                self.pos += 1;
+                if self.chars[self.pos..].starts_with(&['a', 'l', 's', 'e']) {
+                    self.pos += 4;
                 }
+                Ok(Value::Bool(false))
+            }
+            Some('n') => {
+                self.assert_word("null")?;
+                Ok(Value::Null)
+            }
+            Some(ch) if ch == '-' || ch.is_ascii_digit() => self.consume_number(),
+            Some(ch) => {
+                self.pos += 1;
+                Err(self.unexpected(ch))
             }
         }
     }
-    assert_next(seq, ']')?;
-    Ok(Value::Array(ret))
-}
 
-fn consume_string(seq: &mut Peekable<Chars>) -> ParseResult<Value> {
-    assert_next(seq, '\'')?;
-    let mut ret = String::new();
-    let mut escaped = false;
-    loop {
-        let ch = seq.next().ok_or(ParseError::UnexpectEndOfInput)?;
-        if escaped {
-            match ch {
-                'n' => ret.push('\n'),
-                'r' => ret.push('\r'),
-                _ => ret.push(ch),
+    fn acquire_key(&mut self) -> ParseResult<String> {
+        if let Some(q @ ('\'' | '"')) = self.peek() {
+            let key = self.consume_string(q)?;
+            self.assert_next(':')?;
+            return Ok(key);
+        }
+        let mut ret = String::new();
+        loop {
+            match self.next()? {
+                ':' => break,
+                ch => ret.push(ch),
             }
-            escaped = false;
-        } else {
-            match ch {
-                '\'' => break,
-                '\\' => escaped = true,
-                _ => ret.push(ch),
+        }
+        Ok(ret)
+    }
+
+    fn consume_object(&mut self) -> ParseResult<Value> {
+        self.assert_next('{')?;
+        let mut ret = Map::new();
+
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Object(ret));
+        }
+
+        loop {
+            let key = self.acquire_key()?;
+            let value = self.acquire_value()?;
+            ret.insert(key, value);
+            match self.next()? {
+                ',' => {}
+                '}' => break,
+                ch => return Err(self.unexpected(ch)),
             }
         }
+
+        Ok(Value::Object(ret))
     }
-    Ok(Value::String(ret))
-}
 
-fn consume_hexcode(seq: &mut Peekable<Chars>) -> ParseResult<Value> {
-    assert_next(seq, '#')?;
-    match seq.next() {
-        None => Err(ParseError::UnexpectEndOfInput),
-        Some('0') => Ok(Value::String("#000000".into())),
-        Some('F') => Ok(Value::String("#FFFFFF".into())),
-        Some(ch) => Err(ParseError::UnexpectedCharacter(ch)),
+    fn consume_array(&mut self) -> ParseResult<Value> {
+        self.assert_next('[')?;
+        let mut ret = vec![];
+
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Value::Array(ret));
+        }
+
+        loop {
+            // An empty slot is an empty object
+            match self.peek() {
+                Some(',' | ']') => ret.push(Value::Object(Map::new())),
+                _ => ret.push(self.acquire_value()?),
+            }
+            match self.next()? {
+                ',' => {}
+                ']' => break,
+                ch => return Err(self.unexpected(ch)),
+            }
+        }
+
+        Ok(Value::Array(ret))
     }
-}
 
-fn consume_number(seq: &mut Peekable<Chars>) -> ParseResult<Value> {
-    let mut s = String::new();
-    loop {
-        match seq.peek().cloned() {
-            None => return Err(ParseError::UnexpectEndOfInput),
-            Some(c) if "0123456789.".contains(c) => {
-                seq.next();
-                s.push(c);
+    fn consume_string(&mut self, quote: char) -> ParseResult<String> {
+        self.assert_next(quote)?;
+        let mut ret = String::new();
+        loop {
+            match self.next()? {
+                ch if ch == quote => break,
+                '\\' => match self.next()? {
+                    'n' => ret.push('\n'),
+                    'r' => ret.push('\r'),
+                    't' => ret.push('\t'),
+                    'u' => ret.push(self.consume_unicode_escape()?),
+                    ch @ ('\\' | '\'' | '"' | '/') => ret.push(ch),
+                    ch => {
+                        return Err(ParseError::BadEscape {
+                            ch,
+                            position: self.pos - 2,
+                        })
+                    }
+                },
+                ch => ret.push(ch),
             }
-            _ => break,
         }
+        Ok(ret)
+    }
+
+    fn consume_hex4(&mut self) -> ParseResult<u32> {
+        let start = self.pos;
+        let mut ret = 0;
+        for _ in 0..4 {
+            let ch = self.next()?;
+            let digit = ch.to_digit(16).ok_or(ParseError::BadEscape {
+                ch: 'u',
+                position: start - 2,
+            })?;
+            ret = ret * 16 + digit;
+        }
+        Ok(ret)
+    }
+
+    fn consume_unicode_escape(&mut self) -> ParseResult<char> {
+        let start = self.pos - 2;
+        let bad = ParseError::BadEscape {
+            ch: 'u',
+            position: start,
+        };
+        let first = self.consume_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&first) {
+            // Surrogate pair, the low half must follow as another escape
+            self.assert_word("\\u")?;
+            let second = self.consume_hex4()?;
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err(bad);
+            }
+            0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)
+        } else {
+            first
+        };
+        char::from_u32(code).ok_or(bad)
+    }
+
+    fn consume_hexcode(&mut self) -> ParseResult<Value> {
+        self.assert_next('#')?;
+        match self.next()? {
+            '0' => Ok(Value::String("#000000".into())),
+            'F' => Ok(Value::String("#FFFFFF".into())),
+            ch => Err(self.unexpected(ch)),
+        }
+    }
+
+    fn consume_number(&mut self) -> ParseResult<Value> {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            if ch.is_ascii_digit() || "+-.eE".contains(ch) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        let bad = || ParseError::BadNumber {
+            text: text.clone(),
+            position: start,
+        };
+        let number = if text.contains(['.', 'e', 'E']) {
+            // Parse floats ourselves, the standard library rounds correctly
+            // which keeps them stable across an encode/parse cycle
+            text.parse::<f64>()
+                .ok()
+                .filter(|_| text.parse::<Number>().is_ok())
+                .and_then(Number::from_f64)
+        } else {
+            text.parse::<Number>().ok()
+        };
+        number.map(Value::Number).ok_or_else(bad)
     }
-    Ok(Value::Number(s.parse().map_err(ParseError::JsonError)?))
 }
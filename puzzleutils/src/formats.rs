@@ -0,0 +1,327 @@
+//! Puzzle encodings we understand
+//!
+//! Puzzles reach us as f-puzzles links or data, SudokuPad (CtC) links or
+//! data, or as plain strings of digits.  This module works out which we
+//! have been given and decodes it to JSON we can work with.
+
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{ctc, fpuzzles, GridMetadata};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// f-puzzles JSON, lz-string compressed
+    FPuzzles,
+    /// SudokuPad's compacted JSON, lz-string compressed
+    Ctc,
+    /// One character per cell, `0` or `.` for empty cells (eg. 81 chars for 9x9)
+    Digits,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::FPuzzles, Format::Ctc, Format::Digits];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::FPuzzles => "fpuzzles",
+            Format::Ctc => "ctc",
+            Format::Digits => "digits",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fpuzzles" | "f-puzzles" => Ok(Format::FPuzzles),
+            "ctc" | "sudokupad" => Ok(Format::Ctc),
+            "digits" | "81" => Ok(Format::Digits),
+            _ => Err(format!(
+                "Unknown format {s:?}, expected one of fpuzzles, ctc, digits"
+            )),
+        }
+    }
+}
+
+/// Apps we can build links for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum App {
+    FPuzzles,
+    Sudokupad,
+    SudokupadBeta,
+}
+
+impl FromStr for App {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fpuzzles" | "f-puzzles" => Ok(App::FPuzzles),
+            "sudokupad" => Ok(App::Sudokupad),
+            "beta" | "sudokupad-beta" => Ok(App::SudokupadBeta),
+            _ => Err(format!(
+                "Unknown app {s:?}, expected one of fpuzzles, sudokupad, beta"
+            )),
+        }
+    }
+}
+
+/// Build a link which opens the given f-puzzles data in the given app
+pub fn app_url(app: App, fpuzzles: &Value) -> String {
+    let data = fpuzzles::encode(fpuzzles);
+    match app {
+        App::FPuzzles => format!("https://f-puzzles.com/?load={data}"),
+        App::Sudokupad => format!("https://sudokupad.app/fpuzzles{data}"),
+        App::SudokupadBeta => format!("https://beta.sudokupad.app/fpuzzles{data}"),
+    }
+}
+
+/// A decoded puzzle
+///
+/// For f-puzzles and CtC the value is the puzzle's JSON, for digits it is
+/// the normalised string of digits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub format: Format,
+    pub value: Value,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The input was not in the requested format
+    NotFormat(Format),
+    /// The input was not in any format we know
+    Unrecognised,
+    /// The input looked like CtC data but could not be parsed
+    Ctc(ctc::ParseError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NotFormat(format) => write!(f, "Input is not valid {format} data"),
+            DecodeError::Unrecognised => write!(f, "Input is not in any recognised format"),
+            DecodeError::Ctc(e) => write!(f, "Input is not valid ctc data: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn grid_size(cells: usize) -> Option<usize> {
+    (1..=9).find(|n| n * n == cells)
+}
+
+fn decode_digits(input: &str) -> Option<String> {
+    let digits: String = input.chars().filter(|ch| !ch.is_whitespace()).collect();
+    let size = grid_size(digits.chars().count())?;
+    digits
+        .chars()
+        .map(|ch| match ch {
+            '.' => Some('0'),
+            ch if ch.to_digit(10).map(|d| d as usize <= size) == Some(true) => Some(ch),
+            _ => None,
+        })
+        .collect()
+}
+
+fn decode_as(input: &str, format: Format) -> Result<Value, DecodeError> {
+    match format {
+        Format::FPuzzles => fpuzzles::extract(input)
+            .filter(Value::is_object)
+            .ok_or(DecodeError::NotFormat(format)),
+        Format::Ctc => ctc::extract(input).map_err(DecodeError::Ctc).and_then(|v| {
+            v.is_object()
+                .then_some(v)
+                .ok_or(DecodeError::NotFormat(format))
+        }),
+        Format::Digits => decode_digits(input)
+            .map(Value::String)
+            .ok_or(DecodeError::NotFormat(format)),
+    }
+}
+
+/// Decode the input, which may be a link or raw data
+///
+/// If `format` is given then only that format is tried, otherwise each of
+/// the formats we know is tried in turn.
+pub fn decode(input: &str, format: Option<Format>) -> Result<Decoded, DecodeError> {
+    let input = input.trim();
+    if let Some(format) = format {
+        return decode_as(input, format).map(|value| Decoded { format, value });
+    }
+    [Format::Digits, Format::FPuzzles, Format::Ctc]
+        .into_iter()
+        .find_map(|format| {
+            decode_as(input, format)
+                .ok()
+                .map(|value| Decoded { format, value })
+        })
+        .ok_or(DecodeError::Unrecognised)
+}
+
+/// Encode a value of the given format back to its string form
+pub fn encode(format: Format, value: &Value) -> String {
+    match format {
+        Format::FPuzzles => fpuzzles::encode(value),
+        Format::Ctc => ctc::encode_str(value),
+        Format::Digits => value.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+impl Decoded {
+    pub fn metadata(&self) -> Result<GridMetadata, DecodeError> {
+        Ok(match self.format {
+            Format::FPuzzles => fpuzzles::metadata(&self.value),
+            Format::Ctc => ctc::metadata(&self.value).map_err(DecodeError::Ctc)?,
+            Format::Digits => GridMetadata {
+                title: None,
+                author: None,
+                rules: None,
+                rows_cols: self
+                    .value
+                    .as_str()
+                    .and_then(|s| grid_size(s.len()))
+                    .map(|n| (n, n)),
+                has_solution: false,
+            },
+        })
+    }
+
+    /// Check the decoded data is well formed, returning a list of problems
+    pub fn validate(&self) -> Vec<String> {
+        match self.format {
+            Format::FPuzzles => validate_fpuzzles(&self.value),
+            Format::Ctc => validate_ctc(&self.value),
+            // Digits are checked fully during decoding
+            Format::Digits => vec![],
+        }
+    }
+}
+
+fn validate_fpuzzles(value: &Value) -> Vec<String> {
+    let mut problems = vec![];
+    let size = match value.get("size").and_then(Value::as_u64) {
+        Some(size) if (1..=16).contains(&size) => size as usize,
+        Some(size) => {
+            problems.push(format!("Grid size {size} is out of range"));
+            return problems;
+        }
+        None => {
+            problems.push("Missing grid size".into());
+            return problems;
+        }
+    };
+    match value.get("grid").and_then(Value::as_array) {
+        None => problems.push("Missing grid".into()),
+        Some(rows) => {
+            if rows.len() != size {
+                problems.push(format!("Grid has {} rows, expected {size}", rows.len()));
+            }
+            for (r, row) in rows.iter().enumerate() {
+                let Some(cells) = row.as_array() else {
+                    problems.push(format!("Row {} is not a list of cells", r + 1));
+                    continue;
+                };
+                if cells.len() != size {
+                    problems.push(format!(
+                        "Row {} has {} cells, expected {size}",
+                        r + 1,
+                        cells.len()
+                    ));
+                }
+                for (c, cell) in cells.iter().enumerate() {
+                    if let Some(v) = cell.get("value") {
+                        if !v
+                            .as_u64()
+                            .map(|d| (1..=size as u64).contains(&d))
+                            .unwrap_or(false)
+                        {
+                            problems.push(format!("R{}C{} has bad value {v}", r + 1, c + 1));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if let Some(solution) = fpuzzles::solution(value) {
+        if solution.len() != size * size {
+            problems.push(format!(
+                "Solution has {} cells, expected {}",
+                solution.len(),
+                size * size
+            ));
+        }
+    }
+    problems
+}
+
+fn validate_ctc(value: &Value) -> Vec<String> {
+    let mut problems = vec![];
+    let Some(rows) = value.get("ce").and_then(Value::as_array) else {
+        problems.push("Missing cells".into());
+        return problems;
+    };
+    let width = rows
+        .first()
+        .and_then(Value::as_array)
+        .map(Vec::len)
+        .unwrap_or(0);
+    if rows.is_empty() || width == 0 {
+        problems.push("Grid has no cells".into());
+        return problems;
+    }
+    for (r, row) in rows.iter().enumerate() {
+        match row.as_array() {
+            Some(cells) if cells.len() == width => {}
+            Some(cells) => problems.push(format!(
+                "Row {} has {} cells, expected {width}",
+                r + 1,
+                cells.len()
+            )),
+            None => problems.push(format!("Row {} is not a list of cells", r + 1)),
+        }
+    }
+    let in_grid = |cell: &Value| -> bool {
+        let rc = cell.as_array().map(|rc| (rc.first(), rc.get(1)));
+        match rc {
+            Some((Some(r), Some(c))) => matches!(
+                (r.as_f64(), c.as_f64()),
+                (Some(r), Some(c)) if r >= 0.0 && c >= 0.0 && (r as usize) < rows.len() && (c as usize) < width
+            ),
+            _ => false,
+        }
+    };
+    for (key, what) in [("re", "Region"), ("ca", "Cage")] {
+        for (i, entry) in value
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            // Regions are lists of cells, cages keep theirs in "ce"
+            let cells = if key == "re" {
+                entry.as_array()
+            } else {
+                entry.get("ce").and_then(Value::as_array)
+            };
+            if cells.into_iter().flatten().any(|cell| !in_grid(cell)) {
+                problems.push(format!("{what} {} has cells outside the grid", i + 1));
+            }
+        }
+    }
+    problems
+}
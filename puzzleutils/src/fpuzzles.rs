@@ -98,3 +98,22 @@ pub fn metadata(value: &Value) -> GridMetadata {
         has_solution,
     }
 }
+
+/// Extract the embedded solution, if there is one, as a flat row-major
+/// list of digits.  Cells without a known digit are given as zero.
+pub fn solution(value: &Value) -> Option<Vec<u8>> {
+    value
+        .get("solution")
+        .and_then(Value::as_array)
+        .map(|cells| {
+            cells
+                .iter()
+                .map(|cell| {
+                    cell.as_u64()
+                        .or_else(|| cell.as_str().and_then(|s| s.parse().ok()))
+                        .and_then(|d| u8::try_from(d).ok())
+                        .unwrap_or(0)
+                })
+                .collect()
+        })
+}
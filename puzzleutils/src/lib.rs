@@ -1,10 +1,16 @@
 //! Puzzle utilities for Linkdoku
 
+use serde::Serialize;
+
+pub mod convert;
 pub mod ctc;
+pub mod formats;
 pub mod fpuzzles;
+pub mod solver;
+pub mod variants;
 pub mod xform;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct GridMetadata {
     pub title: Option<String>,
     pub author: Option<String>,
@@ -12,3 +18,62 @@ pub struct GridMetadata {
     pub rows_cols: Option<(usize, usize)>,
     pub has_solution: bool,
 }
+
+/// Count the cells in `candidate` which disagree with `solution`
+///
+/// Cells whose solution digit is unknown (zero) are not counted, so a
+/// solution with no known digits at all finds nothing wrong with any grid
+/// and should not be checked against.  If the grids are not the same size
+/// then we cannot compare them, so `None` is returned.
+pub fn count_wrong_cells(solution: &[u8], candidate: &[Option<u8>]) -> Option<usize> {
+    if solution.len() != candidate.len() {
+        return None;
+    }
+    Some(
+        solution
+            .iter()
+            .zip(candidate)
+            .filter(|(&want, &got)| want != 0 && got != Some(want))
+            .count(),
+    )
+}
+
+/// The (height, width) of the boxes in a regular grid of the given size
+pub(crate) fn box_shape(size: usize) -> (usize, usize) {
+    let height = (1..=size)
+        .filter(|&h| size.is_multiple_of(h) && h * h <= size)
+        .max()
+        .unwrap_or(1);
+    (height, size / height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_wrong_cells() {
+        let solution = [1, 2, 3, 4];
+        assert_eq!(
+            count_wrong_cells(&solution, &[Some(1), Some(2), Some(3), Some(4)]),
+            Some(0)
+        );
+        assert_eq!(
+            count_wrong_cells(&solution, &[Some(1), Some(3), None, Some(4)]),
+            Some(2)
+        );
+        assert_eq!(count_wrong_cells(&solution, &[None; 4]), Some(4));
+    }
+
+    #[test]
+    fn ignores_unknown_solution_cells() {
+        assert_eq!(count_wrong_cells(&[0, 2], &[Some(9), None]), Some(1));
+        assert_eq!(count_wrong_cells(&[0, 0], &[None, None]), Some(0));
+    }
+
+    #[test]
+    fn mismatched_sizes() {
+        assert_eq!(count_wrong_cells(&[1, 2], &[Some(1)]), None);
+        assert_eq!(count_wrong_cells(&[], &[Some(1)]), None);
+    }
+}
@@ -0,0 +1,785 @@
+//! Sudoku solver and uniqueness checker
+//!
+//! This works on f-puzzles JSON data and understands the common constraints
+//! which people use when setting variant sudoku: classic rules (including
+//! irregular regions), killer cages, thermometers, arrows, kropki dots, XV,
+//! anti-knight, anti-king, diagonals, extra regions, odd/even cells, and
+//! non-consecutive.  The solver is a simple constraint propagation and
+//! backtracking search, which is more than enough to tell a setter whether
+//! their puzzle has zero, one, or multiple solutions.
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::box_shape;
+
+/// The largest grid we are willing to try and solve
+const MAX_SIZE: usize = 16;
+
+/// The default number of search nodes we will explore before giving up
+pub const DEFAULT_NODE_LIMIT: usize = 100_000;
+
+/// Constraints which f-puzzles knows about but which we cannot reason about.
+/// If any of these are present then we cannot claim to have verified anything.
+const UNSUPPORTED_CONSTRAINTS: &[&str] = &[
+    "minimum",
+    "maximum",
+    "rowindexer",
+    "columnindexer",
+    "boxindexer",
+    "palindrome",
+    "littlekillersum",
+    "sandwichsum",
+    "clone",
+    "betweenline",
+    "quadruple",
+    "disjointgroups",
+];
+
+/// The outcome of solving a puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solutions {
+    /// The puzzle is broken, there is no solution
+    None,
+    /// The puzzle has exactly one solution, given in row-major order
+    Unique(Vec<u8>),
+    /// The puzzle has more than one solution
+    Multiple,
+}
+
+/// Reasons why we could not solve a puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverError {
+    /// The grid size was missing or not something we can solve
+    BadSize,
+    /// A cell reference (e.g. `R1C1`) could not be understood
+    BadCell(String),
+    /// A constraint value could not be understood
+    BadValue(&'static str),
+    /// The puzzle uses a constraint we do not understand
+    Unsupported(String),
+    /// We ran out of search budget before finishing
+    TooHard,
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::BadSize => write!(f, "Unsupported grid size"),
+            SolverError::BadCell(cell) => write!(f, "Bad cell reference: {cell}"),
+            SolverError::BadValue(what) => write!(f, "Bad value in {what} constraint"),
+            SolverError::Unsupported(what) => write!(f, "Unsupported constraint: {what}"),
+            SolverError::TooHard => write!(f, "Gave up, the puzzle is too hard to check"),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+/// Solve the given f-puzzles data with the default search budget
+pub fn solve(value: &Value) -> Result<Solutions, SolverError> {
+    solve_with_limit(value, DEFAULT_NODE_LIMIT)
+}
+
+/// Solve the given f-puzzles data, exploring at most `limit` search nodes
+pub fn solve_with_limit(value: &Value, limit: usize) -> Result<Solutions, SolverError> {
+    let puzzle = Puzzle::from_fpuzzles(value)?;
+    let mut found = vec![];
+    let mut budget = limit;
+    puzzle.search(puzzle.initial.clone(), &mut found, &mut budget)?;
+    Ok(match found.len() {
+        0 => Solutions::None,
+        1 => Solutions::Unique(found.pop().unwrap()),
+        _ => Solutions::Multiple,
+    })
+}
+
+/// Embed the given solution into the f-puzzles data
+///
+/// f-puzzles stores solutions as a flat row-major array of digits.
+pub fn embed_solution(value: &mut Value, solution: &[u8]) {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert(
+            "solution".into(),
+            Value::Array(solution.iter().copied().map(Value::from).collect()),
+        );
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Relation {
+    Less,
+    Difference(u32),
+    Ratio(u32),
+    Sum(u32),
+    NotDifference(u32),
+    NotRatio(u32),
+    NotSum(u32),
+}
+
+impl Relation {
+    fn allows(self, x: u32, y: u32) -> bool {
+        match self {
+            Relation::Less => x < y,
+            Relation::Difference(d) => x.abs_diff(y) == d,
+            Relation::Ratio(r) => x == y * r || y == x * r,
+            Relation::Sum(s) => x + y == s,
+            Relation::NotDifference(d) => x.abs_diff(y) != d,
+            Relation::NotRatio(r) => x != y * r && y != x * r,
+            Relation::NotSum(s) => x + y != s,
+        }
+    }
+}
+
+struct Pair {
+    a: usize,
+    b: usize,
+    relation: Relation,
+}
+
+/// Bounds are worked out in `i64` since a pill bulb's place values alone
+/// can come close to `i32::MAX`
+struct LinearSum {
+    terms: Vec<(usize, i64)>,
+    total: i64,
+}
+
+struct House {
+    cells: Vec<usize>,
+    complete: bool,
+}
+
+struct Puzzle {
+    size: usize,
+    initial: Vec<u32>,
+    houses: Vec<House>,
+    peers: Vec<Vec<usize>>,
+    pairs: Vec<Pair>,
+    sums: Vec<LinearSum>,
+}
+
+fn digits(mask: u32) -> impl Iterator<Item = u32> {
+    (1..=MAX_SIZE as u32).filter(move |d| mask & (1 << d) != 0)
+}
+
+fn term_bounds(mask: u32, coeff: i64) -> (i64, i64) {
+    let lo = i64::from(digits(mask).next().unwrap_or(0)) * coeff;
+    let hi = i64::from(digits(mask).last().unwrap_or(0)) * coeff;
+    (lo.min(hi), lo.max(hi))
+}
+
+fn is_active(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Array(arr) => !arr.is_empty(),
+        _ => true,
+    }
+}
+
+fn entries<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn number_of(value: Option<&Value>) -> Option<u32> {
+    match value? {
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+impl Puzzle {
+    fn from_fpuzzles(value: &Value) -> Result<Self, SolverError> {
+        for key in UNSUPPORTED_CONSTRAINTS {
+            if value.get(key).map(is_active).unwrap_or(false) {
+                return Err(SolverError::Unsupported(key.to_string()));
+            }
+        }
+
+        let size = value
+            .get("size")
+            .and_then(Value::as_u64)
+            .and_then(|s| usize::try_from(s).ok())
+            .filter(|s| (1..=MAX_SIZE).contains(s))
+            .ok_or(SolverError::BadSize)?;
+        let all = ((1u32 << (size + 1)) - 1) & !1;
+
+        let mut puzzle = Puzzle {
+            size,
+            initial: vec![all; size * size],
+            houses: vec![],
+            peers: vec![vec![]; size * size],
+            pairs: vec![],
+            sums: vec![],
+        };
+
+        let cell = |s: &Value| -> Result<usize, SolverError> {
+            let s = s.as_str().ok_or(SolverError::BadValue("cell"))?;
+            let upper = s.to_ascii_uppercase();
+            let (row, col) = upper
+                .strip_prefix('R')
+                .and_then(|rest| rest.split_once('C'))
+                .ok_or_else(|| SolverError::BadCell(s.to_string()))?;
+            let row: usize = row
+                .parse()
+                .map_err(|_| SolverError::BadCell(s.to_string()))?;
+            let col: usize = col
+                .parse()
+                .map_err(|_| SolverError::BadCell(s.to_string()))?;
+            if row == 0 || col == 0 || row > size || col > size {
+                return Err(SolverError::BadCell(s.to_string()));
+            }
+            Ok((row - 1) * size + (col - 1))
+        };
+        let cells = |v: Option<&Value>| -> Result<Vec<usize>, SolverError> {
+            v.and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(cell)
+                .collect()
+        };
+
+        // Givens and regions
+        let (box_height, box_width) = box_shape(size);
+        let mut regions = vec![vec![]; size];
+        for row in 0..size {
+            for col in 0..size {
+                let idx = row * size + col;
+                let entry = value.get("grid").and_then(|g| g.get(row)?.get(col));
+                let region = entry
+                    .and_then(|e| e.get("region"))
+                    .and_then(Value::as_u64)
+                    .map(|r| r as usize)
+                    .unwrap_or((row / box_height) * box_height + col / box_width);
+                if region < size {
+                    regions[region].push(idx);
+                }
+                let given = entry
+                    .and_then(|e| e.get("given"))
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                if given {
+                    let digit = number_of(entry.and_then(|e| e.get("value")))
+                        .filter(|d| (1..=size as u32).contains(d))
+                        .ok_or(SolverError::BadValue("given"))?;
+                    puzzle.initial[idx] = 1 << digit;
+                }
+            }
+        }
+
+        for i in 0..size {
+            puzzle.add_house((0..size).map(|c| i * size + c).collect());
+            puzzle.add_house((0..size).map(|r| r * size + i).collect());
+        }
+        for region in regions {
+            puzzle.add_house(region);
+        }
+        if value.get("diagonal-").map(is_active).unwrap_or(false) {
+            puzzle.add_house((0..size).map(|i| i * size + i).collect());
+        }
+        if value.get("diagonal+").map(is_active).unwrap_or(false) {
+            puzzle.add_house((0..size).map(|i| (size - 1 - i) * size + i).collect());
+        }
+        for region in entries(value, "extraregion") {
+            puzzle.add_house(cells(region.get("cells"))?);
+        }
+
+        // Movement based constraints
+        let neighbours = |offsets: &[(isize, isize)]| {
+            let mut out = vec![];
+            for row in 0..size as isize {
+                for col in 0..size as isize {
+                    for (dr, dc) in offsets {
+                        let (r, c) = (row + dr, col + dc);
+                        if r >= 0 && c >= 0 && r < size as isize && c < size as isize {
+                            out.push((
+                                (row * size as isize + col) as usize,
+                                (r * size as isize + c) as usize,
+                            ));
+                        }
+                    }
+                }
+            }
+            out
+        };
+        if value.get("antiknight").map(is_active).unwrap_or(false) {
+            for (a, b) in neighbours(&[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ]) {
+                puzzle.add_peers(a, b);
+            }
+        }
+        if value.get("antiking").map(is_active).unwrap_or(false) {
+            for (a, b) in neighbours(&[(-1, -1), (-1, 1), (1, -1), (1, 1)]) {
+                puzzle.add_peers(a, b);
+            }
+        }
+        let orthogonal = neighbours(&[(0, 1), (1, 0)]);
+        if value.get("nonconsecutive").map(is_active).unwrap_or(false) {
+            for &(a, b) in &orthogonal {
+                puzzle.add_pair(a, b, Relation::NotDifference(1));
+            }
+        }
+
+        // Parity
+        for (key, keep) in [("odd", 1), ("even", 0)] {
+            for entry in entries(value, key) {
+                let idx = cell(entry.get("cell").ok_or(SolverError::BadValue(key))?)?;
+                puzzle.initial[idx] &= digits(all)
+                    .filter(|d| d % 2 == keep)
+                    .fold(0, |acc, d| acc | (1 << d));
+            }
+        }
+
+        // Killer cages
+        for cage in entries(value, "killercage") {
+            let members = cells(cage.get("cells"))?;
+            for (i, &a) in members.iter().enumerate() {
+                for &b in &members[i + 1..] {
+                    puzzle.add_peers(a, b);
+                }
+            }
+            match cage.get("value") {
+                None | Some(Value::Null) => {}
+                Some(Value::String(s)) if s.trim().is_empty() => {}
+                total => {
+                    let total = number_of(total).ok_or(SolverError::BadValue("killercage"))?;
+                    puzzle.sums.push(LinearSum {
+                        terms: members.into_iter().map(|c| (c, 1)).collect(),
+                        total: i64::from(total),
+                    });
+                }
+            }
+        }
+
+        // Thermometers
+        for thermo in entries(value, "thermometer") {
+            for line in entries(thermo, "lines") {
+                let line = cells(Some(line))?;
+                for step in line.windows(2) {
+                    puzzle.add_pair(step[0], step[1], Relation::Less);
+                }
+            }
+        }
+
+        // Arrows; a multi-cell (pill) bulb is read as a number in reading
+        // order, as f-puzzles does, rather than being summed.
+        for arrow in entries(value, "arrow") {
+            let mut bulb = cells(arrow.get("cells"))?;
+            bulb.sort_unstable();
+            bulb.dedup();
+            let mut terms: Vec<(usize, i64)> = bulb
+                .iter()
+                .rev()
+                .zip(std::iter::successors(Some(1i32), |place| {
+                    place.checked_mul(10)
+                }))
+                .map(|(&c, place)| (c, i64::from(place)))
+                .collect();
+            if terms.len() != bulb.len() {
+                return Err(SolverError::BadValue("arrow"));
+            }
+            for line in entries(arrow, "lines") {
+                for c in cells(Some(line))? {
+                    if !bulb.contains(&c) {
+                        terms.push((c, -1));
+                    }
+                }
+            }
+            puzzle.sums.push(LinearSum { terms, total: 0 });
+        }
+
+        // Dots and XV
+        let mut marked = vec![];
+        for (key, default) in [("difference", 1), ("ratio", 2)] {
+            for dot in entries(value, key) {
+                let pair = cells(dot.get("cells"))?;
+                if pair.len() != 2 {
+                    return Err(SolverError::BadValue(key));
+                }
+                let amount = number_of(dot.get("value")).unwrap_or(default);
+                let relation = if key == "difference" {
+                    Relation::Difference(amount)
+                } else {
+                    Relation::Ratio(amount)
+                };
+                puzzle.add_pair(pair[0], pair[1], relation);
+                marked.push((key, pair[0].min(pair[1]), pair[0].max(pair[1])));
+            }
+        }
+        for xv in entries(value, "xv") {
+            let pair = cells(xv.get("cells"))?;
+            if pair.len() != 2 {
+                return Err(SolverError::BadValue("xv"));
+            }
+            let total = match xv.get("value").and_then(Value::as_str) {
+                Some("X") | Some("x") => 10,
+                Some("V") | Some("v") => 5,
+                _ => return Err(SolverError::BadValue("xv")),
+            };
+            puzzle.add_pair(pair[0], pair[1], Relation::Sum(total));
+            marked.push(("xv", pair[0].min(pair[1]), pair[0].max(pair[1])));
+        }
+
+        // Negative constraints apply to every orthogonal pair without a marker
+        for negative in entries(value, "negative").filter_map(Value::as_str) {
+            let relations: &[Relation] = match negative {
+                "difference" => &[Relation::NotDifference(1)],
+                "ratio" => &[Relation::NotRatio(2)],
+                "xv" => &[Relation::NotSum(5), Relation::NotSum(10)],
+                other => return Err(SolverError::Unsupported(format!("negative {other}"))),
+            };
+            for &(a, b) in &orthogonal {
+                if !marked.contains(&(negative, a, b)) {
+                    for relation in relations {
+                        puzzle.add_pair(a, b, *relation);
+                    }
+                }
+            }
+        }
+
+        Ok(puzzle)
+    }
+
+    fn add_peers(&mut self, a: usize, b: usize) {
+        if a != b && !self.peers[a].contains(&b) {
+            self.peers[a].push(b);
+            self.peers[b].push(a);
+        }
+    }
+
+    fn add_house(&mut self, cells: Vec<usize>) {
+        for (i, &a) in cells.iter().enumerate() {
+            for &b in &cells[i + 1..] {
+                self.add_peers(a, b);
+            }
+        }
+        let complete = cells.len() == self.size;
+        self.houses.push(House { cells, complete });
+    }
+
+    fn add_pair(&mut self, a: usize, b: usize, relation: Relation) {
+        self.pairs.push(Pair { a, b, relation });
+    }
+
+    /// Reduce candidates as far as simple logic allows.  Returns false if
+    /// a contradiction was found.
+    fn propagate(&self, cands: &mut [u32]) -> bool {
+        loop {
+            let mut changed = false;
+
+            // Naked singles
+            for cell in 0..cands.len() {
+                let mask = cands[cell];
+                if mask == 0 {
+                    return false;
+                }
+                if mask.count_ones() == 1 {
+                    for &peer in &self.peers[cell] {
+                        if cands[peer] & mask != 0 {
+                            cands[peer] &= !mask;
+                            if cands[peer] == 0 {
+                                return false;
+                            }
+                            changed = true;
+                        }
+                    }
+                }
+            }
+
+            // Hidden singles
+            for house in self.houses.iter().filter(|h| h.complete) {
+                for digit in 1..=self.size {
+                    let bit = 1 << digit;
+                    let mut places = house.cells.iter().filter(|&&c| cands[c] & bit != 0);
+                    match (places.next(), places.next()) {
+                        (None, _) => return false,
+                        (Some(&only), None) if cands[only] != bit => {
+                            cands[only] = bit;
+                            changed = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            // Pairwise relations
+            for pair in &self.pairs {
+                let (ma, mb) = (cands[pair.a], cands[pair.b]);
+                let na = digits(ma)
+                    .filter(|&x| digits(mb).any(|y| pair.relation.allows(x, y)))
+                    .fold(0, |acc, d| acc | (1 << d));
+                let nb = digits(mb)
+                    .filter(|&y| digits(na).any(|x| pair.relation.allows(x, y)))
+                    .fold(0, |acc, d| acc | (1 << d));
+                if na == 0 || nb == 0 {
+                    return false;
+                }
+                if na != ma || nb != mb {
+                    cands[pair.a] = na;
+                    cands[pair.b] = nb;
+                    changed = true;
+                }
+            }
+
+            // Sums, by bounds reasoning
+            for sum in &self.sums {
+                let bounds: Vec<(i64, i64)> = sum
+                    .terms
+                    .iter()
+                    .map(|&(cell, coeff)| term_bounds(cands[cell], coeff))
+                    .collect();
+                let (min, max) = bounds
+                    .iter()
+                    .fold((0, 0), |(a, b), (lo, hi)| (a + lo, b + hi));
+                if sum.total < min || sum.total > max {
+                    return false;
+                }
+                for (&(cell, coeff), (lo, hi)) in sum.terms.iter().zip(bounds) {
+                    let term_min = sum.total - (max - hi);
+                    let term_max = sum.total - (min - lo);
+                    let mask = cands[cell];
+                    let new = digits(mask)
+                        .filter(|&d| (term_min..=term_max).contains(&(i64::from(d) * coeff)))
+                        .fold(0, |acc, d| acc | (1 << d));
+                    if new == 0 {
+                        return false;
+                    }
+                    if new != mask {
+                        cands[cell] = new;
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                return true;
+            }
+        }
+    }
+
+    fn search(
+        &self,
+        mut cands: Vec<u32>,
+        found: &mut Vec<Vec<u8>>,
+        budget: &mut usize,
+    ) -> Result<(), SolverError> {
+        if *budget == 0 {
+            return Err(SolverError::TooHard);
+        }
+        *budget -= 1;
+
+        if !self.propagate(&mut cands) {
+            return Ok(());
+        }
+
+        let choice = (0..cands.len())
+            .filter(|&c| cands[c].count_ones() > 1)
+            .min_by_key(|&c| cands[c].count_ones());
+
+        match choice {
+            None => {
+                found.push(cands.iter().map(|m| m.trailing_zeros() as u8).collect());
+            }
+            Some(cell) => {
+                for digit in digits(cands[cell]) {
+                    let mut next = cands.clone();
+                    next[cell] = 1 << digit;
+                    self.search(next, found, budget)?;
+                    if found.len() > 1 {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A solved 4x4 grid with 2x2 boxes
+    const GRID: [[u8; 4]; 4] = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+
+    /// The cells of an unavoidable rectangle in `GRID`; with these blanked
+    /// the grid has exactly two solutions.
+    const RECTANGLE: [(usize, usize); 4] = [(0, 0), (0, 1), (2, 0), (2, 1)];
+
+    fn puzzle(blanks: &[(usize, usize)]) -> Value {
+        let grid: Vec<Vec<Value>> = GRID
+            .iter()
+            .enumerate()
+            .map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(c, &digit)| {
+                        if blanks.contains(&(r, c)) {
+                            json!({})
+                        } else {
+                            json!({ "value": digit, "given": true })
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        json!({ "size": 4, "grid": grid })
+    }
+
+    fn with(mut value: Value, key: &str, entries: Value) -> Value {
+        value[key] = entries;
+        value
+    }
+
+    fn unique(value: &Value) -> Vec<u8> {
+        match solve(value) {
+            Ok(Solutions::Unique(solution)) => solution,
+            other => panic!("Expected a unique solution, got {other:?}"),
+        }
+    }
+
+    fn flat(grid: [[u8; 4]; 4]) -> Vec<u8> {
+        grid.iter().flatten().copied().collect()
+    }
+
+    #[test]
+    fn unique_solution() {
+        assert_eq!(unique(&puzzle(&[(0, 0), (1, 2), (3, 3)])), flat(GRID));
+    }
+
+    #[test]
+    fn multiple_solutions() {
+        assert_eq!(solve(&puzzle(&RECTANGLE)), Ok(Solutions::Multiple));
+        assert_eq!(
+            solve(&json!({ "size": 4, "grid": [] })),
+            Ok(Solutions::Multiple)
+        );
+    }
+
+    #[test]
+    fn no_solution() {
+        let mut value = puzzle(&[(0, 0)]);
+        value["grid"][0][1] = json!({ "value": 1, "given": true });
+        assert_eq!(solve(&value), Ok(Solutions::None));
+    }
+
+    #[test]
+    fn rejects_unsupported() {
+        let value = with(puzzle(&[]), "sandwichsum", json!([{ "cell": "R1C1" }]));
+        assert_eq!(
+            solve(&value),
+            Err(SolverError::Unsupported("sandwichsum".into()))
+        );
+        assert_eq!(solve(&json!({ "size": 0 })), Err(SolverError::BadSize));
+    }
+
+    #[test]
+    fn runs_out_of_budget() {
+        let value = json!({ "size": 9, "grid": [] });
+        assert_eq!(solve_with_limit(&value, 3), Err(SolverError::TooHard));
+    }
+
+    /// The rectangle, swapped, so that the top row starts 2 1
+    fn swapped() -> Vec<u8> {
+        let mut grid = GRID;
+        grid[0][0] = 2;
+        grid[0][1] = 1;
+        grid[2][0] = 1;
+        grid[2][1] = 2;
+        flat(grid)
+    }
+
+    #[test]
+    fn thermometer_breaks_rectangle() {
+        let value = with(
+            puzzle(&RECTANGLE),
+            "thermometer",
+            json!([{ "lines": [["R1C2", "R1C1"]] }]),
+        );
+        assert_eq!(unique(&value), swapped());
+    }
+
+    #[test]
+    fn kropki_breaks_rectangle() {
+        let white = with(
+            puzzle(&RECTANGLE),
+            "difference",
+            json!([{ "cells": ["R1C1", "R2C1"] }]),
+        );
+        assert_eq!(unique(&white), swapped());
+
+        let black = with(
+            puzzle(&RECTANGLE),
+            "ratio",
+            json!([{ "cells": ["R1C2", "R2C2"] }]),
+        );
+        assert_eq!(unique(&black), flat(GRID));
+    }
+
+    #[test]
+    fn negative_kropki() {
+        // GRID has a given 3 next to a given 4 without a dot between them
+        let value = with(puzzle(&RECTANGLE), "negative", json!(["difference"]));
+        assert_eq!(solve(&value), Ok(Solutions::None));
+    }
+
+    #[test]
+    fn arrow_breaks_rectangle() {
+        let value = with(
+            puzzle(&RECTANGLE),
+            "arrow",
+            json!([{ "cells": ["R2C4"], "lines": [["R2C4", "R1C1"]] }]),
+        );
+        assert_eq!(unique(&value), swapped());
+    }
+
+    #[test]
+    fn pill_arrow_is_a_number() {
+        // The pill reads 12 in the original grid and 21 when swapped, and
+        // the line always sums to 12.
+        for bulb in [["R1C1", "R1C2"], ["R1C2", "R1C1"]] {
+            let value = with(
+                puzzle(&RECTANGLE),
+                "arrow",
+                json!([{ "cells": bulb, "lines": [["R1C2", "R2C2", "R3C3", "R4C1"]] }]),
+            );
+            assert_eq!(unique(&value), flat(GRID));
+        }
+    }
+
+    #[test]
+    fn long_pill_arrows() {
+        // A ten cell bulb reads at least 10^9, which no line can reach, and
+        // an eleven cell one cannot be read at all
+        let bulb: Vec<String> = (1..=9)
+            .map(|c| format!("R1C{c}"))
+            .chain((1..=2).map(|c| format!("R2C{c}")))
+            .collect();
+        let arrow = |cells: &[String]| {
+            json!({
+                "size": 9,
+                "grid": [],
+                "arrow": [{ "cells": cells, "lines": [["R2C9", "R3C9", "R4C9"]] }],
+            })
+        };
+        assert_eq!(solve(&arrow(&bulb[..10])), Ok(Solutions::None));
+        assert_eq!(solve(&arrow(&bulb)), Err(SolverError::BadValue("arrow")));
+    }
+}
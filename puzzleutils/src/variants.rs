@@ -0,0 +1,251 @@
+//! Variant detection for puzzle data
+//!
+//! Given f-puzzles or CtC JSON we try to work out which `variant:*` tags
+//! apply to the puzzle.  f-puzzles names its constraints so we can rely on
+//! the keys present.  CtC data mostly draws constraints as lines and shapes,
+//! so there we fall back on the rules text to spot the variants in use.
+
+use serde_json::Value;
+
+/// f-puzzles constraint keys and the tags they imply
+const FPUZZLES_KEYS: &[(&str, &str)] = &[
+    ("thermometer", "variant:Thermo"),
+    ("arrow", "variant:Arrow"),
+    ("killercage", "variant:Killer"),
+    ("littlekillersum", "variant:Little Killer"),
+    ("difference", "variant:Kropki"),
+    ("ratio", "variant:Kropki"),
+    ("xv", "variant:XV"),
+    ("antiknight", "variant:Anti-Knight"),
+    ("antiking", "variant:Anti-King"),
+    ("sandwichsum", "variant:Sandwich"),
+    ("nonconsecutive", "variant:Non-Consecutive"),
+    ("diagonal+", "variant:Sudoku-X"),
+    ("diagonal-", "variant:Sudoku-X"),
+    ("odd", "variant:Odd-Even"),
+    ("even", "variant:Odd-Even"),
+    ("quadruple", "variant:Quadruples"),
+    ("betweenline", "variant:Between Lines"),
+    ("clone", "variant:Clones"),
+    ("palindrome", "variant:Palindromes"),
+    ("minimum", "variant:Min/Max"),
+    ("maximum", "variant:Min/Max"),
+    ("extraregion", "variant:Extra regions"),
+    ("rowindexer", "variant:Indexing"),
+    ("columnindexer", "variant:Indexing"),
+    ("boxindexer", "variant:Indexing"),
+];
+
+/// Phrases in rules text and the tags they imply.  These are checked
+/// against lowercased rules with hyphens treated as spaces.
+const RULES_PHRASES: &[(&str, &str)] = &[
+    ("thermo", "variant:Thermo"),
+    ("double arrow", "variant:Double Arrow"),
+    ("arrow", "variant:Arrow"),
+    ("little killer", "variant:Little Killer"),
+    ("killer", "variant:Killer"),
+    ("renban", "variant:Renban"),
+    ("kropki", "variant:Kropki"),
+    ("sandwich", "variant:Sandwich"),
+    ("non consecutive", "variant:Non-Consecutive"),
+    ("nonconsecutive", "variant:Non-Consecutive"),
+    ("german whisper", "variant:German Whispers"),
+    ("dutch whisper", "variant:Dutch Whispers"),
+    ("chinese whisper", "variant:Chinese Whispers"),
+    ("entropic", "variant:Entropic Lines"),
+    ("global entropy", "variant:Global Entropy"),
+    ("fog of war", "variant:Fog of War"),
+    ("anti knight", "variant:Anti-Knight"),
+    ("antiknight", "variant:Anti-Knight"),
+    ("anti king", "variant:Anti-King"),
+    ("antiking", "variant:Anti-King"),
+    ("palindrome", "variant:Palindromes"),
+    ("equal sum line", "variant:Equal Sum Lines"),
+    ("ten line", "variant:Ten-Lines"),
+    ("10 line", "variant:Ten-Lines"),
+    ("windoku", "variant:Windoku"),
+    ("x sum", "variant:X-Sums"),
+    ("skyscraper", "variant:Skyscraper"),
+    ("doubler", "variant:Doublers"),
+    ("japanese sum", "variant:Japanese sums"),
+    ("yin yang", "variant:Yin-Yang"),
+    ("look and say", "variant:Look and Say"),
+    ("knightmare", "variant:Knightmare"),
+    ("between line", "variant:Between Lines"),
+    ("quadruple", "variant:Quadruples"),
+    ("indexing", "variant:Indexing"),
+    ("irregular", "variant:Irregular"),
+    ("clone", "variant:Clones"),
+];
+
+fn is_active(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Array(arr) => !arr.is_empty(),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+fn from_rules(rules: &str, out: &mut Vec<&'static str>) {
+    let rules = rules.to_lowercase().replace('-', " ");
+    let mut remaining = rules.clone();
+    for (phrase, tag) in RULES_PHRASES {
+        if remaining.contains(phrase) {
+            out.push(tag);
+            // Stop "double arrow" also counting as "arrow" and so on
+            remaining = remaining.replace(phrase, " ");
+        }
+    }
+    if rules
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|w| w == "xv")
+    {
+        out.push("variant:XV");
+    }
+}
+
+fn fpuzzles_tags(value: &Value, out: &mut Vec<&'static str>) {
+    for (key, tag) in FPUZZLES_KEYS {
+        if value.get(key).map(is_active).unwrap_or(false) {
+            out.push(tag);
+        }
+    }
+
+    let irregular = value
+        .get("grid")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_array)
+        .flatten()
+        .any(|cell| cell.get("region").map(|r| !r.is_null()).unwrap_or(false));
+    if irregular {
+        out.push("variant:Irregular");
+    }
+
+    if let Some(rules) = value.get("ruleset").and_then(Value::as_str) {
+        from_rules(rules, out);
+    }
+}
+
+fn ctc_tags(value: &Value, out: &mut Vec<&'static str>) {
+    let cages = value.get("ca").and_then(Value::as_array);
+    let killer = cages.into_iter().flatten().any(|cage| {
+        // Metadata is stored in cages as "key: value", real cages are numbers or blank
+        cage.get("v")
+            .and_then(Value::as_str)
+            .map(|v| !v.contains(": "))
+            .unwrap_or(false)
+    });
+    if killer {
+        out.push("variant:Killer");
+    }
+
+    if let Some(meta) = cages.into_iter().flatten().find_map(|cage| {
+        cage.get("v")
+            .and_then(Value::as_str)
+            .and_then(|v| v.strip_prefix("rules: "))
+    }) {
+        from_rules(meta, out);
+    }
+}
+
+/// Suggest `variant:*` tag names for the given puzzle data
+///
+/// Both f-puzzles and CtC JSON are understood.  If we find no variant
+/// constraints at all in f-puzzles data then we suggest `variant:Classic`.
+pub fn suggest_tags(value: &Value) -> Vec<&'static str> {
+    let mut out = vec![];
+    if value.get("grid").is_some() {
+        fpuzzles_tags(value, &mut out);
+        if out.is_empty() {
+            out.push("variant:Classic");
+        }
+    } else {
+        ctc_tags(value, &mut out);
+    }
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rules(text: &str) -> Vec<&'static str> {
+        let mut out = vec![];
+        from_rules(text, &mut out);
+        out
+    }
+
+    #[test]
+    fn fpuzzles_keys() {
+        let value = json!({
+            "size": 9,
+            "grid": [],
+            "thermometer": [{ "lines": [["R1C1", "R1C2"]] }],
+            "difference": [{ "cells": ["R1C1", "R2C1"] }],
+            "ratio": [{ "cells": ["R1C2", "R2C2"] }],
+            "antiknight": true,
+            "antiking": false,
+            "arrow": [],
+        });
+        assert_eq!(
+            suggest_tags(&value),
+            ["variant:Anti-Knight", "variant:Kropki", "variant:Thermo"]
+        );
+    }
+
+    #[test]
+    fn fpuzzles_classic_and_irregular() {
+        let value = json!({ "size": 9, "grid": [], "killercage": [], "antiking": false });
+        assert_eq!(suggest_tags(&value), ["variant:Classic"]);
+
+        let value = json!({ "size": 4, "grid": [[{ "region": 1 }, {}], [{}, {}]] });
+        assert_eq!(suggest_tags(&value), ["variant:Irregular"]);
+
+        let value = json!({ "size": 9, "grid": [], "ruleset": "Digits along a renban line..." });
+        assert_eq!(suggest_tags(&value), ["variant:Renban"]);
+    }
+
+    #[test]
+    fn ctc_cages() {
+        let value = json!({
+            "cells": [],
+            "ca": [
+                { "v": "title: Not a killer" },
+                { "v": "rules: Normal sudoku rules apply.  German whispers..." },
+            ],
+        });
+        assert_eq!(suggest_tags(&value), ["variant:German Whispers"]);
+
+        let value = json!({ "cells": [], "ca": [{ "v": "15" }] });
+        assert_eq!(suggest_tags(&value), ["variant:Killer"]);
+
+        // CtC data without any hints is not assumed to be classic
+        assert!(suggest_tags(&json!({ "cells": [] })).is_empty());
+    }
+
+    #[test]
+    fn longer_phrases_win() {
+        assert_eq!(rules("Double arrows: ..."), ["variant:Double Arrow"]);
+        assert_eq!(rules("Little Killer clues ..."), ["variant:Little Killer"]);
+        assert_eq!(
+            rules("Double arrows, and normal arrows too"),
+            ["variant:Double Arrow", "variant:Arrow"]
+        );
+    }
+
+    #[test]
+    fn rules_spelling() {
+        assert_eq!(rules("ANTI-KNIGHT"), ["variant:Anti-Knight"]);
+        assert_eq!(rules("Non-consecutive"), ["variant:Non-Consecutive"]);
+        assert_eq!(rules("XV rules apply"), ["variant:XV"]);
+        assert!(rules("Chapter XVI").is_empty());
+    }
+}
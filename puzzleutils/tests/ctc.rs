@@ -0,0 +1,233 @@
+//! Round-trip and robustness tests for the CtC encoding
+
+use std::{fs, path::PathBuf};
+
+use proptest::prelude::*;
+use puzzleutils::{
+    convert,
+    ctc::{self, ParseError},
+    formats::{Decoded, Format},
+};
+use serde_json::{Number, Value};
+
+fn fixtures() -> impl Iterator<Item = (PathBuf, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ctc");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .expect("fixtures directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map(|e| e == "ctc").unwrap_or(false))
+        .collect();
+    paths.sort();
+    paths.into_iter().map(|path| {
+        let content = fs::read_to_string(&path).unwrap();
+        (path, content.trim().to_string())
+    })
+}
+
+#[test]
+fn fixtures_parse() {
+    for (path, content) in fixtures() {
+        let value = ctc::parse_str(&content)
+            .unwrap_or_else(|e| panic!("{} failed to parse: {e}", path.display()));
+        let metadata =
+            ctc::metadata(&value).unwrap_or_else(|e| panic!("{} has no grid: {e}", path.display()));
+        assert!(
+            metadata.rows_cols.is_some(),
+            "{} has no grid",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn fixtures_cover_drawings() {
+    // Between them the fixtures should exercise every kind of CtC drawing
+    let mut seen: Vec<&str> = vec![];
+    for (_, content) in fixtures() {
+        let value = ctc::parse_str(&content).unwrap();
+        for key in ["re", "ca", "l", "a", "o", "u"] {
+            let used = value
+                .get(key)
+                .and_then(Value::as_array)
+                .map(|items| !items.is_empty())
+                .unwrap_or(false);
+            if used && !seen.contains(&key) {
+                seen.push(key);
+            }
+        }
+    }
+    seen.sort();
+    assert_eq!(seen, ["a", "ca", "l", "o", "re", "u"]);
+}
+
+#[test]
+fn metadata_needs_a_grid() {
+    for value in [
+        serde_json::json!({}),
+        serde_json::json!({ "ce": [] }),
+        serde_json::json!({ "ce": [[]] }),
+        serde_json::json!({ "ce": [7] }),
+    ] {
+        assert!(
+            matches!(ctc::metadata(&value), Err(ParseError::NoGrid)),
+            "{value} gave a grid"
+        );
+    }
+    let metadata = ctc::metadata(&serde_json::json!({
+        "ce": [[{}, {}], [{}, {}]],
+        "ca": [{ "v": "title: Tiny" }, { "v": "solution: 1221" }],
+    }))
+    .unwrap();
+    assert_eq!(metadata.rows_cols, Some((2, 2)));
+    assert_eq!(metadata.title.as_deref(), Some("Tiny"));
+    assert!(metadata.has_solution);
+}
+
+#[test]
+fn fixtures_round_trip() {
+    for (path, content) in fixtures() {
+        let value = ctc::parse_str(&content).unwrap();
+        let reparsed = ctc::parse_str(&ctc::encode_str(&value)).unwrap();
+        assert_eq!(value, reparsed, "{} did not round-trip", path.display());
+    }
+}
+
+#[test]
+fn fixtures_convert_round_trip() {
+    for (path, content) in fixtures() {
+        let value = ctc::parse_str(&content).unwrap();
+        if value["id"] != "fpuzzles" {
+            continue;
+        }
+        let decoded = Decoded {
+            format: Format::Ctc,
+            value,
+        };
+        let fpuzzles = convert::convert(&decoded, Format::FPuzzles).unwrap();
+        assert!(
+            fpuzzles.unmapped.is_empty(),
+            "{} lost {:?}",
+            path.display(),
+            fpuzzles.unmapped
+        );
+        let decoded_fp = Decoded {
+            format: Format::FPuzzles,
+            value: fpuzzles.value,
+        };
+        let ctc = convert::convert(&decoded_fp, Format::Ctc).unwrap();
+        assert_eq!(
+            ctc.value,
+            decoded.value,
+            "{} did not survive f-puzzles",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn colour_shorthand() {
+    let value = ctc::parse("{c1:#0,c2:#F,c3:'#0'}").unwrap();
+    assert_eq!(value["c1"], "#000000");
+    assert_eq!(value["c2"], "#FFFFFF");
+    assert_eq!(value["c3"], "#0");
+    assert_eq!(ctc::encode(&value), "{c1:#0,c2:#F,c3:'#0'}");
+}
+
+#[test]
+fn empty_objects_in_arrays() {
+    let value = ctc::parse("[[,,],[{}],[]]").unwrap();
+    assert_eq!(value, serde_json::json!([[{}, {}, {}], [{}], []]));
+    assert_eq!(ctc::encode(&value), "[[,,],[{}],[]]");
+}
+
+#[test]
+fn escapes_and_unicode() {
+    let value = ctc::parse(r"{'a key':'it\'s\n\t\\ é 😀 ☺'}").unwrap();
+    assert_eq!(value["a key"], "it's\n\t\\ é 😀 ☺");
+}
+
+#[test]
+fn numbers() {
+    let value = ctc::parse("[0,-3,2.5,-0.125,1e-7,1.5E+20,18446744073709551615]").unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!([0, -3, 2.5, -0.125, 1e-7, 1.5e20, u64::MAX])
+    );
+    assert!(matches!(
+        ctc::parse("[1,-]"),
+        Err(ParseError::BadNumber { position: 3, .. })
+    ));
+}
+
+#[test]
+fn error_positions() {
+    let cases = [
+        ("{a:1,b:}", Some(7)),
+        ("{a:'unterminated", Some(16)),
+        ("[1,2]x", Some(5)),
+        ("{a:'\\q'}", Some(4)),
+        ("{a:#7}", Some(4)),
+        ("", Some(0)),
+    ];
+    for (input, position) in cases {
+        let err = ctc::parse(input).unwrap_err();
+        assert_eq!(err.position(), position, "{input:?} gave {err}");
+    }
+    assert_eq!(ParseError::LZStrEncodingError.position(), None);
+}
+
+fn arb_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("#000000".to_string()),
+        Just("#FFFFFF".to_string()),
+        "[#0-9A-F]{0,3}",
+        "[a-z ':\\\\,{}\\[\\]\n\t\r]{0,10}",
+        any::<String>(),
+    ]
+}
+
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_filter_map("finite", |f| Number::from_f64(f).map(Value::Number)),
+        arb_string().prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+            prop::collection::vec(
+                (prop_oneof!["[A-Za-z0-9_]{1,4}", arb_string()], inner),
+                0..8
+            )
+            .prop_map(|kv| Value::Object(kv.into_iter().collect())),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn round_trip(value in arb_json()) {
+        let encoded = ctc::encode(&value);
+        let reparsed = ctc::parse(&encoded)
+            .map_err(|e| TestCaseError::fail(format!("{encoded:?}: {e}")))?;
+        prop_assert_eq!(value, reparsed);
+    }
+
+    #[test]
+    fn compressed_round_trip(value in arb_json()) {
+        prop_assert_eq!(ctc::parse_str(&ctc::encode_str(&value)).unwrap(), value);
+    }
+
+    #[test]
+    fn parse_never_panics(input in any::<String>()) {
+        let _ = ctc::parse(&input);
+    }
+
+    #[test]
+    fn parse_never_panics_on_near_misses(input in "[\\[\\]{}',:#0Fatfnul\\\\e.+-9]{0,24}") {
+        let _ = ctc::parse(&input);
+    }
+}
@@ -3,5 +3,6 @@
 //! This likely uses a bunch of Bulma classes etc.
 
 pub mod editor;
+pub mod plain;
 pub mod render;
 pub mod xform;
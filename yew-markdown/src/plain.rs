@@ -0,0 +1,82 @@
+//! Plain text renderings of markdown
+//!
+//! These are for places which cannot show markdown at all, such as the
+//! metadata which link previews are built from.
+
+use pulldown_cmark::{Event, Parser, Tag};
+
+/// The text of some markdown, without any formatting, as a single line of at
+/// most `max_chars` characters.  Longer text is cut at a word boundary and
+/// ends with an ellipsis.
+pub fn excerpt(markdown: &str, max_chars: usize) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(chunk) | Event::Code(chunk) => text.push_str(&chunk),
+            Event::SoftBreak | Event::HardBreak | Event::Rule => text.push(' '),
+            Event::End(Tag::Paragraph | Tag::Heading(..) | Tag::Item | Tag::TableCell) => {
+                text.push(' ')
+            }
+            _ => {}
+        }
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+
+    // Leave room for the ellipsis
+    let limit = max_chars.saturating_sub(1);
+    let mut ret = String::new();
+    for word in text.split(' ') {
+        let needed = word.chars().count() + usize::from(!ret.is_empty());
+        if ret.chars().count() + needed > limit {
+            break;
+        }
+        if !ret.is_empty() {
+            ret.push(' ');
+        }
+        ret.push_str(word);
+    }
+    if ret.is_empty() {
+        // A single enormous word, such as a URL, is simply cut short
+        ret.extend(text.chars().take(limit));
+    }
+    ret.push('…');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_markdown() {
+        let markdown = "# Title\n\nSome *emphasis* and `code`, with a [link](https://example.com).\n\n- one\n- two\n";
+        assert_eq!(
+            excerpt(markdown, 200),
+            "Title Some emphasis and code, with a link. one two"
+        );
+    }
+
+    #[test]
+    fn exact_boundary() {
+        assert_eq!(excerpt("one two three", 13), "one two three");
+        assert_eq!(excerpt("one two three", 12), "one two…");
+        assert_eq!(excerpt("one two three", 8).chars().count(), 8);
+    }
+
+    #[test]
+    fn over_long_word() {
+        assert_eq!(excerpt("abcdefghij", 5), "abcd…");
+        assert_eq!(excerpt("abcdefghij klm", 5), "abcd…");
+    }
+
+    #[test]
+    fn multibyte_text() {
+        assert_eq!(excerpt("héllo wörld ünïcode", 19), "héllo wörld ünïcode");
+        assert_eq!(excerpt("héllo wörld ünïcode", 12), "héllo wörld…");
+        assert_eq!(excerpt("ääääää", 4), "äää…");
+    }
+}